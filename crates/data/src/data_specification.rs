@@ -1,21 +1,102 @@
+use std::fmt;
+
+use delegate::delegate;
+
 use merc_aterm::ATerm;
+use merc_aterm::ATermArgs;
+use merc_aterm::ATermIndex;
+use merc_aterm::ATermList;
 use merc_aterm::ATermRead;
+use merc_aterm::ATermRef;
 use merc_aterm::ATermStreamable;
 use merc_aterm::ATermWrite;
-use merc_aterm::Symbol;
+use merc_aterm::Markable;
+use merc_aterm::SymbolRef;
+use merc_aterm::Term;
+use merc_aterm::TermIterator;
+use merc_aterm::Transmutable;
+use merc_aterm::storage::Marker;
+use merc_macros::merc_derive_terms;
+use merc_macros::merc_term;
 use merc_utilities::MercError;
 
-/// TODO: Not yet useful, but can be used to read the data specification from a binary stream.
-#[derive(Default)]
-pub struct DataSpecification {}
+use crate::DataFunctionSymbol;
+use crate::DataVariable;
+use crate::SortExpression;
+use crate::is_data_equation;
+use crate::is_data_expression;
+use crate::is_data_function_symbol;
+use crate::is_sort_alias;
+use crate::is_sort_expression;
+
+/// The data specification of an mCRL2 specification, which consists of the
+/// user defined sorts, sort aliases, constructors, mappings and equations.
+///
+/// # Details
+///
+/// Structured sorts, such as `sort D = struct d1 | d2;`, are stored as
+/// aliases, and their constructors and mappings are derived by the mCRL2
+/// toolset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DataSpecification {
+    sorts: Vec<SortExpression>,
+    aliases: Vec<SortAlias>,
+    constructors: Vec<DataFunctionSymbol>,
+    mappings: Vec<DataFunctionSymbol>,
+    equations: Vec<DataEquation>,
+}
+
+impl DataSpecification {
+    /// Creates a data specification consisting of the given sections.
+    pub fn new(
+        sorts: Vec<SortExpression>,
+        aliases: Vec<SortAlias>,
+        constructors: Vec<DataFunctionSymbol>,
+        mappings: Vec<DataFunctionSymbol>,
+        equations: Vec<DataEquation>,
+    ) -> Self {
+        Self {
+            sorts,
+            aliases,
+            constructors,
+            mappings,
+            equations,
+        }
+    }
+
+    /// Returns the user defined sorts.
+    pub fn sorts(&self) -> &[SortExpression] {
+        &self.sorts
+    }
+
+    /// Returns the user defined sort aliases.
+    pub fn aliases(&self) -> &[SortAlias] {
+        &self.aliases
+    }
+
+    /// Returns the user defined constructors.
+    pub fn constructors(&self) -> &[DataFunctionSymbol] {
+        &self.constructors
+    }
+
+    /// Returns the user defined mappings.
+    pub fn mappings(&self) -> &[DataFunctionSymbol] {
+        &self.mappings
+    }
+
+    /// Returns the user defined equations.
+    pub fn equations(&self) -> &[DataEquation] {
+        &self.equations
+    }
+}
 
 impl ATermStreamable for DataSpecification {
     fn write<W: ATermWrite>(&self, writer: &mut W) -> Result<(), MercError> {
-        writer.write_aterm_iter((0..0).map(|_| ATerm::constant(&Symbol::new("unimportant", 0))))?;
-        writer.write_aterm_iter((0..0).map(|_| ATerm::constant(&Symbol::new("unimportant", 0))))?;
-        writer.write_aterm_iter((0..0).map(|_| ATerm::constant(&Symbol::new("unimportant", 0))))?;
-        writer.write_aterm_iter((0..0).map(|_| ATerm::constant(&Symbol::new("unimportant", 0))))?;
-        writer.write_aterm_iter((0..0).map(|_| ATerm::constant(&Symbol::new("unimportant", 0))))?;
+        writer.write_aterm_iter(self.sorts.iter().map(|term| term.protect()))?;
+        writer.write_aterm_iter(self.aliases.iter().map(|term| term.protect()))?;
+        writer.write_aterm_iter(self.constructors.iter().map(|term| term.protect()))?;
+        writer.write_aterm_iter(self.mappings.iter().map(|term| term.protect()))?;
+        writer.write_aterm_iter(self.equations.iter().map(|term| term.protect()))?;
 
         Ok(())
     }
@@ -24,13 +105,117 @@ impl ATermStreamable for DataSpecification {
     where
         Self: Sized,
     {
-        let _sorts: Result<Vec<ATerm>, MercError> = reader.read_aterm_iter()?.collect();
-        let _aliases: Result<Vec<ATerm>, MercError> = reader.read_aterm_iter()?.collect();
-        let _constructors: Result<Vec<ATerm>, MercError> = reader.read_aterm_iter()?.collect();
-        let _user_defined_mappings: Result<Vec<ATerm>, MercError> = reader.read_aterm_iter()?.collect();
-        let _user_defined_equations: Result<Vec<ATerm>, MercError> = reader.read_aterm_iter()?.collect();
+        let sorts = read_section(reader, "sort", |term| is_sort_expression(term))?;
+        let aliases = read_section(reader, "sort alias", |term| is_sort_alias(term))?;
+        let constructors = read_section(reader, "constructor", |term| is_data_function_symbol(term))?;
+        let mappings = read_section(reader, "mapping", |term| is_data_function_symbol(term))?;
+        let equations = read_section(reader, "equation", |term| is_data_equation(term))?;
 
-        // Ignore results for now.
-        Ok(DataSpecification {})
+        Ok(DataSpecification {
+            sorts,
+            aliases,
+            constructors,
+            mappings,
+            equations,
+        })
     }
 }
+
+/// Reads a list of terms from the reader, and checks that every term satisfies the given recogniser.
+fn read_section<R: ATermRead, T: From<ATerm>>(
+    reader: &mut R,
+    name: &str,
+    recogniser: impl Fn(&ATerm) -> bool,
+) -> Result<Vec<T>, MercError> {
+    reader
+        .read_aterm_iter()?
+        .map(|term| {
+            let term = term?;
+            if !recogniser(&term) {
+                return Err(format!("Expected a {name} in the data specification, got {term}.").into());
+            }
+
+            Ok(term.into())
+        })
+        .collect()
+}
+
+// This module is only used internally to run the proc macro.
+#[merc_derive_terms]
+mod inner {
+    use crate::DataExpressionRef;
+    use crate::SortExpressionRef;
+
+    use super::*;
+
+    /// A sort alias `name = sort`, where the sort can be any sort expression,
+    /// for example a structured sort.
+    #[merc_term(is_sort_alias)]
+    pub struct SortAlias {
+        term: ATerm,
+    }
+
+    impl SortAlias {
+        /// Returns the name of the alias.
+        pub fn name(&self) -> SortExpressionRef<'_> {
+            self.term.arg(0).into()
+        }
+
+        /// Returns the sort expression that the alias refers to.
+        pub fn sort(&self) -> ATermRef<'_> {
+            self.term.arg(1)
+        }
+    }
+
+    impl fmt::Display for SortAlias {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} = {}", self.name(), self.sort())
+        }
+    }
+
+    /// An equation `condition -> lhs = rhs` with the given variables, where the
+    /// condition is optional.
+    #[merc_term(is_data_equation)]
+    pub struct DataEquation {
+        term: ATerm,
+    }
+
+    impl DataEquation {
+        /// Returns the variables of the equation.
+        pub fn variables(&self) -> ATermList<DataVariable> {
+            self.term.arg(0).into()
+        }
+
+        /// Returns the condition of the equation, if any.
+        pub fn condition(&self) -> Option<DataExpressionRef<'_>> {
+            let condition = self.term.arg(1);
+            if is_data_expression(&condition) {
+                Some(condition.into())
+            } else {
+                None
+            }
+        }
+
+        /// Returns the left-hand side of the equation.
+        pub fn lhs(&self) -> DataExpressionRef<'_> {
+            self.term.arg(2).into()
+        }
+
+        /// Returns the right-hand side of the equation.
+        pub fn rhs(&self) -> DataExpressionRef<'_> {
+            self.term.arg(3).into()
+        }
+    }
+
+    impl fmt::Display for DataEquation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if let Some(condition) = self.condition() {
+                write!(f, "{condition} -> ")?;
+            }
+
+            write!(f, "{} = {}", self.lhs(), self.rhs())
+        }
+    }
+}
+
+pub use inner::*;
@@ -23,6 +23,10 @@ pub struct DataSymbols {
     pub data_variable: ManuallyDrop<Symbol>,
     pub data_where_clause: ManuallyDrop<Symbol>,
     pub data_abstraction: ManuallyDrop<Symbol>,
+    /// SortRef(name, sort)
+    pub sort_alias: ManuallyDrop<Symbol>,
+    /// DataEqn(variables, condition, lhs, rhs)
+    pub data_equation: ManuallyDrop<Symbol>,
 
     /// The data application symbol for a given arity.
     data_appl: Vec<Symbol>,
@@ -38,6 +42,8 @@ impl DataSymbols {
 
             data_where_clause: ManuallyDrop::new(Symbol::new("Where", 2)),
             data_abstraction: ManuallyDrop::new(Symbol::new("Abstraction", 2)),
+            sort_alias: ManuallyDrop::new(Symbol::new("SortRef", 2)),
+            data_equation: ManuallyDrop::new(Symbol::new("DataEqn", 4)),
             data_appl: Vec::new(),
         }
     }
@@ -80,13 +86,22 @@ impl DataSymbols {
         term.get_head_symbol() == **self.data_abstraction
     }
 
+    pub fn is_sort_alias<'a, 'b>(&self, term: &'b impl Term<'a, 'b>) -> bool {
+        term.get_head_symbol() == **self.sort_alias
+    }
+
+    pub fn is_data_equation<'a, 'b>(&self, term: &'b impl Term<'a, 'b>) -> bool {
+        term.get_head_symbol() == **self.data_equation
+    }
+
     /// Returns true iff the given term is a data application.
     pub fn is_data_application<'a, 'b>(&self, term: &'b impl Term<'a, 'b>) -> bool {
         if let Some(symbol) = self.data_appl.get(term.get_head_symbol().arity()) {
             return term.get_head_symbol() == **symbol;
         }
 
-        false
+        // The symbol for this arity has not been created yet, which happens for terms read from a stream.
+        term.get_head_symbol().name() == "DataAppl" && term.get_head_symbol().arity() > 0
     }
 
     pub fn get_data_application_symbol(&mut self, arity: usize) -> &SymbolRef<'_> {
@@ -133,6 +148,14 @@ pub fn is_data_abstraction<'a, 'b>(term: &'b impl Term<'a, 'b>) -> bool {
     DATA_SYMBOLS.with_borrow(|ds| ds.is_data_abstraction(term))
 }
 
+pub fn is_sort_alias<'a, 'b>(term: &'b impl Term<'a, 'b>) -> bool {
+    DATA_SYMBOLS.with_borrow(|ds| ds.is_sort_alias(term))
+}
+
+pub fn is_data_equation<'a, 'b>(term: &'b impl Term<'a, 'b>) -> bool {
+    DATA_SYMBOLS.with_borrow(|ds| ds.is_data_equation(term))
+}
+
 pub fn is_data_application<'a, 'b>(term: &'b impl Term<'a, 'b>) -> bool {
     DATA_SYMBOLS.with_borrow(|ds| ds.is_data_application(term))
 }
@@ -15,6 +15,7 @@ use merc_aterm::TermIterator;
 use merc_aterm::Transmutable;
 use merc_aterm::storage::Marker;
 use merc_macros::merc_derive_terms;
+use merc_macros::merc_ignore;
 use merc_macros::merc_term;
 
 use crate::DATA_SYMBOLS;
@@ -38,6 +39,14 @@ mod inner {
             self.term.arg(0).get_head_symbol().name()
         }
 
+        /// Creates a basic sort expression with the given name.
+        #[merc_ignore]
+        pub fn new(name: &str) -> SortExpression {
+            DATA_SYMBOLS.with_borrow(|ds| SortExpression {
+                term: ATerm::with_args(ds.sort_id_symbol.deref(), &[ATermString::new(name)]).protect(),
+            })
+        }
+
         /// Creates a sort expression with the unknown value.
         pub fn unknown_sort() -> SortExpression {
             DATA_SYMBOLS.with_borrow(|ds| SortExpression {
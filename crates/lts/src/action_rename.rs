@@ -0,0 +1,240 @@
+#![forbid(unsafe_code)]
+
+use std::cmp::Ordering;
+
+use num::BigInt;
+use rustc_hash::FxHashMap;
+
+use merc_syntax::Action;
+use merc_syntax::ActionRHS;
+use merc_syntax::ActionRenameDecl;
+use merc_syntax::DataExpr;
+use merc_syntax::DataExprBinaryOp;
+use merc_syntax::DataExprUnaryOp;
+use merc_syntax::MultiAction;
+use merc_syntax::UntypedActionRenameSpec;
+use merc_utilities::MercError;
+
+use crate::LabelledTransitionSystem;
+use crate::TransitionLabel;
+use crate::transform_actions;
+
+/// Renames the actions of the labels according to the rename rules of an
+/// mCRL2 action rename specification, as used by `ltsconvert --action-rename`.
+///
+/// # Details
+///
+/// Every action of a multi-action is renamed by the first rule of which the
+/// left-hand side matches it and whose condition holds, where the declared
+/// variables match any argument. Renaming an action to `tau` removes it from
+/// the multi-action, and renaming an action to `delta` removes the transitions
+/// with that multi-action.
+///
+/// The data expressions of an explicit LTS are already evaluated, so the
+/// arguments are compared syntactically. Therefore, the conditions can only
+/// consist of boolean connectives and comparisons of arguments, where only
+/// numbers are ordered.
+pub fn rename_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    specification: &UntypedActionRenameSpec,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    transform_actions(lts, |multi_action| {
+        let mut actions = Vec::with_capacity(multi_action.actions.len());
+        for action in &multi_action.actions {
+            match rename_action(&specification.rename_declarations, action)? {
+                None => actions.push(action.clone()),
+                Some(ActionRHS::Tau) => {}
+                Some(ActionRHS::Delta) => return Ok(None),
+                Some(ActionRHS::Action(renamed)) => actions.push(renamed),
+            }
+        }
+
+        Ok(Some(MultiAction { actions }))
+    })
+}
+
+/// Returns the right-hand side of the first rule that applies to the action, with its variables substituted.
+fn rename_action(rules: &[ActionRenameDecl], action: &Action) -> Result<Option<ActionRHS>, MercError> {
+    for decl in rules {
+        let rule = &decl.rename_rule;
+        if rule.action.id != action.id || rule.action.args.len() != action.args.len() {
+            continue;
+        }
+
+        let mut bindings: FxHashMap<&str, &DataExpr> = FxHashMap::default();
+        let matches = rule.action.args.iter().zip(&action.args).all(|(pattern, argument)| {
+            match pattern {
+                DataExpr::Id(name) if decl.variables_specification.iter().any(|var| var.identifier == *name) => {
+                    // A variable that occurs multiple times must be bound to the same argument.
+                    let bound = bindings.entry(name.as_str()).or_insert(argument);
+                    bound.to_string() == argument.to_string()
+                }
+                _ => pattern.to_string() == argument.to_string(),
+            }
+        });
+
+        if !matches {
+            continue;
+        }
+
+        if let Some(condition) = &rule.condition {
+            if !evaluate_condition(condition, &bindings)? {
+                continue;
+            }
+        }
+
+        return Ok(Some(match &rule.rhs {
+            ActionRHS::Tau => ActionRHS::Tau,
+            ActionRHS::Delta => ActionRHS::Delta,
+            ActionRHS::Action(renamed) => ActionRHS::Action(Action {
+                id: renamed.id.clone(),
+                args: renamed.args.iter().map(|arg| substitute(arg, &bindings)).collect(),
+            }),
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Evaluates the condition of a rename rule for the given variable bindings.
+fn evaluate_condition(condition: &DataExpr, bindings: &FxHashMap<&str, &DataExpr>) -> Result<bool, MercError> {
+    match condition {
+        DataExpr::Bool(value) => Ok(*value),
+        DataExpr::Id(name) if bindings.contains_key(name.as_str()) => {
+            evaluate_condition(bindings[name.as_str()], bindings)
+        }
+        DataExpr::Id(name) if name == "true" || name == "false" => Ok(name == "true"),
+        DataExpr::Unary {
+            op: DataExprUnaryOp::Negation,
+            expr,
+        } => Ok(!evaluate_condition(expr, bindings)?),
+        DataExpr::Binary { op, lhs, rhs } => match op {
+            DataExprBinaryOp::Conj => Ok(evaluate_condition(lhs, bindings)? && evaluate_condition(rhs, bindings)?),
+            DataExprBinaryOp::Disj => Ok(evaluate_condition(lhs, bindings)? || evaluate_condition(rhs, bindings)?),
+            DataExprBinaryOp::Implies => Ok(!evaluate_condition(lhs, bindings)? || evaluate_condition(rhs, bindings)?),
+            DataExprBinaryOp::Equal => Ok(value(lhs, bindings) == value(rhs, bindings)),
+            DataExprBinaryOp::NotEqual => Ok(value(lhs, bindings) != value(rhs, bindings)),
+            DataExprBinaryOp::LessThan => Ok(compare_numbers(lhs, rhs, bindings)?.is_lt()),
+            DataExprBinaryOp::LessEqual => Ok(compare_numbers(lhs, rhs, bindings)?.is_le()),
+            DataExprBinaryOp::GreaterThan => Ok(compare_numbers(lhs, rhs, bindings)?.is_gt()),
+            DataExprBinaryOp::GreaterEqual => Ok(compare_numbers(lhs, rhs, bindings)?.is_ge()),
+            _ => Err(format!("Unsupported operator in the condition {condition} of a rename rule.").into()),
+        },
+        _ => Err(format!("Cannot evaluate the condition {condition} of a rename rule.").into()),
+    }
+}
+
+/// Returns the textual value of the given expression after substituting the bound variables.
+fn value(expr: &DataExpr, bindings: &FxHashMap<&str, &DataExpr>) -> String {
+    substitute(expr, bindings).to_string()
+}
+
+/// Compares the values of two expressions that should both be numbers.
+fn compare_numbers(
+    lhs: &DataExpr,
+    rhs: &DataExpr,
+    bindings: &FxHashMap<&str, &DataExpr>,
+) -> Result<Ordering, MercError> {
+    let number = |expr: &DataExpr| -> Result<BigInt, MercError> {
+        let value = value(expr, bindings);
+        value
+            .parse::<BigInt>()
+            .map_err(|_| format!("Cannot compare {value} in a rename rule, since it is not a number.").into())
+    };
+
+    Ok(number(lhs)?.cmp(&number(rhs)?))
+}
+
+/// Replaces the variables in the given expression by the expressions they are bound to.
+fn substitute(expr: &DataExpr, bindings: &FxHashMap<&str, &DataExpr>) -> DataExpr {
+    match expr {
+        DataExpr::Id(name) => bindings
+            .get(name.as_str())
+            .map_or_else(|| expr.clone(), |bound| (*bound).clone()),
+        DataExpr::Application { function, arguments } => DataExpr::Application {
+            function: Box::new(substitute(function, bindings)),
+            arguments: arguments.iter().map(|arg| substitute(arg, bindings)).collect(),
+        },
+        DataExpr::List(elements) => DataExpr::List(elements.iter().map(|e| substitute(e, bindings)).collect()),
+        DataExpr::Set(elements) => DataExpr::Set(elements.iter().map(|e| substitute(e, bindings)).collect()),
+        DataExpr::Unary { op, expr } => DataExpr::Unary {
+            op: op.clone(),
+            expr: Box::new(substitute(expr, bindings)),
+        },
+        DataExpr::Binary { op, lhs, rhs } => DataExpr::Binary {
+            op: op.clone(),
+            lhs: Box::new(substitute(lhs, bindings)),
+            rhs: Box::new(substitute(rhs, bindings)),
+        },
+        _ => expr.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use itertools::Itertools;
+    use test_log::test;
+
+    use crate::LTS;
+    use crate::read_aut;
+    use crate::read_lts;
+
+    #[test]
+    fn test_rename_actions() {
+        let lts = read_aut(
+            b"des (0,5,3)\n(0,\"a(1)\",1)\n(0,\"a(2)\",2)\n(1,\"b(3, 3)|c\",2)\n(1,\"b(3, 4)\",0)\n(2,d,0)\n"
+                as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let specification = UntypedActionRenameSpec::parse(
+            "act a, e: Nat; b: Nat # Nat; c, d;
+             var n, m: Nat;
+             rename
+                n > 1 -> a(n) => e(n);
+                b(n, n) => tau;
+                b(n, m) => e(m);
+                d => delta;",
+        )
+        .unwrap();
+
+        let result = &rename_actions(lts, &specification).unwrap();
+        let labels: Vec<String> = result
+            .iter_states()
+            .flat_map(|state_index| {
+                result.outgoing_transitions(state_index).map(move |transition| {
+                    format!("{state_index} {} {}", result.labels()[transition.label], transition.to)
+                })
+            })
+            .sorted()
+            .collect();
+
+        assert_eq!(labels, vec!["0 a(1) 1", "0 e(2) 2", "1 c 2", "1 e(4) 0"]);
+    }
+
+    #[test]
+    fn test_rename_unsupported_condition() {
+        let lts = read_aut(b"des (0,1,2)\n(0,\"a(1)\",1)\n" as &[u8], Vec::new()).unwrap();
+
+        let specification =
+            UntypedActionRenameSpec::parse("act a, b: Nat; var n: Nat; rename f(n) -> a(n) => b(n);").unwrap();
+        assert!(rename_actions(lts, &specification).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_rename_multi_actions() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+        let num_of_transitions = lts.num_of_transitions();
+
+        let specification = UntypedActionRenameSpec::parse("act r1, s4: D; var d: D; rename r1(d) => s4(d);").unwrap();
+        let result = rename_actions(lts, &specification).unwrap();
+
+        assert_eq!(result.num_of_transitions(), num_of_transitions);
+        assert!(result.labels().iter().all(|label| !label.matches_label("r1")));
+        assert!(result.specification().is_some());
+    }
+}
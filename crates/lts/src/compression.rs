@@ -0,0 +1,134 @@
+#![forbid(unsafe_code)]
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Stdout;
+use std::io::Write;
+use std::io::stdout;
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+use merc_utilities::MercError;
+
+/// The compression of an LTS file, which is given by an additional extension
+/// after the format extension, for example `abp.aut.gz`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// The file is not compressed.
+    None,
+    /// The file is compressed using gzip, with the `.gz` extension.
+    Gzip,
+    /// The file is compressed using Zstandard, with the `.zst` extension.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the compression indicated by the extension of the given path.
+    pub fn from_extension(path: &Path) -> Compression {
+        if path.extension() == Some(OsStr::new("gz")) {
+            Compression::Gzip
+        } else if path.extension() == Some(OsStr::new("zst")) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Reads a file that is decompressed on the fly, where the compression is
+/// derived from the extension of the file.
+pub enum CompressedReader {
+    Plain(File),
+    Gzip(MultiGzDecoder<File>),
+    Zstd(zstd::Decoder<'static, BufReader<File>>),
+}
+
+impl CompressedReader {
+    /// Opens the file at the given path for reading.
+    pub fn open(path: &Path) -> Result<CompressedReader, MercError> {
+        let file = File::open(path)?;
+
+        Ok(match Compression::from_extension(path) {
+            Compression::None => CompressedReader::Plain(file),
+            Compression::Gzip => CompressedReader::Gzip(MultiGzDecoder::new(file)),
+            Compression::Zstd => CompressedReader::Zstd(zstd::Decoder::new(file)?),
+        })
+    }
+}
+
+impl Read for CompressedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            CompressedReader::Plain(reader) => reader.read(buf),
+            CompressedReader::Gzip(reader) => reader.read(buf),
+            CompressedReader::Zstd(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Writes to a file that is compressed on the fly, where the compression is
+/// derived from the extension of the file, or to the standard output.
+///
+/// # Details
+///
+/// The compressed stream must be completed by calling [CompressedWriter::finish].
+pub enum CompressedWriter {
+    Stdout(Stdout),
+    Plain(File),
+    Gzip(GzEncoder<File>),
+    Zstd(zstd::Encoder<'static, File>),
+}
+
+impl CompressedWriter {
+    /// Creates the file at the given path for writing, or writes to the
+    /// standard output when no path is given.
+    pub fn create(path: Option<&Path>) -> Result<CompressedWriter, MercError> {
+        let Some(path) = path else {
+            return Ok(CompressedWriter::Stdout(stdout()));
+        };
+
+        let file = File::create(path)?;
+        Ok(match Compression::from_extension(path) {
+            Compression::None => CompressedWriter::Plain(file),
+            Compression::Gzip => CompressedWriter::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+            Compression::Zstd => CompressedWriter::Zstd(zstd::Encoder::new(file, 0)?),
+        })
+    }
+
+    /// Writes the remaining compressed data and flushes the output.
+    pub fn finish(self) -> Result<(), MercError> {
+        match self {
+            CompressedWriter::Stdout(mut writer) => writer.flush()?,
+            CompressedWriter::Plain(mut writer) => writer.flush()?,
+            CompressedWriter::Gzip(writer) => writer.finish()?.flush()?,
+            CompressedWriter::Zstd(writer) => writer.finish()?.flush()?,
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedWriter::Stdout(writer) => writer.write(buf),
+            CompressedWriter::Plain(writer) => writer.write(buf),
+            CompressedWriter::Gzip(writer) => writer.write(buf),
+            CompressedWriter::Zstd(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedWriter::Stdout(writer) => writer.flush(),
+            CompressedWriter::Plain(writer) => writer.flush(),
+            CompressedWriter::Gzip(writer) => writer.flush(),
+            CompressedWriter::Zstd(writer) => writer.flush(),
+        }
+    }
+}
@@ -1,18 +1,30 @@
 #![forbid(unsafe_code)]
 
 use std::ffi::OsStr;
-use std::fs::File;
 use std::path::Path;
 
+use rustc_hash::FxHashMap;
+
 use merc_utilities::MercError;
 use merc_utilities::Timing;
 
+use crate::CompressedReader;
+use crate::CompressedWriter;
+use crate::Compression;
 use crate::LTS;
 use crate::LabelledTransitionSystem;
 use crate::MultiAction;
+use crate::TransitionLabel;
 use crate::read_aut;
+use crate::read_aut_parallel;
 use crate::read_bcg;
 use crate::read_lts;
+use crate::write_aut;
+use crate::write_bcg;
+use crate::write_csv;
+use crate::write_dot;
+use crate::write_json;
+use crate::write_lts;
 
 /// Convenience macro to call `GenericLts::apply` with the same function for both variants.
 /// Useful with generic functions that can be monomorphized for both label types.
@@ -56,20 +68,39 @@ pub enum LtsFormat {
     Lts,
     /// The CADP BCG format (requires 'cadp' feature)
     Bcg,
+    /// The Graphviz DOT format (write only)
+    Dot,
+    /// A JSON object with nodes and edges (write only)
+    Json,
+    /// A CSV edge list (write only)
+    Csv,
 }
 
-/// Guesses the LTS file format from the file extension.
+/// Guesses the LTS file format from the file extension, where the extension
+/// of a compressed file is ignored, see [Compression].
 pub fn guess_lts_format_from_extension(path: &Path, format: Option<LtsFormat>) -> Option<LtsFormat> {
     if let Some(format) = format {
         return Some(format);
     }
 
+    let path = if Compression::from_extension(path) == Compression::None {
+        path.to_path_buf()
+    } else {
+        path.with_extension("")
+    };
+
     if path.extension() == Some(OsStr::new("aut")) {
         Some(LtsFormat::Aut)
     } else if path.extension() == Some(OsStr::new("lts")) {
         Some(LtsFormat::Lts)
     } else if path.extension() == Some(OsStr::new("bcg")) {
         Some(LtsFormat::Bcg)
+    } else if path.extension() == Some(OsStr::new("dot")) {
+        Some(LtsFormat::Dot)
+    } else if path.extension() == Some(OsStr::new("json")) {
+        Some(LtsFormat::Json)
+    } else if path.extension() == Some(OsStr::new("csv")) {
+        Some(LtsFormat::Csv)
     } else {
         None
     }
@@ -139,26 +170,256 @@ impl GenericLts {
 }
 
 /// Reads an explicit labelled transition system from the given path and format.
+///
+/// Files in the `.aut` and `.lts` formats are decompressed transparently when
+/// the path has a `.gz` or `.zst` extension. Uncompressed files in the `.aut`
+/// format are parsed by the given number of threads, see [read_aut_parallel].
 pub fn read_explicit_lts(
     path: &Path,
     format: LtsFormat,
     hidden_labels: Vec<String>,
+    num_of_threads: usize,
     timing: &mut Timing,
 ) -> Result<GenericLts, MercError> {
     let mut time_read = timing.start("read_explicit_lts");
 
     let result = match format {
-        LtsFormat::Aut => {
-            let file = File::open(path)?;
-            GenericLts::Aut(read_aut(&file, hidden_labels)?)
+        LtsFormat::Aut if num_of_threads > 1 && Compression::from_extension(path) == Compression::None => {
+            GenericLts::Aut(read_aut_parallel(
+                CompressedReader::open(path)?,
+                hidden_labels,
+                num_of_threads,
+            )?)
+        }
+        LtsFormat::Aut => GenericLts::Aut(read_aut(CompressedReader::open(path)?, hidden_labels)?),
+        LtsFormat::Lts => GenericLts::Lts(read_lts(CompressedReader::open(path)?, hidden_labels)?),
+        LtsFormat::Bcg => {
+            if Compression::from_extension(path) != Compression::None {
+                return Err("Compressed BCG files are not supported.".into());
+            }
+
+            GenericLts::Bcg(read_bcg(path, hidden_labels)?)
         }
-        LtsFormat::Lts => {
-            let file = File::open(path)?;
-            GenericLts::Lts(read_lts(&file, hidden_labels)?)
+        LtsFormat::Dot | LtsFormat::Json | LtsFormat::Csv => {
+            return Err(format!("The {format:?} format can only be written.").into());
         }
-        LtsFormat::Bcg => GenericLts::Bcg(read_bcg(path, hidden_labels)?),
     };
 
     time_read.finish();
     Ok(result)
 }
+
+/// Writes an explicit labelled transition system in the given format to the
+/// given path, or to the standard output when no path is given.
+///
+/// The multi-actions of an LTS read from the `.lts` format are preserved when
+/// it is written in the `.lts` format again. Textual labels are parsed into
+/// multi-actions when they are written in the `.lts` format, and conversely.
+/// Files in the `.aut` and `.lts` formats are compressed when the path has a
+/// `.gz` or `.zst` extension. The DOT, JSON and CSV formats are intended for
+/// visualisation and scripting, and can only be written.
+pub fn write_explicit_lts(
+    lts: GenericLts,
+    path: Option<&Path>,
+    format: LtsFormat,
+    timing: &mut Timing,
+) -> Result<(), MercError> {
+    let mut time_write = timing.start("write_explicit_lts");
+
+    match format {
+        LtsFormat::Bcg => {
+            let path = path.ok_or("Output path must be specified when writing BCG files.")?;
+            if Compression::from_extension(path) != Compression::None {
+                return Err("Compressed BCG files are not supported.".into());
+            }
+
+            match lts {
+                GenericLts::Aut(lts) | GenericLts::Bcg(lts) => write_bcg(&lts, path)?,
+                GenericLts::Lts(lts) => write_bcg(&print_multi_actions(lts), path)?,
+            }
+        }
+        _ => {
+            let mut writer = CompressedWriter::create(path)?;
+            match (lts, format) {
+                (lts, LtsFormat::Dot) => apply_lts!(lts, &mut writer, |lts, writer| write_dot(writer, &lts))?,
+                (lts, LtsFormat::Json) => apply_lts!(lts, &mut writer, |lts, writer| write_json(writer, &lts))?,
+                (lts, LtsFormat::Csv) => apply_lts!(lts, &mut writer, |lts, writer| write_csv(writer, &lts))?,
+                (GenericLts::Aut(lts) | GenericLts::Bcg(lts), LtsFormat::Aut) => write_aut(&mut writer, &lts)?,
+                (GenericLts::Aut(lts) | GenericLts::Bcg(lts), _) => write_lts(&mut writer, &parse_multi_actions(lts)?)?,
+                (GenericLts::Lts(lts), LtsFormat::Aut) => write_aut(&mut writer, &print_multi_actions(lts))?,
+                (GenericLts::Lts(lts), _) => write_lts(&mut writer, &lts)?,
+            }
+            writer.finish()?;
+        }
+    }
+
+    time_write.finish();
+    Ok(())
+}
+
+/// Parses the textual labels of the given LTS into multi-actions, where the
+/// internal action becomes the empty multi-action.
+fn parse_multi_actions(
+    lts: LabelledTransitionSystem<String>,
+) -> Result<LabelledTransitionSystem<MultiAction>, MercError> {
+    let mut multi_actions: FxHashMap<String, MultiAction> = FxHashMap::default();
+    for label in lts.labels() {
+        let multi_action = if label.is_tau_label() {
+            MultiAction::tau_label()
+        } else {
+            MultiAction::from_string(label)
+                .map_err(|error| format!("Cannot parse label \"{label}\" as a multi-action: {error}"))?
+        };
+
+        multi_actions.insert(label.clone(), multi_action);
+    }
+
+    Ok(lts.relabel(|label| multi_actions[&label].clone()))
+}
+
+/// Prints the multi-actions of the given LTS as textual labels, where the
+/// empty multi-action becomes the internal action.
+fn print_multi_actions(lts: LabelledTransitionSystem<MultiAction>) -> LabelledTransitionSystem<String> {
+    lts.relabel(|label| {
+        if label.is_tau_label() {
+            String::tau_label()
+        } else {
+            label.to_string()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::check_equivalent;
+    use crate::random_lts_monolithic;
+    use merc_utilities::random_test;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_write_explicit_lts() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("abp.lts");
+        let mut timing = Timing::new();
+
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), Vec::new()).unwrap();
+        write_explicit_lts(GenericLts::Lts(lts.clone()), Some(&path), LtsFormat::Lts, &mut timing).unwrap();
+
+        let GenericLts::Lts(result_lts) = read_explicit_lts(&path, LtsFormat::Lts, Vec::new(), 1, &mut timing).unwrap()
+        else {
+            panic!("Reading an .lts file should result in multi-actions");
+        };
+
+        // The sorts of the data arguments are not written, so only the textual representation is preserved.
+        check_equivalent(
+            &lts.relabel(|label| label.to_string()),
+            &result_lts.relabel(|label| label.to_string()),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_convert_aut_lts() {
+        random_test(100, |rng| {
+            let directory = tempfile::tempdir().unwrap();
+            let lts_path = directory.path().join("random.lts");
+            let aut_path = directory.path().join("random.aut");
+            let mut timing = Timing::new();
+
+            // Converting to the .lts format and back should result in the same LTS.
+            let lts = random_lts_monolithic::<String>(rng, 10, 3, 3);
+            write_explicit_lts(
+                GenericLts::Aut(lts.clone()),
+                Some(&lts_path),
+                LtsFormat::Lts,
+                &mut timing,
+            )
+            .unwrap();
+            let result_lts = read_explicit_lts(&lts_path, LtsFormat::Lts, Vec::new(), 1, &mut timing).unwrap();
+            write_explicit_lts(result_lts, Some(&aut_path), LtsFormat::Aut, &mut timing).unwrap();
+
+            let GenericLts::Aut(result_lts) =
+                read_explicit_lts(&aut_path, LtsFormat::Aut, Vec::new(), 1, &mut timing).unwrap()
+            else {
+                panic!("Reading an .aut file should result in textual labels");
+            };
+            check_equivalent(&lts, &result_lts);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_read_explicit_lts_parallel() {
+        random_test(10, |rng| {
+            let directory = tempfile::tempdir().unwrap();
+            let path = directory.path().join("random.aut");
+            let mut timing = Timing::new();
+
+            let lts = random_lts_monolithic::<String>(rng, 1000, 5, 3);
+            write_explicit_lts(GenericLts::Aut(lts), Some(&path), LtsFormat::Aut, &mut timing).unwrap();
+
+            // Reading with multiple threads should result in the same LTS as the sequential reader.
+            let expected_lts = read_aut(std::fs::File::open(&path).unwrap(), vec!["a".to_string()]).unwrap();
+            let GenericLts::Aut(result_lts) =
+                read_explicit_lts(&path, LtsFormat::Aut, vec!["a".to_string()], 4, &mut timing).unwrap()
+            else {
+                panic!("Reading an .aut file should result in textual labels");
+            };
+            check_equivalent(&expected_lts, &result_lts);
+        });
+    }
+
+    #[test]
+    fn test_convert_invalid_label() {
+        let lts = read_aut(b"des (0,1,2)\n(0,\"a(\",1)\n" as &[u8], Vec::new()).unwrap();
+        let mut buffer = Vec::new();
+
+        let error = parse_multi_actions(lts).and_then(|lts| write_lts(&mut buffer, &lts));
+        assert!(
+            error.is_err_and(|error| error.to_string().contains("\"a(\"")),
+            "The error should name the label that failed to parse"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_compressed_lts() {
+        random_test(10, |rng| {
+            let directory = tempfile::tempdir().unwrap();
+            let mut timing = Timing::new();
+
+            let lts = random_lts_monolithic::<String>(rng, 10, 3, 3);
+            for name in ["random.aut.gz", "random.aut.zst", "random.lts.gz", "random.lts.zst"] {
+                let path = directory.path().join(name);
+                let format = guess_lts_format_from_extension(&path, None).unwrap();
+
+                write_explicit_lts(GenericLts::Aut(lts.clone()), Some(&path), format, &mut timing).unwrap();
+                let result_lts = read_explicit_lts(&path, format, Vec::new(), 1, &mut timing).unwrap();
+
+                let result_lts = match result_lts {
+                    GenericLts::Aut(lts) => lts,
+                    GenericLts::Lts(lts) => print_multi_actions(lts),
+                    GenericLts::Bcg(_) => panic!("The format should not be BCG"),
+                };
+                check_equivalent(&lts, &result_lts);
+            }
+        });
+    }
+
+    #[test]
+    fn test_guess_compressed_lts_format() {
+        assert_eq!(
+            guess_lts_format_from_extension(Path::new("abp.aut.gz"), None),
+            Some(LtsFormat::Aut)
+        );
+        assert_eq!(
+            guess_lts_format_from_extension(Path::new("abp.lts.zst"), None),
+            Some(LtsFormat::Lts)
+        );
+        assert_eq!(guess_lts_format_from_extension(Path::new("abp.gz"), None), None);
+    }
+}
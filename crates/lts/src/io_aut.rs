@@ -1,12 +1,19 @@
 #![forbid(unsafe_code)]
 
+use std::fmt;
+use std::io::BufRead;
+use std::io::BufReader;
 use std::io::BufWriter;
 use std::io::Read;
 use std::io::Write;
 
 use log::info;
 use merc_io::LargeFormatter;
+use num::One;
+use num::Zero;
+use rayon::prelude::*;
 use regex::Regex;
+use rustc_hash::FxHashMap;
 use streaming_iterator::StreamingIterator;
 use thiserror::Error;
 
@@ -15,9 +22,15 @@ use merc_io::TimeProgress;
 use merc_utilities::MercError;
 use merc_utilities::debug_trace;
 
+use crate::Distribution;
 use crate::LTS;
+use crate::LabelIndex;
 use crate::LabelledTransitionSystem;
 use crate::LtsBuilder;
+use crate::LtsBuilderFast;
+use crate::ProbabilisticLts;
+use crate::ProbabilisticLtsBuilder;
+use crate::Probability;
 use crate::StateIndex;
 use crate::TransitionLabel;
 
@@ -28,6 +41,9 @@ pub enum IOError {
 
     #[error("Invalid transition {0}")]
     InvalidTransition(String),
+
+    #[error("Invalid distribution {0}")]
+    InvalidDistribution(String),
 }
 
 /// Loads a labelled transition system in the [Aldebaran
@@ -50,20 +66,7 @@ pub fn read_aut(reader: impl Read, hidden_labels: Vec<String>) -> Result<Labelle
         .get()
         .ok_or(IOError::InvalidHeader("The first line should be the header"))?;
 
-    // Regex for des (<initial>: Nat, <num_of_states>: Nat, <num_of_transitions>: Nat)
-    let header_regex = Regex::new(r#"des\s*\(\s*([0-9]*)\s*,\s*([0-9]*)\s*,\s*([0-9]*)\s*\)\s*"#)
-        .expect("Regex compilation should not fail");
-
-    let (_, [initial_txt, num_of_transitions_txt, num_of_states_txt]) = header_regex
-        .captures(header)
-        .ok_or(IOError::InvalidHeader(
-            "does not match des (<init>, <num_of_transitions>, <num_of_states>)",
-        ))?
-        .extract();
-
-    let initial_state = StateIndex::new(initial_txt.parse()?);
-    let num_of_transitions: usize = num_of_transitions_txt.parse()?;
-    let num_of_states: usize = num_of_states_txt.parse()?;
+    let (initial_state, num_of_transitions, num_of_states) = read_header(header)?;
 
     let mut builder = LtsBuilder::with_capacity(Vec::new(), hidden_labels, num_of_states, 16, num_of_transitions);
     let progress = TimeProgress::new(
@@ -97,6 +100,96 @@ pub fn read_aut(reader: impl Read, hidden_labels: Vec<String>) -> Result<Labelle
     Ok(builder.finish(initial_state))
 }
 
+/// Loads a labelled transition system in the Aldebaran format, see
+/// [read_aut], where the transitions are parsed by the given number of threads.
+///
+/// # Details
+///
+/// The input is read in batches of a bounded size, which are split into chunks
+/// at line boundaries. The chunks are parsed in parallel, after which the
+/// transitions are merged into a single [LtsBuilderFast] in the order of the
+/// input.
+pub fn read_aut_parallel(
+    reader: impl Read,
+    hidden_labels: Vec<String>,
+    num_of_threads: usize,
+) -> Result<LabelledTransitionSystem<String>, MercError> {
+    read_aut_parallel_impl(reader, hidden_labels, num_of_threads, CHUNK_SIZE)
+}
+
+/// The implementation of [read_aut_parallel], where every thread parses at most chunk_size bytes at once.
+fn read_aut_parallel_impl(
+    reader: impl Read,
+    hidden_labels: Vec<String>,
+    num_of_threads: usize,
+    chunk_size: usize,
+) -> Result<LabelledTransitionSystem<String>, MercError> {
+    info!("Reading LTS in .aut format using {num_of_threads} threads...");
+
+    let mut reader = BufReader::new(reader);
+    let mut header = String::new();
+    reader.read_line(&mut header)?;
+    let (initial_state, num_of_transitions, num_of_states) = read_header(&header)?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_of_threads)
+        .build()
+        .expect("Failed to create the thread pool");
+
+    let mut builder = LtsBuilderFast::with_capacity(Vec::new(), hidden_labels, num_of_transitions);
+    builder.require_num_of_states(num_of_states);
+
+    let progress = TimeProgress::new(
+        move |read: usize| {
+            info!(
+                "Read {} transitions {}%...",
+                LargeFormatter(read),
+                read * 100 / num_of_transitions.max(1)
+            )
+        },
+        1,
+    );
+
+    // The batch can start with the incomplete last line of the previous batch.
+    let batch_size = chunk_size * num_of_threads;
+    let mut batch: Vec<u8> = Vec::with_capacity(batch_size);
+    let mut end_of_input = false;
+    while !end_of_input {
+        end_of_input = reader.by_ref().take(batch_size as u64).read_to_end(&mut batch)? < batch_size;
+
+        let end = if end_of_input {
+            batch.len()
+        } else if let Some(position) = batch.iter().rposition(|&c| c == b'\n') {
+            position + 1
+        } else {
+            // The batch does not contain a complete line yet.
+            continue;
+        };
+
+        let chunks = split_lines(&batch[..end], num_of_threads);
+        let parsed = pool.install(|| {
+            chunks
+                .par_iter()
+                .map(|chunk| read_chunk(chunk))
+                .collect::<Result<Vec<_>, MercError>>()
+        })?;
+
+        for (labels, transitions) in parsed {
+            let labels: Vec<LabelIndex> = labels.into_iter().map(|label| builder.add_label(label)).collect();
+            for (from, label, to) in transitions {
+                builder.add_transition_index(from, labels[label], to);
+            }
+        }
+
+        progress.print(builder.num_of_transitions());
+        batch.drain(..end);
+    }
+
+    info!("Finished reading LTS");
+
+    Ok(builder.finish(initial_state, false))
+}
+
 /// Write a labelled transition system in plain text in Aldebaran format to the
 /// given writer, see [read_aut].
 ///
@@ -144,6 +237,234 @@ pub fn write_aut(writer: &mut impl Write, lts: &impl LTS) -> Result<(), MercErro
     Ok(())
 }
 
+/// Loads a probabilistic labelled transition system in the probabilistic
+/// variant of the Aldebaran format, as used by the mCRL2 toolset, from the
+/// given reader.
+///
+/// # Details
+///
+/// The format is the same as [read_aut], except that the initial state in the
+/// header and the target of every transition are distributions of the form
+/// `<s_0> <p_0> <s_1> <p_1> ... <s_n>`, where the last state gets the remaining
+/// probability. For example, `(0, "a", 1 1/3 2)` is a transition to state 1
+/// with probability 1/3 and to state 2 with probability 2/3.
+pub fn read_probabilistic_aut(
+    reader: impl Read,
+    hidden_labels: Vec<String>,
+) -> Result<ProbabilisticLts<String>, MercError> {
+    info!("Reading probabilistic LTS in .aut format...");
+
+    let mut lines = LineIterator::new(reader);
+    lines.advance();
+    let header = lines
+        .get()
+        .ok_or(IOError::InvalidHeader("The first line should be the header"))?;
+
+    // Regex for des (<initial>: Distribution, <num_of_states>: Nat, <num_of_transitions>: Nat)
+    let header_regex = Regex::new(r#"des\s*\(\s*([0-9/ ]*?)\s*,\s*([0-9]*)\s*,\s*([0-9]*)\s*\)\s*"#)
+        .expect("Regex compilation should not fail");
+
+    let (_, [initial_txt, num_of_transitions_txt, num_of_states_txt]) = header_regex
+        .captures(header)
+        .ok_or(IOError::InvalidHeader(
+            "does not match des (<init>, <num_of_transitions>, <num_of_states>)",
+        ))?
+        .extract();
+
+    let initial_distribution = read_distribution(initial_txt)?;
+    let num_of_transitions: usize = num_of_transitions_txt.parse()?;
+    let num_of_states: usize = num_of_states_txt.parse()?;
+
+    let mut builder = ProbabilisticLtsBuilder::new(Vec::new(), hidden_labels);
+    builder.require_num_of_states(num_of_states);
+
+    let progress = TimeProgress::new(
+        move |read: usize| {
+            info!(
+                "Read {} transitions {}%...",
+                LargeFormatter(read),
+                read * 100 / num_of_transitions
+            )
+        },
+        1,
+    );
+
+    while let Some(line) = lines.next() {
+        let (from_txt, label_txt, to_txt) =
+            read_transition(line).ok_or_else(|| IOError::InvalidTransition(line.clone()))?;
+
+        let from = StateIndex::new(from_txt.parse()?);
+        let to = read_distribution(to_txt)?;
+
+        debug_trace!("Read transition {from} --[{label_txt}]-> {to}");
+
+        builder.add_transition(from, label_txt, to);
+
+        progress.print(builder.num_of_transitions());
+    }
+
+    info!("Finished reading probabilistic LTS");
+
+    Ok(builder.finish(initial_distribution))
+}
+
+/// Write a probabilistic labelled transition system in plain text in the
+/// probabilistic Aldebaran format to the given writer, see
+/// [read_probabilistic_aut].
+///
+/// Note that the writer is buffered internally using a `BufWriter`.
+pub fn write_probabilistic_aut<L: TransitionLabel>(
+    writer: &mut impl Write,
+    lts: &ProbabilisticLts<L>,
+) -> Result<(), MercError> {
+    info!("Writing probabilistic LTS in .aut format...");
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(
+        writer,
+        "des ({}, {}, {})",
+        DistributionFormatter(lts.distribution(lts.initial_distribution())),
+        lts.num_of_transitions(),
+        lts.num_of_states()
+    )?;
+
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            writeln!(
+                writer,
+                "({}, \"{}\", {})",
+                state_index,
+                lts.labels()[transition.label.value()],
+                DistributionFormatter(lts.distribution(transition.to))
+            )?;
+        }
+    }
+
+    info!("Finished writing probabilistic LTS.");
+    Ok(())
+}
+
+/// Parses a distribution of the form `<s_0> <p_0> <s_1> <p_1> ... <s_n>`, where
+/// the last state gets the remaining probability.
+fn read_distribution(input: &str) -> Result<Distribution, MercError> {
+    let invalid = || IOError::InvalidDistribution(input.to_string());
+
+    let mut entries = Vec::new();
+    let mut remainder = Probability::one();
+    let mut parts = input.split_whitespace();
+    loop {
+        let state_index = StateIndex::new(parts.next().ok_or_else(invalid)?.parse()?);
+        match parts.next() {
+            Some(probability_txt) => {
+                let probability: Probability = probability_txt.parse().map_err(|_| invalid())?;
+                if probability <= Probability::zero() || probability >= remainder {
+                    return Err(invalid().into());
+                }
+
+                remainder -= &probability;
+                entries.push((state_index, probability));
+            }
+            None => {
+                entries.push((state_index, remainder));
+                return Ok(Distribution::new(entries));
+            }
+        }
+    }
+}
+
+/// Prints a distribution in the format that is parsed by [read_distribution].
+struct DistributionFormatter<'a>(&'a Distribution);
+
+impl fmt::Display for DistributionFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let last = self.0.len() - 1;
+        for (index, (state_index, probability)) in self.0.iter().enumerate() {
+            if index == last {
+                write!(f, "{state_index}")?;
+            } else {
+                write!(f, "{state_index} {probability} ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of bytes that every thread parses at once in [read_aut_parallel].
+const CHUNK_SIZE: usize = 1 << 24;
+
+/// Parses the header `des (<initial>: Nat, <num_of_transitions>: Nat, <num_of_states>: Nat)`.
+fn read_header(header: &str) -> Result<(StateIndex, usize, usize), MercError> {
+    let header_regex = Regex::new(r#"des\s*\(\s*([0-9]*)\s*,\s*([0-9]*)\s*,\s*([0-9]*)\s*\)\s*"#)
+        .expect("Regex compilation should not fail");
+
+    let (_, [initial_txt, num_of_transitions_txt, num_of_states_txt]) = header_regex
+        .captures(header)
+        .ok_or(IOError::InvalidHeader(
+            "does not match des (<init>, <num_of_transitions>, <num_of_states>)",
+        ))?
+        .extract();
+
+    Ok((
+        StateIndex::new(initial_txt.parse()?),
+        num_of_transitions_txt.parse()?,
+        num_of_states_txt.parse()?,
+    ))
+}
+
+/// Splits the input into at most the given number of chunks of roughly equal
+/// size, where every chunk ends at a line boundary.
+fn split_lines(input: &[u8], num_of_chunks: usize) -> Vec<&[u8]> {
+    let chunk_size = input.len().div_ceil(num_of_chunks.max(1)).max(1);
+
+    let mut result = Vec::with_capacity(num_of_chunks);
+    let mut start = 0;
+    while start < input.len() {
+        let end = (start + chunk_size).min(input.len());
+        let end = input[end..]
+            .iter()
+            .position(|&c| c == b'\n')
+            .map_or(input.len(), |position| end + position + 1);
+
+        result.push(&input[start..end]);
+        start = end;
+    }
+
+    result
+}
+
+/// The labels of a chunk in the order of their first occurrence, and its
+/// transitions where the labels are given by their index in these labels.
+type Chunk<'a> = (Vec<&'a str>, Vec<(StateIndex, usize, StateIndex)>);
+
+/// Parses the transitions of a chunk of complete lines, where empty lines are ignored.
+fn read_chunk(input: &[u8]) -> Result<Chunk<'_>, MercError> {
+    let mut labels_index: FxHashMap<&str, usize> = FxHashMap::default();
+    let mut labels = Vec::new();
+    let mut transitions = Vec::new();
+
+    for line in std::str::from_utf8(input)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (from_txt, label_txt, to_txt) =
+            read_transition(line).ok_or_else(|| IOError::InvalidTransition(line.to_string()))?;
+
+        let label = *labels_index.entry(label_txt).or_insert_with(|| {
+            labels.push(label_txt);
+            labels.len() - 1
+        });
+        transitions.push((
+            StateIndex::new(from_txt.parse()?),
+            label,
+            StateIndex::new(to_txt.parse()?),
+        ));
+    }
+
+    Ok((labels, transitions))
+}
+
 /// Dedicated function to parse the following transition formats:
 ///
 /// # Details
@@ -186,6 +507,10 @@ impl TransitionLabel for String {
         self == label
     }
 
+    fn from_string(label: &str) -> Result<Self, MercError> {
+        Ok(label.to_string())
+    }
+
     fn from_index(i: usize) -> Self {
         char::from_digit(i as u32, 36)
             .expect("Radix is less than 37, so should not panic")
@@ -195,7 +520,9 @@ impl TransitionLabel for String {
 
 #[cfg(test)]
 mod tests {
+    use crate::check_equivalent_probabilistic;
     use crate::random_lts_monolithic;
+    use crate::random_probabilistic_lts;
 
     use super::*;
 
@@ -271,4 +598,74 @@ mod tests {
             crate::check_equivalent(&lts, &result_lts);
         })
     }
+
+    #[test]
+    fn test_reading_probabilistic_aut() {
+        let file = "des (0 1/2 1, 2, 3)
+            (0, \"a\", 1 1/3 2)
+            (1, \"i\", 0)";
+
+        let lts = read_probabilistic_aut(file.as_bytes(), vec![]).unwrap();
+
+        assert_eq!(lts.num_of_states(), 3);
+        assert_eq!(lts.num_of_transitions(), 2);
+        assert_eq!(
+            lts.distribution(lts.initial_distribution()).to_string(),
+            "{0: 1/2, 1: 1/2}"
+        );
+
+        let transition = lts.outgoing_transitions(StateIndex::new(0)).next().unwrap();
+        assert_eq!(lts.distribution(transition.to).to_string(), "{1: 1/3, 2: 2/3}");
+
+        let transition = lts.outgoing_transitions(StateIndex::new(1)).next().unwrap();
+        assert!(lts.is_hidden_label(transition.label));
+
+        // The probabilities must be positive and add up to one.
+        let wrong_distribution = "des (0, 1, 2)
+            (0, \"a\", 1 3/2 0)";
+        assert!(read_probabilistic_aut(wrong_distribution.as_bytes(), vec![]).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_random_probabilistic_aut_io() {
+        random_test(100, |rng| {
+            let lts = random_probabilistic_lts::<String>(rng, 50, 3, 5, 3);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            write_probabilistic_aut(&mut buffer, &lts).unwrap();
+
+            let result_lts = read_probabilistic_aut(&buffer[0..], vec![]).unwrap();
+
+            check_equivalent_probabilistic(&lts, &result_lts);
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_random_aut_parallel_io() {
+        random_test(100, |rng| {
+            let lts = random_lts_monolithic::<String>(rng, 100, 3, 20);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            write_aut(&mut buffer, &lts).unwrap();
+
+            // Use small chunks such that the transitions are spread over multiple batches.
+            let result_lts = read_aut_parallel_impl(&buffer[0..], vec![], 3, 64).unwrap();
+
+            crate::check_equivalent(&lts, &result_lts);
+        })
+    }
+
+    #[test]
+    fn test_split_lines() {
+        let input = b"(0, a, 1)\n(1, b, 2)\n(2, c, 0)\n";
+
+        for num_of_chunks in 1..5 {
+            let chunks = split_lines(input, num_of_chunks);
+            assert!(chunks.len() <= num_of_chunks);
+            assert_eq!(chunks.concat(), input);
+            assert!(chunks.iter().all(|chunk| chunk.ends_with(b"\n")));
+        }
+    }
 }
@@ -0,0 +1,69 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::io::BufWriter;
+use std::io::Write;
+
+use log::info;
+
+use merc_utilities::MercError;
+
+use crate::LTS;
+
+/// Write the transitions of a labelled transition system as a CSV edge list
+/// with the header `source,label,target` to the given writer.
+///
+/// # Details
+///
+/// The labels are quoted when necessary, following RFC 4180. Note that the
+/// initial state and the states without transitions are not part of an edge
+/// list.
+pub fn write_csv(writer: &mut impl Write, lts: &impl LTS) -> Result<(), MercError> {
+    info!("Writing LTS in .csv format...");
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "source,label,target")?;
+
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            writeln!(
+                writer,
+                "{state_index},{},{}",
+                quote(&lts.labels()[transition.label].to_string()),
+                transition.to
+            )?;
+        }
+    }
+
+    info!("Finished writing LTS.");
+    Ok(())
+}
+
+/// Quotes a field that contains a separator, quote or line break, where the quotes are doubled.
+fn quote(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::read_aut;
+
+    #[test]
+    fn test_write_csv() {
+        let lts = read_aut(b"des (0,2,2)\n(0,\"a(1, 2)\",1)\n(1,i,0)\n" as &[u8], Vec::new()).unwrap();
+
+        let mut buffer = Vec::new();
+        write_csv(&mut buffer, &lts).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "source,label,target\n0,\"a(1, 2)\",1\n1,i,0\n"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+#![forbid(unsafe_code)]
+
+use std::io::BufWriter;
+use std::io::Write;
+
+use log::info;
+
+use merc_utilities::MercError;
+
+use crate::LTS;
+use crate::StateLabelFormatter;
+
+/// Write a labelled transition system in the Graphviz DOT format to the given
+/// writer, for example to visualise small LTSs using `dot -Tpdf`.
+///
+/// # Details
+///
+/// The initial state is marked by an incoming arrow, and the transitions with
+/// a hidden label are dashed. When the LTS has state labels, the values of
+/// every state are shown below its index.
+pub fn write_dot(writer: &mut impl Write, lts: &impl LTS) -> Result<(), MercError> {
+    info!("Writing LTS in .dot format...");
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "digraph lts {{")?;
+    writeln!(writer, "  rankdir=LR;")?;
+    writeln!(writer, "  graph [fontname=\"DejaVu Sans\", splines=true];")?;
+    writeln!(writer, "  node [fontname=\"DejaVu Sans\", shape=circle];")?;
+    writeln!(
+        writer,
+        "  edge [fontname=\"DejaVu Sans\", color=\"#444444\", arrowsize=0.9];"
+    )?;
+
+    for state_index in lts.iter_states() {
+        match lts.state_labels() {
+            Some(state_labels) => writeln!(
+                writer,
+                "  s{state_index} [shape=box, style=rounded, label=\"{state_index}\\n{}\"];",
+                escape(&StateLabelFormatter(state_labels.values(state_index)).to_string())
+            )?,
+            None => writeln!(writer, "  s{state_index} [label=\"{state_index}\"];")?,
+        }
+    }
+
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            let style = if lts.is_hidden_label(transition.label) {
+                ", style=dashed"
+            } else {
+                ""
+            };
+
+            writeln!(
+                writer,
+                "  s{state_index} -> s{} [label=\"{}\"{style}];",
+                transition.to,
+                escape(&lts.labels()[transition.label].to_string())
+            )?;
+        }
+    }
+
+    writeln!(writer, "  init [shape=point, width=0.05, label=\"\"];")?;
+    writeln!(writer, "  init -> s{} [arrowsize=0.6];", lts.initial_state_index())?;
+    writeln!(writer, "}}")?;
+
+    info!("Finished writing LTS.");
+    Ok(())
+}
+
+/// Escapes the quotes and backslashes of a string in the DOT format.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::read_aut;
+    use crate::read_lts;
+
+    #[test]
+    fn test_write_dot() {
+        let lts = read_aut(b"des (0,2,2)\n(0,\"a\"b\",1)\n(1,i,0)\n" as &[u8], Vec::new()).unwrap();
+
+        let mut buffer = Vec::new();
+        write_dot(&mut buffer, &lts).unwrap();
+        let result = String::from_utf8(buffer).unwrap();
+
+        assert!(result.contains("init -> s0"));
+        assert!(result.contains("s1 -> s0 [label=\"i\", style=dashed];"));
+        assert!(result.contains(r#"s0 -> s1 [label="a\"b"];"#));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_write_dot_state_labels() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+
+        let mut buffer = Vec::new();
+        write_dot(&mut buffer, &lts).unwrap();
+        let result = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(result.matches("shape=box").count(), lts.num_of_states());
+    }
+}
@@ -0,0 +1,137 @@
+#![forbid(unsafe_code)]
+
+use std::io::BufWriter;
+use std::io::Write;
+
+use log::info;
+
+use merc_utilities::MercError;
+
+use crate::LTS;
+
+/// Write a labelled transition system as a JSON object with its nodes and
+/// edges to the given writer, which is convenient for scripting.
+///
+/// # Details
+///
+/// The structure is as follows, where the parameters and the values of every
+/// node are only present when the LTS has state labels:
+///
+/// ```plain
+/// {
+///   "initial_state": 0,
+///   "parameters": [{"name": "x", "sort": "Nat"}],
+///   "nodes": [{"id": 0, "values": [["0"]]}, ...],
+///   "edges": [{"source": 0, "label": "a", "target": 1, "hidden": false}, ...]
+/// }
+/// ```
+///
+/// The nodes and edges are written one at a time, so the LTS is not converted
+/// into a JSON value in memory.
+pub fn write_json(writer: &mut impl Write, lts: &impl LTS) -> Result<(), MercError> {
+    info!("Writing LTS in .json format...");
+
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"initial_state\": {},", lts.initial_state_index())?;
+
+    if let Some(state_labels) = lts.state_labels() {
+        write!(writer, "  \"parameters\": [")?;
+        for (index, (name, sort)) in state_labels.parameters().iter().enumerate() {
+            if index > 0 {
+                write!(writer, ", ")?;
+            }
+
+            write!(
+                writer,
+                "{{\"name\": {}, \"sort\": {}}}",
+                serde_json::to_string(name)?,
+                serde_json::to_string(sort)?
+            )?;
+        }
+        writeln!(writer, "],")?;
+    }
+
+    writeln!(writer, "  \"nodes\": [")?;
+    for state_index in lts.iter_states() {
+        write!(writer, "    {{\"id\": {state_index}")?;
+        if let Some(state_labels) = lts.state_labels() {
+            write!(
+                writer,
+                ", \"values\": {}",
+                serde_json::to_string(state_labels.values(state_index))?
+            )?;
+        }
+
+        let separator = if state_index.value() + 1 < lts.num_of_states() {
+            ","
+        } else {
+            ""
+        };
+        writeln!(writer, "}}{separator}")?;
+    }
+    writeln!(writer, "  ],")?;
+
+    write!(writer, "  \"edges\": [")?;
+    let mut first = true;
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            write!(writer, "{}", if first { "\n" } else { ",\n" })?;
+            first = false;
+
+            write!(
+                writer,
+                "    {{\"source\": {state_index}, \"label\": {}, \"target\": {}, \"hidden\": {}}}",
+                serde_json::to_string(&lts.labels()[transition.label].to_string())?,
+                transition.to,
+                lts.is_hidden_label(transition.label)
+            )?;
+        }
+    }
+    writeln!(writer, "\n  ]")?;
+    writeln!(writer, "}}")?;
+
+    info!("Finished writing LTS.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::Value;
+
+    use merc_utilities::random_test;
+
+    use crate::random_lts_monolithic;
+    use crate::read_lts;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_write_json() {
+        random_test(100, |rng| {
+            let lts = random_lts_monolithic::<String>(rng, 10, 3, 3);
+
+            let mut buffer = Vec::new();
+            write_json(&mut buffer, &lts).unwrap();
+
+            let result: Value = serde_json::from_slice(&buffer).unwrap();
+            assert_eq!(result["initial_state"], lts.initial_state_index().value());
+            assert_eq!(result["nodes"].as_array().unwrap().len(), lts.num_of_states());
+            assert_eq!(result["edges"].as_array().unwrap().len(), lts.num_of_transitions());
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_write_json_state_labels() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+
+        let mut buffer = Vec::new();
+        write_json(&mut buffer, &lts).unwrap();
+
+        let result: Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(result["parameters"][0]["name"], "s1_S");
+        assert_eq!(result["nodes"][0]["values"].as_array().unwrap().len(), 1);
+    }
+}
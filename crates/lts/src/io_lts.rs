@@ -1,6 +1,7 @@
 #![forbid(unsafe_code)]
 
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::io::BufReader;
 use std::io::BufWriter;
 use std::io::Read;
@@ -15,18 +16,38 @@ use merc_aterm::ATermStreamable;
 use merc_aterm::ATermWrite;
 use merc_aterm::BinaryATermReader;
 use merc_aterm::BinaryATermWriter;
+use merc_aterm::Symb;
 use merc_aterm::Symbol;
+use merc_aterm::Term;
+use merc_aterm::is_empty_list_term;
 use merc_aterm::is_list_term;
+use merc_data::DataApplication;
+use merc_data::DataExpression;
+use merc_data::DataExpressionRef;
+use merc_data::DataFunctionSymbol;
 use merc_data::DataSpecification;
+use merc_data::DataVariable;
+use merc_data::SortExpression;
+use merc_data::is_data_application;
+use merc_data::is_data_function_symbol;
 use merc_io::LargeFormatter;
 use merc_io::TimeProgress;
 use merc_utilities::MercError;
+use num::BigUint;
+use num::One;
+use num::Zero;
 
+use crate::Distribution;
 use crate::LTS;
 use crate::LabelledTransitionSystem;
 use crate::LtsBuilder;
+use crate::LtsSpecification;
 use crate::MultiAction;
+use crate::ProbabilisticLts;
+use crate::ProbabilisticLtsBuilder;
+use crate::Probability;
 use crate::StateIndex;
+use crate::StateLabels;
 
 /// Loads a labelled transition system from the binary 'lts' format of the mCRL2 toolset.
 pub fn read_lts(
@@ -41,10 +62,14 @@ pub fn read_lts(
         return Err("Stream does not contain a labelled transition system (LTS).".into());
     }
 
-    // Read the data specification, parameters, and actions.
-    let _data_spec = DataSpecification::read(&mut reader)?;
-    let _parameters = reader.read_aterm()?;
-    let _actions = reader.read_aterm()?;
+    let specification = read_specification(&mut reader)?;
+    let mut state_labels = StateLabels::new(
+        specification
+            .parameters()
+            .iter()
+            .map(|parameter| (parameter.name().to_string(), parameter.sort().to_string()))
+            .collect(),
+    );
 
     // Use a cache to avoid translating the same multi-action multiple times.
     let mut multi_actions: HashMap<ATerm, MultiAction> = HashMap::new();
@@ -89,19 +114,28 @@ pub fn read_lts(
 
                     progress.print(builder.num_of_transitions());
                 } else if t == probabilistic_transition_mark() {
-                    return Err("Probabilistic transitions are not supported yet.".into());
-                } else if is_list_term(&t) {
-                    // State labels can be ignored for the reduction algorithm.
+                    return Err(
+                        "Probabilistic transitions are not supported, use read_probabilistic_lts instead.".into(),
+                    );
+                } else if is_list_term(&t) || is_empty_list_term(&t) {
+                    // The state labels are given in the order of the state indices.
+                    let label: ATermList<ATerm> = t.into();
+                    state_labels.push(
+                        label
+                            .iter()
+                            .map(|state| {
+                                let mut values = Vec::new();
+                                flatten_balanced_tree(&state, &mut values);
+                                values
+                            })
+                            .collect(),
+                    );
                 } else if t == initial_state_marker() {
-                    let length = ATermInt::from(reader.read_aterm()?.ok_or("Missing initial state length")?).value();
-                    if length != 1 {
-                        return Err("Initial state length greater than 1 is not supported.".into());
-                    }
-
-                    initial_state = Some(StateIndex::new(
-                        ATermInt::from(reader.read_aterm()?.ok_or("Missing initial state index")?).value(),
-                    ));
-                    println!("Initial state: {:?}", initial_state);
+                    initial_state = Some(
+                        read_distribution(&mut reader)?
+                            .as_state()
+                            .ok_or("Initial distributions are not supported, use read_probabilistic_lts instead.")?,
+                    );
                 } else {
                     return Err(format!("Unexpected term in LTS stream: {}", t).into());
                 }
@@ -111,7 +145,27 @@ pub fn read_lts(
     }
     info!("Finished reading LTS.");
 
-    Ok(builder.finish(initial_state.ok_or("Missing initial state")?))
+    if state_labels.is_empty() {
+        return Ok(builder
+            .finish(initial_state.ok_or("Missing initial state")?)
+            .with_specification(specification));
+    }
+
+    // The state labels also determine the number of states, including the deadlock states without incoming transitions.
+    if state_labels.len() < builder.num_of_states() {
+        return Err(format!(
+            "The LTS has {} state labels, but {} states.",
+            state_labels.len(),
+            builder.num_of_states()
+        )
+        .into());
+    }
+
+    builder.require_num_of_states(state_labels.len());
+    Ok(builder
+        .finish(initial_state.ok_or("Missing initial state")?)
+        .with_state_labels(state_labels)
+        .with_specification(specification))
 }
 
 /// Write a labelled transition system in binary 'lts' format to the given
@@ -155,11 +209,12 @@ where
     let mut writer = BinaryATermWriter::new(BufWriter::new(writer))?;
 
     writer.write_aterm(&lts_marker())?;
-
-    // Write the data specification, parameters, and actions.
-    DataSpecification::default().write(&mut writer)?;
-    writer.write_aterm(&ATermList::<ATerm>::empty().into())?; // Empty parameters
-    writer.write_aterm(&ATermList::<ATerm>::empty().into())?; // Empty action labels
+    write_specification(
+        &mut writer,
+        lts.specification(),
+        lts.state_labels()
+            .map_or(&[] as &[(String, String)], |labels| labels.parameters()),
+    )?;
 
     // Convert the internal multi-actions to the ATerm representation that mCRL2 expects.
     let label_terms = lts
@@ -170,8 +225,7 @@ where
 
     // Write the initial state.
     writer.write_aterm(&initial_state_marker())?;
-    writer.write_aterm(&ATermInt::new(1))?; // Length of initial state is 1.
-    writer.write_aterm(&ATermInt::new(*lts.initial_state_index()))?;
+    write_distribution(&mut writer, &Distribution::dirac(lts.initial_state_index()))?;
 
     let num_of_transitions = lts.num_of_transitions();
     let progress = TimeProgress::new(
@@ -198,10 +252,373 @@ where
         }
     }
 
+    // Write the state labels in the order of the state indices.
+    if let Some(state_labels) = lts.state_labels() {
+        for state_index in lts.iter_states() {
+            let label = ATermList::<ATerm>::from_double_iter(state_labels.values(state_index).iter().map(|values| {
+                // Values that cannot be parsed, such as internal mCRL2 functions, are written as constants to preserve their text.
+                let values: Vec<ATerm> = values
+                    .iter()
+                    .map(|value| match DataExpression::from_string(value) {
+                        Ok(expression) => expression.protect(),
+                        Err(_) => DataFunctionSymbol::new(value).protect(),
+                    })
+                    .collect();
+                balanced_tree(&values)
+            }));
+            writer.write_aterm(&label.into())?;
+        }
+    }
+
     info!("Finished writing LTS.");
     Ok(())
 }
 
+/// Loads a probabilistic labelled transition system from the binary 'lts'
+/// format of the mCRL2 toolset.
+///
+/// # Details
+///
+/// The state labels are not kept, because the [ProbabilisticLts] does not
+/// support them, but they do determine the number of states.
+pub fn read_probabilistic_lts(
+    reader: impl Read,
+    hidden_labels: Vec<String>,
+) -> Result<ProbabilisticLts<MultiAction>, MercError> {
+    info!("Reading probabilistic LTS in .lts format...");
+
+    let mut reader = BinaryATermReader::new(BufReader::new(reader))?;
+
+    if reader.read_aterm()? != Some(lts_marker()) {
+        return Err("Stream does not contain a labelled transition system (LTS).".into());
+    }
+
+    let specification = read_specification(&mut reader)?;
+
+    // Use a cache to avoid translating the same multi-action multiple times.
+    let mut multi_actions: HashMap<ATerm, MultiAction> = HashMap::new();
+
+    let mut initial_distribution: Option<Distribution> = None;
+    let mut num_of_state_labels = 0;
+    let mut builder = ProbabilisticLtsBuilder::new(Vec::new(), hidden_labels);
+
+    let progress = TimeProgress::new(
+        |num_of_transitions| {
+            info!("Read {num_of_transitions} transitions...");
+        },
+        1,
+    );
+
+    while let Some(t) = reader.read_aterm()? {
+        if t == transition_marker() || t == probabilistic_transition_mark() {
+            let from: ATermInt = reader.read_aterm()?.ok_or("Missing from state")?.into();
+            let label = reader.read_aterm()?.ok_or("Missing transition label")?;
+            let to = if t == transition_marker() {
+                let to: ATermInt = reader.read_aterm()?.ok_or("Missing to state")?.into();
+                Distribution::dirac(StateIndex::new(to.value()))
+            } else {
+                read_distribution(&mut reader)?
+            };
+
+            let multi_action = match multi_actions.entry(label) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let multi_action = MultiAction::from_mcrl2_aterm(entry.key().clone())?;
+                    entry.insert(multi_action)
+                }
+            };
+            builder.add_transition(StateIndex::new(from.value()), multi_action, to);
+
+            progress.print(builder.num_of_transitions());
+        } else if is_list_term(&t) || is_empty_list_term(&t) {
+            num_of_state_labels += 1;
+        } else if t == initial_state_marker() {
+            initial_distribution = Some(read_distribution(&mut reader)?);
+        } else {
+            return Err(format!("Unexpected term in LTS stream: {}", t).into());
+        }
+    }
+    info!("Finished reading probabilistic LTS.");
+
+    builder.require_num_of_states(num_of_state_labels);
+    Ok(builder
+        .finish(initial_distribution.ok_or("Missing initial state")?)
+        .with_specification(specification))
+}
+
+/// Write a probabilistic labelled transition system in the binary 'lts'
+/// format to the given writer, see [write_lts] for the structure.
+///
+/// # Details
+///
+/// The transitions to a single state are written as ordinary transitions, and
+/// the other transitions as follows:
+///
+/// ```plain
+/// probabilistic transition:
+///     probabilistic_transition_marker: ATerm
+///     from: ATermInt
+///     label: ATerm (the multi_action)
+///     to: distribution
+/// ```
+///
+/// A distribution, which is also used for the initial state, consists of its
+/// size as an ATermInt followed by the state index as an ATermInt when the size
+/// is one, and pairs of state indices and probabilities (data expressions of
+/// sort Real) otherwise.
+///
+/// Finally, an empty state label is written for every state to preserve the
+/// number of states.
+pub fn write_probabilistic_lts(writer: &mut impl Write, lts: &ProbabilisticLts<MultiAction>) -> Result<(), MercError> {
+    info!("Writing probabilistic LTS in .lts format...");
+
+    let mut writer = BinaryATermWriter::new(BufWriter::new(writer))?;
+
+    writer.write_aterm(&lts_marker())?;
+    write_specification(&mut writer, lts.specification(), &[])?;
+
+    let label_terms = lts
+        .labels()
+        .iter()
+        .map(|label| label.to_mcrl2_aterm())
+        .collect::<Result<Vec<ATerm>, MercError>>()?;
+
+    writer.write_aterm(&initial_state_marker())?;
+    write_distribution(&mut writer, lts.distribution(lts.initial_distribution()))?;
+
+    for state in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state) {
+            let to = lts.distribution(transition.to);
+            if let Some(to) = to.as_state() {
+                writer.write_aterm(&transition_marker())?;
+                writer.write_aterm(&ATermInt::new(*state))?;
+                writer.write_aterm(&label_terms[transition.label.value()])?;
+                writer.write_aterm(&ATermInt::new(*to))?;
+            } else {
+                writer.write_aterm(&probabilistic_transition_mark())?;
+                writer.write_aterm(&ATermInt::new(*state))?;
+                writer.write_aterm(&label_terms[transition.label.value()])?;
+                write_distribution(&mut writer, to)?;
+            }
+        }
+    }
+
+    // Write an empty state label for every state, such that the states without incoming and outgoing transitions are kept.
+    for _ in lts.iter_states() {
+        writer.write_aterm(&ATermList::<ATerm>::empty().into())?;
+    }
+
+    info!("Finished writing probabilistic LTS.");
+    Ok(())
+}
+
+/// Reads the data specification, process parameters and action declarations that precede the states and transitions.
+fn read_specification(reader: &mut impl ATermRead) -> Result<LtsSpecification, MercError> {
+    let data_spec = DataSpecification::read(reader)?;
+    let parameters: ATermList<DataVariable> = reader.read_aterm()?.ok_or("Missing parameters")?.into();
+    let actions: ATermList<ATerm> = reader.read_aterm()?.ok_or("Missing action declarations")?.into();
+
+    Ok(LtsSpecification::new(&data_spec, parameters, actions))
+}
+
+/// Writes the given specification, or otherwise an empty specification with the given parameters, consisting of pairs of names and sorts.
+fn write_specification(
+    writer: &mut impl ATermWrite,
+    specification: Option<&LtsSpecification>,
+    parameters: &[(String, String)],
+) -> Result<(), MercError> {
+    if let Some(specification) = specification {
+        specification.data_specification().write(writer)?;
+        writer.write_aterm(&specification.parameters().into())?;
+        writer.write_aterm(&specification.action_declarations().into())?;
+    } else {
+        // Without a specification the parameters can only be derived from the state labels.
+        DataSpecification::default().write(writer)?;
+        writer.write_aterm(
+            &ATermList::<DataVariable>::from_double_iter(
+                parameters
+                    .iter()
+                    .map(|(name, sort)| DataVariable::with_sort(name.as_str(), SortExpression::new(sort).copy())),
+            )
+            .into(),
+        )?;
+        writer.write_aterm(&ATermList::<ATerm>::empty().into())?; // Empty action labels
+    }
+
+    Ok(())
+}
+
+/// Reads a distribution, which is either a single state or a list of states with their probabilities.
+fn read_distribution(reader: &mut impl ATermRead) -> Result<Distribution, MercError> {
+    let length = ATermInt::from(reader.read_aterm()?.ok_or("Missing distribution length")?).value();
+    if length == 1 {
+        let state: ATermInt = reader.read_aterm()?.ok_or("Missing state index")?.into();
+        return Ok(Distribution::dirac(StateIndex::new(state.value())));
+    }
+
+    let mut entries = Vec::with_capacity(length);
+    for _ in 0..length {
+        let state: ATermInt = reader.read_aterm()?.ok_or("Missing state index")?.into();
+        let probability = read_probability(&reader.read_aterm()?.ok_or("Missing probability")?)?;
+        entries.push((StateIndex::new(state.value()), probability));
+    }
+
+    if entries.iter().map(|(_, probability)| probability).sum::<Probability>() != Probability::one() {
+        return Err("The probabilities of a distribution do not add up to one.".into());
+    }
+
+    Ok(Distribution::new(entries))
+}
+
+/// Writes a distribution in the format that is read by [read_distribution].
+fn write_distribution(writer: &mut impl ATermWrite, distribution: &Distribution) -> Result<(), MercError> {
+    writer.write_aterm(&ATermInt::new(distribution.len()))?;
+    if let Some(state) = distribution.as_state() {
+        return writer.write_aterm(&ATermInt::new(*state));
+    }
+
+    for (state, probability) in distribution.iter() {
+        writer.write_aterm(&ATermInt::new(**state))?;
+        writer.write_aterm(&write_probability(probability))?;
+    }
+
+    Ok(())
+}
+
+/// Converts a probability given by a data expression `@cReal(@cInt(n), d)` of
+/// sort Real, where `n` is a natural number and `d` a positive number, to a
+/// fraction.
+fn read_probability(term: &ATerm) -> Result<Probability, MercError> {
+    let expression = DataExpressionRef::from(term.copy());
+    let invalid = || format!("The probability {term} is not a positive fraction.");
+
+    let (name, arguments) = application(&expression).ok_or_else(invalid)?;
+    match (name.as_str(), arguments.as_slice()) {
+        ("@cReal", [numerator, denominator]) => {
+            let (name, arguments) = application(numerator).ok_or_else(invalid)?;
+            let numerator = match (name.as_str(), arguments.as_slice()) {
+                ("@cInt", [numerator]) => nat_value(numerator).ok_or_else(invalid)?,
+                _ => return Err(invalid().into()),
+            };
+            let denominator = pos_value(denominator).ok_or_else(invalid)?;
+            let probability = Probability::new(numerator.into(), denominator.into());
+
+            if probability.is_zero() || probability > Probability::one() {
+                return Err(invalid().into());
+            }
+            Ok(probability)
+        }
+        _ => Err(invalid().into()),
+    }
+}
+
+/// Returns the data expression of sort Real that is read by [read_probability].
+fn write_probability(probability: &Probability) -> ATerm {
+    let numerator = probability
+        .numer()
+        .to_biguint()
+        .expect("Probabilities are not negative");
+    let denominator = probability.denom().to_biguint().expect("Denominators are positive");
+
+    let numerator = if numerator.is_zero() {
+        DataFunctionSymbol::new("@c0").protect()
+    } else {
+        apply("@cNat", &[pos_expression(&numerator)])
+    };
+
+    apply("@cReal", &[apply("@cInt", &[numerator]), pos_expression(&denominator)])
+}
+
+/// Returns the name of the function symbol and the arguments of the given expression, if it is an application or a constant.
+fn application<'a>(expression: &DataExpressionRef<'a>) -> Option<(String, Vec<DataExpressionRef<'a>>)> {
+    if !is_data_application(expression) && !is_data_function_symbol(expression) {
+        return None;
+    }
+
+    Some((
+        expression.data_function_symbol().name().to_string(),
+        expression.data_arguments().collect(),
+    ))
+}
+
+/// Returns the value of a natural number, given by either `@c0` or `@cNat(p)`.
+fn nat_value(expression: &DataExpressionRef<'_>) -> Option<BigUint> {
+    let (name, arguments) = application(expression)?;
+    match (name.as_str(), arguments.as_slice()) {
+        ("@c0", []) => Some(BigUint::zero()),
+        ("@cNat", [pos]) => pos_value(pos),
+        _ => None,
+    }
+}
+
+/// Returns the value of a positive number, which mCRL2 represents by a list
+/// of 64-bit digits, or by a list of bits in older versions.
+fn pos_value(expression: &DataExpressionRef<'_>) -> Option<BigUint> {
+    let (name, arguments) = application(expression)?;
+    match (name.as_str(), arguments.as_slice()) {
+        ("@most_significant_digit", [digit]) => Some(BigUint::from(digit.annotation()?)),
+        ("@concat_digit", [pos, digit]) => Some((pos_value(pos)? << 64) + BigUint::from(digit.annotation()?)),
+        ("@c1", []) => Some(BigUint::one()),
+        ("@cDub", [bit, pos]) => {
+            let (name, arguments) = application(bit)?;
+            let bit = match (name.as_str(), arguments.as_slice()) {
+                ("true", []) => 1u32,
+                ("false", []) => 0u32,
+                _ => return None,
+            };
+            Some(pos_value(pos)? * 2u32 + bit)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the data expression for the given positive number, see [pos_value].
+fn pos_expression(value: &BigUint) -> ATerm {
+    // The digits are ordered from least to most significant.
+    let digits = value.to_u64_digits();
+    let (most_significant, rest) = digits.split_last().expect("A positive number has at least one digit");
+
+    let mut result = apply(
+        "@most_significant_digit",
+        &[ATermInt::new(*most_significant as usize).into()],
+    );
+    for digit in rest.iter().rev() {
+        result = apply("@concat_digit", &[result, ATermInt::new(*digit as usize).into()]);
+    }
+
+    result
+}
+
+/// Returns the application of the function symbol with the given name, which has an unknown sort, to the arguments.
+fn apply(name: &str, arguments: &[ATerm]) -> ATerm {
+    DataApplication::with_args(&DataFunctionSymbol::new(name), arguments).protect()
+}
+
+/// Appends the leaves of the given balanced tree, which is how mCRL2 stores
+/// the values of a state, to the result.
+fn flatten_balanced_tree<'a, 'b>(tree: &'b impl Term<'a, 'b>, result: &mut Vec<String>) {
+    let symbol = tree.get_head_symbol();
+    if symbol.name() == "@node@" && symbol.arity() == 2 {
+        flatten_balanced_tree(&tree.arg(0), result);
+        flatten_balanced_tree(&tree.arg(1), result);
+    } else if symbol.name() != "@empty@" || symbol.arity() != 0 {
+        result.push(DataExpression::from(tree.protect()).to_string());
+    }
+}
+
+/// Returns a balanced tree with the given leaves, where the left subtree
+/// contains the first half of the leaves rounded up.
+fn balanced_tree(leaves: &[ATerm]) -> ATerm {
+    match leaves {
+        [] => ATerm::constant(&Symbol::new("@empty@", 0)),
+        [leaf] => leaf.clone(),
+        _ => {
+            let (left, right) = leaves.split_at(leaves.len().div_ceil(2));
+            ATerm::with_args(&Symbol::new("@node@", 2), &[balanced_tree(left), balanced_tree(right)]).protect()
+        }
+    }
+}
+
 /// Returns the ATerm marker for a labelled transition system.
 fn lts_marker() -> ATerm {
     ATerm::constant(&Symbol::new("labelled_transition_system", 0))
@@ -226,10 +643,15 @@ fn probabilistic_transition_mark() -> ATerm {
 mod tests {
     use super::*;
 
+    use num::BigInt;
+
     use merc_utilities::random_test;
 
+    use crate::DistributionIndex;
     use crate::LTS;
+    use crate::check_equivalent_probabilistic;
     use crate::random_lts_monolithic;
+    use crate::random_probabilistic_lts;
 
     #[test]
     #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
@@ -239,6 +661,40 @@ mod tests {
         assert_eq!(lts.num_of_states(), 74);
         assert_eq!(lts.num_of_transitions(), 92);
         assert_eq!(*lts.initial_state_index(), 0);
+
+        let state_labels = lts.state_labels().expect("The LTS should have state labels");
+        assert_eq!(state_labels.parameters().len(), 11);
+        assert_eq!(state_labels.parameters()[0], ("s1_S".to_string(), "Pos".to_string()));
+        assert_eq!(state_labels.len(), 74);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_write_lts_state_labels() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_lts(&mut buffer, &lts).unwrap();
+        let result_lts = read_lts(&buffer[0..], vec![]).unwrap();
+
+        assert_eq!(lts.state_labels(), result_lts.state_labels());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_write_lts_specification() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+
+        let specification = lts.specification().expect("The LTS should have a specification");
+        assert_eq!(specification.data_specification().aliases().len(), 2);
+        assert_eq!(specification.parameters().iter().count(), 11);
+        assert_eq!(specification.action_declarations().iter().count(), 21);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_lts(&mut buffer, &lts).unwrap();
+        let result_lts = read_lts(&buffer[0..], vec![]).unwrap();
+
+        assert_eq!(lts.specification(), result_lts.specification());
     }
 
     #[test]
@@ -255,4 +711,51 @@ mod tests {
             crate::check_equivalent(&lts, &result_lts);
         })
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_random_probabilistic_lts_io() {
+        random_test(100, |rng| {
+            let lts = random_probabilistic_lts::<MultiAction>(rng, 50, 3, 5, 3);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            write_probabilistic_lts(&mut buffer, &lts).unwrap();
+
+            let result_lts = read_probabilistic_lts(&buffer[0..], vec![]).unwrap();
+            check_equivalent_probabilistic(&lts, &result_lts);
+
+            // The non-probabilistic reader only accepts LTSs without proper distributions.
+            let is_probabilistic = (0..lts.num_of_distributions())
+                .any(|index| lts.distribution(DistributionIndex::new(index)).as_state().is_none());
+            assert_eq!(read_lts(&buffer[0..], vec![]).is_err(), is_probabilistic);
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_probability_conversion() {
+        for (numerator, denominator) in [(1u64, 3u64), (2, 2), (u64::MAX, u64::MAX - 1)] {
+            // Multiply the denominator to exceed a single 64-bit digit.
+            let probability = Probability::new(
+                numerator.into(),
+                BigInt::from(denominator) * BigInt::from(u64::MAX) * BigInt::from(7),
+            );
+
+            assert_eq!(read_probability(&write_probability(&probability)).unwrap(), probability);
+        }
+
+        // The older representation of positive numbers as a list of bits, here 1/2.
+        let two = apply(
+            "@cDub",
+            &[
+                DataFunctionSymbol::new("false").protect(),
+                DataFunctionSymbol::new("@c1").protect(),
+            ],
+        );
+        let one = apply("@cNat", &[DataFunctionSymbol::new("@c1").protect()]);
+        let half = apply("@cReal", &[apply("@cInt", &[one]), two]);
+        assert_eq!(read_probability(&half).unwrap(), Probability::new(1.into(), 2.into()));
+
+        assert!(read_probability(&DataFunctionSymbol::new("@c0").protect()).is_err());
+    }
 }
@@ -13,7 +13,9 @@ use merc_utilities::TagIndex;
 use crate::LTS;
 use crate::LabelIndex;
 use crate::LabelTag;
+use crate::LtsSpecification;
 use crate::StateIndex;
+use crate::StateLabels;
 use crate::Transition;
 use crate::TransitionLabel;
 
@@ -36,6 +38,12 @@ pub struct LabelledTransitionSystem<Label> {
 
     /// The index of the initial state.
     initial_state: StateIndex,
+
+    /// The optional values of the process parameters for every state.
+    state_labels: Option<StateLabels>,
+
+    /// The optional specification from which the LTS was generated.
+    specification: Option<LtsSpecification>,
 }
 
 impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
@@ -137,6 +145,8 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
             states,
             transition_labels,
             transition_to,
+            state_labels: None,
+            specification: None,
         }
     }
 
@@ -186,6 +196,8 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
             states,
             transition_labels,
             transition_to,
+            state_labels: None,
+            specification: None,
         }
     }
 
@@ -246,6 +258,24 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
         self.states.push(self.num_of_transitions());
         debug_assert_eq!(self.num_of_states(), total_number_of_states);
 
+        // The state labels can only be combined when both LTSs have the same parameters.
+        let state_labels = match (self.state_labels, other.state_labels()) {
+            (Some(mut state_labels), Some(other_state_labels))
+                if state_labels.parameters() == other_state_labels.parameters() =>
+            {
+                for state_index in other.iter_states() {
+                    state_labels.push(other_state_labels.values(state_index).to_vec());
+                }
+                Some(state_labels)
+            }
+            _ => None,
+        };
+
+        // Similarly, the specification is only kept when both LTSs originate from the same specification.
+        let specification = self
+            .specification
+            .filter(|specification| other.specification() == Some(specification));
+
         (
             Self {
                 initial_state: self.initial_state,
@@ -253,6 +283,8 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
                 states: self.states,
                 transition_labels: self.transition_labels,
                 transition_to: self.transition_to,
+                state_labels,
+                specification,
             },
             StateIndex::new(offset + other.initial_state_index().value()),
         )
@@ -284,6 +316,8 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
             states,
             transition_labels: lts.transition_labels,
             transition_to: lts.transition_to,
+            state_labels: lts.state_labels.map(|state_labels| state_labels.permute(permutation)),
+            specification: lts.specification,
         }
     }
 
@@ -297,9 +331,29 @@ impl<Label: TransitionLabel> LabelledTransitionSystem<Label> {
             states: self.states,
             transition_labels: self.transition_labels,
             transition_to: self.transition_to,
+            state_labels: self.state_labels,
+            specification: self.specification,
         }
     }
 
+    /// Consumes the LTS and sets the values of the process parameters for every state.
+    pub fn with_state_labels(mut self, state_labels: StateLabels) -> Self {
+        assert_eq!(
+            state_labels.len(),
+            self.num_of_states(),
+            "Every state should have a state label"
+        );
+
+        self.state_labels = Some(state_labels);
+        self
+    }
+
+    /// Consumes the LTS and sets the specification from which it was generated.
+    pub fn with_specification(mut self, specification: LtsSpecification) -> Self {
+        self.specification = Some(specification);
+        self
+    }
+
     /// Returns metrics about the LTS.
     pub fn metrics(&self) -> LtsMetrics {
         LtsMetrics {
@@ -355,6 +409,14 @@ impl<L: TransitionLabel> LTS for LabelledTransitionSystem<L> {
         label_index.value() == 0
     }
 
+    fn state_labels(&self) -> Option<&StateLabels> {
+        self.state_labels.as_ref()
+    }
+
+    fn specification(&self) -> Option<&LtsSpecification> {
+        self.specification.as_ref()
+    }
+
     fn merge_disjoint<T: LTS<Label = Self::Label>>(self, other: &T) -> (Self, StateIndex) {
         self.merge_disjoint_impl(other)
     }
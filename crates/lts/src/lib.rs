@@ -1,27 +1,49 @@
 #![doc = include_str!("../README.md")]
 
+mod action_rename;
+mod compression;
 mod incoming_transitions;
 mod io;
 mod io_aut;
 mod io_bcg;
+mod io_csv;
+mod io_dot;
+mod io_json;
 mod io_lts;
 mod labelled_transition_system;
 mod lts;
 mod lts_builder;
 mod lts_builder_fast;
+mod lts_specification;
 mod multi_action;
+mod parallel_composition;
+mod probabilistic_lts;
+mod probabilistic_lts_builder;
 mod product_lts;
 mod random_lts;
+mod state_labels;
+mod transform;
 
+pub use action_rename::*;
+pub use compression::*;
 pub use incoming_transitions::*;
 pub use io::*;
 pub use io_aut::*;
 pub use io_bcg::*;
+pub use io_csv::*;
+pub use io_dot::*;
+pub use io_json::*;
 pub use io_lts::*;
 pub use labelled_transition_system::*;
 pub use lts::*;
 pub use lts_builder::*;
 pub use lts_builder_fast::*;
+pub use lts_specification::*;
 pub use multi_action::*;
+pub use parallel_composition::*;
+pub use probabilistic_lts::*;
+pub use probabilistic_lts_builder::*;
 pub use product_lts::*;
 pub use random_lts::*;
+pub use state_labels::*;
+pub use transform::*;
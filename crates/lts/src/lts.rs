@@ -5,9 +5,12 @@
 use std::fmt;
 use std::hash::Hash;
 
+use merc_utilities::MercError;
 use merc_utilities::TagIndex;
 
 use crate::LabelledTransitionSystem;
+use crate::LtsSpecification;
+use crate::StateLabels;
 
 /// A unique type for the labels.
 pub struct LabelTag;
@@ -57,6 +60,16 @@ where
     /// Returns true iff the given label index is a hidden label.
     fn is_hidden_label(&self, label_index: LabelIndex) -> bool;
 
+    /// Returns the values of the process parameters for every state, if these are known.
+    fn state_labels(&self) -> Option<&StateLabels> {
+        None
+    }
+
+    /// Returns the specification that the LTS was generated from, if this is known.
+    fn specification(&self) -> Option<&LtsSpecification> {
+        None
+    }
+
     /// Consumes the current LTS and merges it with another one, returning the
     /// disjoint merged LTS and the initial state of the other LTS in the merged
     /// LTS.
@@ -83,6 +96,9 @@ pub trait TransitionLabel: Ord + Hash + Eq + Clone + fmt::Display + fmt::Debug {
     /// Returns true iff this label matches the given string label.
     fn matches_label(&self, label: &str) -> bool;
 
+    /// Parses a label from its textual representation, which is the inverse of [fmt::Display] for visible labels.
+    fn from_string(label: &str) -> Result<Self, MercError>;
+
     /// Used for generating labels for the random LTSs
     fn from_index(i: usize) -> Self;
 }
@@ -69,17 +69,31 @@ impl<L: TransitionLabel> LtsBuilderFast<L> {
     pub fn add_transition<Q>(&mut self, from: StateIndex, label: &Q, to: StateIndex)
     where
         L: Borrow<Q>,
-        Q: ToOwned<Owned = L> + Eq + Hash,
+        Q: ToOwned<Owned = L> + Eq + Hash + ?Sized,
     {
-        let label_index = if let Some(&index) = self.labels_index.get(label) {
+        let label_index = self.add_label(label);
+        self.add_transition_index(from, label_index, to);
+    }
+
+    /// Returns the index of the given label, which is added when it is new.
+    pub fn add_label<Q>(&mut self, label: &Q) -> LabelIndex
+    where
+        L: Borrow<Q>,
+        Q: ToOwned<Owned = L> + Eq + Hash + ?Sized,
+    {
+        if let Some(&index) = self.labels_index.get(label) {
             index
         } else {
             let index = LabelIndex::new(self.labels.len());
             self.labels_index.insert(label.to_owned(), index);
             self.labels.push(label.to_owned());
             index
-        };
+        }
+    }
 
+    /// Adds a transition with a label index obtained from [Self::add_label] to the builder.
+    pub fn add_transition_index(&mut self, from: StateIndex, label_index: LabelIndex, to: StateIndex) {
+        debug_assert!(label_index < self.labels.len(), "The label index should be known");
         self.transitions.push((from, label_index, to));
 
         // Update the number of states.
@@ -0,0 +1,96 @@
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::sync::Arc;
+
+use merc_aterm::ATerm;
+use merc_aterm::ATermList;
+use merc_aterm::ATermSend;
+use merc_aterm::Symbol;
+use merc_aterm::Term;
+use merc_data::DataEquation;
+use merc_data::DataFunctionSymbol;
+use merc_data::DataSpecification;
+use merc_data::DataVariable;
+use merc_data::SortAlias;
+use merc_data::SortExpression;
+
+/// The data specification, process parameters and action declarations of an
+/// LTS in the `.lts` format of the mCRL2 toolset, which are required by the
+/// mCRL2 tools to interpret the multi-actions and state labels.
+///
+/// # Details
+///
+/// Terms cannot be sent between threads, but an LTS can. Therefore, the
+/// specification is stored as a single [ATermSend] that is converted back into
+/// typed terms on demand.
+#[derive(Clone)]
+pub struct LtsSpecification {
+    term: Arc<ATermSend>,
+}
+
+impl LtsSpecification {
+    /// Creates a specification from the given data specification, process parameters and action declarations.
+    pub fn new(
+        data_specification: &DataSpecification,
+        parameters: ATermList<DataVariable>,
+        action_declarations: ATermList<ATerm>,
+    ) -> Self {
+        let args: [ATerm; 7] = [
+            ATermList::<SortExpression>::from_double_iter(data_specification.sorts().iter().cloned()).into(),
+            ATermList::<SortAlias>::from_double_iter(data_specification.aliases().iter().cloned()).into(),
+            ATermList::<DataFunctionSymbol>::from_double_iter(data_specification.constructors().iter().cloned()).into(),
+            ATermList::<DataFunctionSymbol>::from_double_iter(data_specification.mappings().iter().cloned()).into(),
+            ATermList::<DataEquation>::from_double_iter(data_specification.equations().iter().cloned()).into(),
+            parameters.into(),
+            action_declarations.into(),
+        ];
+
+        Self {
+            term: Arc::new(ATermSend::from(
+                ATerm::with_args(&Symbol::new("lts_specification", args.len()), &args).protect(),
+            )),
+        }
+    }
+
+    /// Returns the data specification.
+    pub fn data_specification(&self) -> DataSpecification {
+        DataSpecification::new(
+            self.section(0).to_vec(),
+            self.section(1).to_vec(),
+            self.section(2).to_vec(),
+            self.section(3).to_vec(),
+            self.section(4).to_vec(),
+        )
+    }
+
+    /// Returns the process parameters.
+    pub fn parameters(&self) -> ATermList<DataVariable> {
+        self.section(5)
+    }
+
+    /// Returns the action declarations.
+    pub fn action_declarations(&self) -> ATermList<ATerm> {
+        self.section(6)
+    }
+
+    /// Returns the list stored at the given argument.
+    fn section<T>(&self, index: usize) -> ATermList<T> {
+        self.term.arg(index).into()
+    }
+}
+
+impl PartialEq for LtsSpecification {
+    fn eq(&self, other: &Self) -> bool {
+        // Terms are maximally shared, so equal specifications are the same term.
+        self.term.copy() == other.term.copy()
+    }
+}
+
+impl Eq for LtsSpecification {}
+
+impl fmt::Debug for LtsSpecification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.term.copy())
+    }
+}
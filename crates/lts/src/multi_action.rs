@@ -281,6 +281,10 @@ impl TransitionLabel for MultiAction {
         self.actions.iter().any(|action| action.label == label)
     }
 
+    fn from_string(label: &str) -> Result<Self, MercError> {
+        MultiAction::from_string(label)
+    }
+
     fn from_index(i: usize) -> Self {
         // For now we only generate single actions, but these could become multiactions as well
         MultiAction {
@@ -0,0 +1,556 @@
+#![forbid(unsafe_code)]
+
+use itertools::Itertools;
+use log::trace;
+use rustc_hash::FxHashMap;
+
+use merc_collections::IndexedSet;
+use merc_syntax::Action;
+use merc_syntax::MultiAction;
+use merc_syntax::ProcExprBinaryOp;
+use merc_syntax::ProcessExpr;
+use merc_utilities::MercError;
+
+use crate::LTS;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::LtsBuilderFast;
+use crate::StateIndex;
+use crate::TransitionLabel;
+use crate::multi_action_label;
+use crate::parse_multi_action;
+use crate::transform_actions;
+
+/// Describes how a number of LTSs are composed in parallel, similar to the
+/// `lts.combine` of mCRL2 and the EXP files of CADP.
+///
+/// # Details
+///
+/// The specification is given by an mCRL2 process expression consisting of
+/// the parallel composition of the components, to which the operators
+/// `comm`, `allow`, `hide` and `rename` are applied in that order. Each of
+/// these operators is optional, for example
+///
+/// ```plain
+/// hide({c}, allow({c, d}, comm({a|b -> c}, P0 || P1 || P2)))
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompositionSpecification {
+    /// The names of the components, in the order of the parallel composition.
+    pub components: Vec<String>,
+
+    /// The communications, where the sorted action names on the left-hand side result in the action on the right-hand side.
+    pub communications: Vec<(Vec<String>, String)>,
+
+    /// The sorted action names of the multi-actions that are allowed after communication, or `None` when all are allowed.
+    pub allow: Option<Vec<Vec<String>>>,
+
+    /// The actions that are hidden after the allow operator.
+    pub hide: Vec<String>,
+
+    /// The actions that are renamed at the end.
+    pub renames: Vec<(String, String)>,
+}
+
+impl CompositionSpecification {
+    /// Parses a composition specification from an mCRL2 process expression.
+    pub fn parse(spec: &str) -> Result<CompositionSpecification, MercError> {
+        let mut result = CompositionSpecification::default();
+
+        // The operators are peeled off from the outside, so every operator must come before the previous one.
+        let mut expr = &ProcessExpr::parse(spec)?;
+        let mut last_operator = 4;
+        loop {
+            let (operator, operand) = match expr {
+                ProcessExpr::Rename { renames, operand } => {
+                    result.renames = renames
+                        .iter()
+                        .map(|rename| (rename.from.clone(), rename.to.clone()))
+                        .collect();
+                    (3, operand)
+                }
+                ProcessExpr::Hide { actions, operand } => {
+                    result.hide = actions.clone();
+                    (2, operand)
+                }
+                ProcessExpr::Allow { actions, operand } => {
+                    result.allow = Some(
+                        actions
+                            .iter()
+                            .map(|multi_action| multi_action.actions.iter().cloned().sorted_unstable().collect())
+                            .collect(),
+                    );
+                    (1, operand)
+                }
+                ProcessExpr::Comm { comm, operand } => {
+                    result.communications = comm
+                        .iter()
+                        .map(|comm| {
+                            (
+                                comm.from.actions.iter().cloned().sorted_unstable().collect(),
+                                comm.to.clone(),
+                            )
+                        })
+                        .collect();
+                    (0, operand)
+                }
+                _ => break,
+            };
+
+            if operator >= last_operator {
+                return Err("The operators of a composition must be applied in the order comm, allow, hide and rename, each at most once.".into());
+            }
+
+            last_operator = operator;
+            expr = operand;
+        }
+
+        collect_components(expr, &mut result.components)?;
+        Ok(result)
+    }
+
+    /// Returns the sorted action names of the multi-actions in which actions of
+    /// different components can take part together, which are the allowed
+    /// multi-actions before communication or the left-hand sides of the
+    /// communications when there is no allow set.
+    pub fn synchronisations(&self) -> Vec<Vec<String>> {
+        match allowed_before_communication(self) {
+            Some(allowed) => allowed.into_iter().filter(|names| names.len() > 1).collect(),
+            None => self.communications.iter().map(|(from, _)| from.clone()).collect(),
+        }
+    }
+}
+
+/// Adds the names of the components in the parallel composition given by the expression.
+fn collect_components(expr: &ProcessExpr, components: &mut Vec<String>) -> Result<(), MercError> {
+    match expr {
+        ProcessExpr::Binary {
+            op: ProcExprBinaryOp::Parallel,
+            lhs,
+            rhs,
+        } => {
+            collect_components(lhs, components)?;
+            collect_components(rhs, components)
+        }
+        ProcessExpr::Action(name, arguments) if arguments.is_empty() => {
+            components.push(name.clone());
+            Ok(())
+        }
+        ProcessExpr::Id(name, assignments) if assignments.is_empty() => {
+            components.push(name.clone());
+            Ok(())
+        }
+        _ => Err(format!("Expected a parallel composition of component names, but found {expr:?}.").into()),
+    }
+}
+
+/// Computes the parallel composition of two LTSs, where both LTSs can take a
+/// step independently or simultaneously with the combined multi-action, as in
+/// the parallel operator of mCRL2.
+///
+/// # Details
+///
+/// The multi-actions that can never become allowed by the specification,
+/// after adding the actions of other components and applying communication,
+/// are removed immediately. The other operators of the specification are not
+/// applied, see [apply_composition_operators], such that the result can be
+/// composed with further components. Hidden transitions are never taken
+/// simultaneously, and only the reachable part of the product is constructed.
+pub fn parallel_lts<L: TransitionLabel>(
+    left: &impl LTS<Label = L>,
+    right: &impl LTS<Label = L>,
+    specification: &CompositionSpecification,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let allowed = allowed_before_communication(specification);
+    let mut builder = LtsBuilderFast::new(Vec::new(), Vec::new());
+
+    let left_actions: Vec<MultiAction> = left.labels().iter().map(parse_multi_action).collect();
+    let right_actions: Vec<MultiAction> = right.labels().iter().map(parse_multi_action).collect();
+    let left_independent = independent_labels(left, &left_actions, &allowed, &mut builder);
+    let right_independent = independent_labels(right, &right_actions, &allowed, &mut builder);
+
+    // The label of the simultaneous transitions for every pair of labels, if it can become allowed.
+    let mut simultaneous_labels: FxHashMap<(LabelIndex, LabelIndex), Option<LabelIndex>> = FxHashMap::default();
+
+    let mut discovered_states: IndexedSet<(StateIndex, StateIndex)> = IndexedSet::new();
+    let mut working = vec![(left.initial_state_index(), right.initial_state_index())];
+    discovered_states.insert((left.initial_state_index(), right.initial_state_index()));
+
+    while let Some((left_state, right_state)) = working.pop() {
+        let (product_index, _) = discovered_states.insert((left_state, right_state));
+        let product_state = StateIndex::new(*product_index);
+        trace!("Considering ({left_state}, {right_state})");
+
+        // The labels and target states of the outgoing transitions of the product state.
+        let mut successors: Vec<(LabelIndex, (StateIndex, StateIndex))> = Vec::new();
+
+        for left_transition in left.outgoing_transitions(left_state) {
+            if let Some(label_index) = left_independent[left_transition.label] {
+                successors.push((label_index, (left_transition.to, right_state)));
+            }
+        }
+
+        for right_transition in right.outgoing_transitions(right_state) {
+            if let Some(label_index) = right_independent[right_transition.label] {
+                successors.push((label_index, (left_state, right_transition.to)));
+            }
+        }
+
+        for left_transition in left.outgoing_transitions(left_state) {
+            if left.is_hidden_label(left_transition.label) {
+                continue;
+            }
+
+            for right_transition in right.outgoing_transitions(right_state) {
+                if right.is_hidden_label(right_transition.label) {
+                    continue;
+                }
+
+                let key = (left_transition.label, right_transition.label);
+                let label_index = match simultaneous_labels.get(&key) {
+                    Some(label_index) => *label_index,
+                    None => {
+                        let multi_action = MultiAction {
+                            actions: left_actions[left_transition.label]
+                                .actions
+                                .iter()
+                                .chain(&right_actions[right_transition.label].actions)
+                                .cloned()
+                                .collect(),
+                        };
+
+                        let label_index = if is_viable(&allowed, &multi_action) {
+                            Some(builder.add_label(&multi_action_label::<L>(multi_action)?))
+                        } else {
+                            None
+                        };
+
+                        simultaneous_labels.insert(key, label_index);
+                        label_index
+                    }
+                };
+
+                if let Some(label_index) = label_index {
+                    successors.push((label_index, (left_transition.to, right_transition.to)));
+                }
+            }
+        }
+
+        for (label_index, to) in successors {
+            let (to_index, inserted) = discovered_states.insert(to);
+            builder.add_transition_index(product_state, label_index, StateIndex::new(*to_index));
+
+            if inserted {
+                working.push(to);
+            }
+        }
+    }
+
+    builder.require_num_of_states(discovered_states.len());
+    Ok(builder.finish(StateIndex::new(0), true))
+}
+
+/// Returns the label in the product of every transition that is taken independently, if it can become allowed.
+fn independent_labels<L: TransitionLabel>(
+    lts: &impl LTS<Label = L>,
+    actions: &[MultiAction],
+    allowed: &Option<Vec<Vec<String>>>,
+    builder: &mut LtsBuilderFast<L>,
+) -> Vec<Option<LabelIndex>> {
+    lts.labels()
+        .iter()
+        .enumerate()
+        .map(|(label_index, label)| {
+            if lts.is_hidden_label(LabelIndex::new(label_index)) {
+                Some(builder.add_label(&L::tau_label()))
+            } else if is_viable(allowed, &actions[label_index]) {
+                Some(builder.add_label(label))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Applies the communication, allow, hide and rename operators of the
+/// specification to the result of the parallel composition of all components.
+pub fn apply_composition_operators<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    specification: &CompositionSpecification,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    transform_actions(lts, |multi_action| Ok(apply_operators(multi_action, specification)))
+}
+
+/// Hides and removes the transitions of which the multi-action can no longer
+/// be taken simultaneously with an action of the remaining components, when
+/// these would eventually be hidden or blocked by the operators of the
+/// specification.
+///
+/// # Details
+///
+/// This is used to hide actions as early as possible during compositional
+/// minimisation. Without an allow set every multi-action can still be extended
+/// by the remaining components, so then actions are only hidden when there are
+/// no remaining components.
+pub fn hide_closed_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    remaining: &[LabelledTransitionSystem<L>],
+    specification: &CompositionSpecification,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let allowed = allowed_before_communication(specification);
+    let remaining_actions: Vec<MultiAction> = remaining
+        .iter()
+        .flat_map(|component| {
+            component
+                .labels()
+                .iter()
+                .enumerate()
+                .filter(|(label_index, _)| !component.is_hidden_label(LabelIndex::new(*label_index)))
+                .map(|(_, label)| parse_multi_action(label))
+        })
+        .collect();
+
+    transform_actions(lts, |multi_action| {
+        let closed = if allowed.is_some() {
+            remaining_actions.iter().all(|other| {
+                let combined = MultiAction {
+                    actions: multi_action.actions.iter().chain(&other.actions).cloned().collect(),
+                };
+                !is_viable(&allowed, &combined)
+            })
+        } else {
+            remaining.is_empty()
+        };
+
+        if !closed {
+            return Ok(Some(multi_action.clone()));
+        }
+
+        // A visible result must be kept as is, since it could otherwise be renamed twice.
+        Ok(match apply_operators(multi_action, specification) {
+            None => None,
+            Some(result) if result.actions.is_empty() => Some(result),
+            Some(_) => Some(multi_action.clone()),
+        })
+    })
+}
+
+/// Applies the communication, allow, hide and rename operators to a single
+/// multi-action, and returns `None` when it is not allowed.
+fn apply_operators(multi_action: &MultiAction, specification: &CompositionSpecification) -> Option<MultiAction> {
+    let mut actions = multi_action.actions.clone();
+    for (from, to) in &specification.communications {
+        while communicate(&mut actions, from, to) {}
+    }
+
+    if let Some(allow) = &specification.allow {
+        let names: Vec<&str> = actions
+            .iter()
+            .map(|action| action.id.as_str())
+            .sorted_unstable()
+            .collect();
+        if !names.is_empty() && !allow.iter().any(|allowed| allowed.iter().eq(names.iter())) {
+            return None;
+        }
+    }
+
+    actions.retain(|action| !specification.hide.contains(&action.id));
+    for action in &mut actions {
+        if let Some((_, to)) = specification.renames.iter().find(|(from, _)| *from == action.id) {
+            action.id = to.clone();
+        }
+    }
+
+    Some(MultiAction { actions })
+}
+
+/// Replaces actions with the given names and equal arguments by a single
+/// action with the resulting name, and returns true iff this was possible.
+fn communicate(actions: &mut Vec<Action>, from: &[String], to: &str) -> bool {
+    for candidate in 0..actions.len() {
+        if actions[candidate].id != from[0] {
+            continue;
+        }
+
+        // Find a distinct action for every name, with the same arguments as the candidate.
+        let mut used: Vec<usize> = Vec::with_capacity(from.len());
+        for name in from {
+            if let Some(index) = (0..actions.len()).find(|index| {
+                !used.contains(index) && actions[*index].id == *name && actions[*index].args == actions[candidate].args
+            }) {
+                used.push(index);
+            }
+        }
+
+        if used.len() == from.len() {
+            let result = Action {
+                id: to.to_string(),
+                args: actions[candidate].args.clone(),
+            };
+
+            used.sort_unstable();
+            for index in used.into_iter().rev() {
+                actions.remove(index);
+            }
+            actions.push(result);
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns the sorted action names of the multi-actions before communication
+/// that are allowed after communication, or `None` when all are allowed.
+fn allowed_before_communication(specification: &CompositionSpecification) -> Option<Vec<Vec<String>>> {
+    let allow = specification.allow.as_ref()?;
+
+    // Every allowed action is either present, or is the result of a communication.
+    let mut result = Vec::new();
+    for allowed in allow {
+        let alternatives = allowed.iter().map(|name| {
+            let mut alternatives = vec![vec![name.clone()]];
+            alternatives.extend(
+                specification
+                    .communications
+                    .iter()
+                    .filter(|(_, to)| to == name)
+                    .map(|(from, _)| from.clone()),
+            );
+            alternatives
+        });
+
+        for choice in alternatives.multi_cartesian_product() {
+            result.push(choice.into_iter().flatten().sorted_unstable().collect());
+        }
+    }
+
+    Some(result)
+}
+
+/// Returns true iff the multi-action can become allowed by adding actions of other components.
+fn is_viable(allowed: &Option<Vec<Vec<String>>>, multi_action: &MultiAction) -> bool {
+    let Some(allowed) = allowed else {
+        return true;
+    };
+
+    let names: Vec<&str> = multi_action
+        .actions
+        .iter()
+        .map(|action| action.id.as_str())
+        .sorted_unstable()
+        .collect();
+
+    allowed.iter().any(|allowed| is_submultiset(&names, allowed))
+}
+
+/// Returns true iff the sorted names are contained in the sorted multi-set of names.
+fn is_submultiset(names: &[&str], multiset: &[String]) -> bool {
+    let mut remaining = multiset.iter();
+    names
+        .iter()
+        .all(|name| remaining.by_ref().any(|element| element == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::read_aut;
+    use crate::remove_unreachable_states;
+
+    /// Returns the transitions of the given LTS as strings, sorted.
+    fn transitions<L: LTS>(lts: &L) -> Vec<String> {
+        lts.iter_states()
+            .flat_map(|state_index| {
+                lts.outgoing_transitions(state_index).map(move |transition| {
+                    format!("{state_index} {} {}", lts.labels()[transition.label], transition.to)
+                })
+            })
+            .sorted()
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_composition_specification() {
+        let specification = CompositionSpecification::parse(
+            "rename({c -> e}, hide({d}, allow({c, d|a}, comm({b|a -> c}, P || Q || R))))",
+        )
+        .unwrap();
+
+        assert_eq!(specification.components, vec!["P", "Q", "R"]);
+        assert_eq!(
+            specification.communications,
+            vec![(vec!["a".to_string(), "b".to_string()], "c".to_string())]
+        );
+        assert_eq!(
+            specification.allow,
+            Some(vec![vec!["c".to_string()], vec!["a".to_string(), "d".to_string()]])
+        );
+        assert_eq!(specification.hide, vec!["d"]);
+        assert_eq!(specification.renames, vec![("c".to_string(), "e".to_string())]);
+
+        assert!(CompositionSpecification::parse("comm({a|b -> c}, allow({c}, P || Q))").is_err());
+        assert!(CompositionSpecification::parse("P || a . Q").is_err());
+    }
+
+    #[test]
+    fn test_parallel_lts() {
+        let left = read_aut(b"des (0,3,2)\n(0,a,1)\n(1,i,0)\n(0,x,0)\n" as &[u8], Vec::new()).unwrap();
+        let right = read_aut(b"des (0,1,2)\n(0,b,1)\n" as &[u8], Vec::new()).unwrap();
+
+        // Without an allow set both LTSs can take steps independently and simultaneously.
+        let result = parallel_lts(&left, &right, &CompositionSpecification::default()).unwrap();
+        assert_eq!(result.num_of_states(), 4);
+        assert_eq!(result.num_of_transitions(), 10);
+
+        // The action x can never be allowed, but a and b can still communicate with other components.
+        let specification = CompositionSpecification::parse("allow({c}, comm({a|b -> c}, P || Q))").unwrap();
+        let result = parallel_lts(&left, &right, &specification).unwrap();
+        assert!(result.labels().iter().all(|label| !label.contains('x')));
+        assert_eq!(result.num_of_transitions(), 7);
+
+        let result = remove_unreachable_states(apply_composition_operators(result, &specification).unwrap());
+        assert_eq!(transitions(&result), vec!["0 c 2", "2 i 1"]);
+    }
+
+    #[test]
+    fn test_apply_composition_operators() {
+        let lts = read_aut(
+            b"des (0,4,2)\n(0,\"a(1)|b(1)\",1)\n(0,\"a(1)|b(2)\",1)\n(1,\"a(2)|b(2)|d\",0)\n(1,d,0)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let specification =
+            CompositionSpecification::parse("rename({c -> e}, hide({d}, allow({c, c|d}, comm({a|b -> c}, P || Q))))")
+                .unwrap();
+        let result = apply_composition_operators(lts, &specification).unwrap();
+
+        // The actions with different arguments do not communicate, and d alone is not allowed.
+        assert_eq!(transitions(&result), vec!["0 e(1) 1", "1 e(2) 0"]);
+    }
+
+    #[test]
+    fn test_hide_closed_actions() {
+        let lts = read_aut(
+            b"des (0,4,2)\n(0,x,1)\n(0,a,1)\n(1,d,0)\n(1,y,0)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+        let remaining = vec![read_aut(b"des (0,1,2)\n(0,b,1)\n" as &[u8], Vec::new()).unwrap()];
+
+        let specification =
+            CompositionSpecification::parse("hide({c, x}, allow({c, d, x}, comm({a|b -> c}, P || Q)))").unwrap();
+        assert_eq!(
+            specification.synchronisations(),
+            vec![vec!["a".to_string(), "b".to_string()]]
+        );
+
+        // Only a can still communicate with b, and d is visible in the end.
+        let result = hide_closed_actions(lts, &remaining, &specification).unwrap();
+        assert_eq!(transitions(&result), vec!["0 a 1", "0 i 1", "1 d 0"]);
+    }
+}
@@ -0,0 +1,359 @@
+#![forbid(unsafe_code)]
+
+//! Labelled transition systems where the target of every transition is a
+//! probability distribution over states.
+
+use std::fmt;
+
+use itertools::Itertools;
+use num::BigRational;
+use num::One;
+use num::Zero;
+
+use merc_utilities::TagIndex;
+
+use crate::LabelIndex;
+use crate::LtsSpecification;
+use crate::StateIndex;
+use crate::TransitionLabel;
+
+/// The probabilities are exact fractions, such that equal probabilities can be compared reliably.
+pub type Probability = BigRational;
+
+/// A unique type for the distributions.
+pub struct DistributionTag;
+
+/// The index for a distribution.
+pub type DistributionIndex = TagIndex<usize, DistributionTag>;
+
+/// A probability distribution over states, where every state has a positive
+/// probability and the probabilities add up to one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Distribution {
+    /// The states with their probabilities, sorted by state index.
+    entries: Vec<(StateIndex, Probability)>,
+}
+
+impl Distribution {
+    /// Creates a distribution from the given states and probabilities, where
+    /// the probabilities of duplicate states are added up and states with
+    /// probability zero are removed.
+    pub fn new(mut entries: Vec<(StateIndex, Probability)>) -> Self {
+        entries.sort_unstable_by_key(|(state_index, _)| *state_index);
+
+        let mut result: Vec<(StateIndex, Probability)> = Vec::with_capacity(entries.len());
+        for (state_index, probability) in entries {
+            match result.last_mut() {
+                Some((last_index, last_probability)) if *last_index == state_index => *last_probability += probability,
+                _ => result.push((state_index, probability)),
+            }
+        }
+        result.retain(|(_, probability)| !probability.is_zero());
+
+        debug_assert!(
+            result.iter().map(|(_, probability)| probability).sum::<Probability>() == Probability::one(),
+            "The probabilities of a distribution should add up to one"
+        );
+
+        Self { entries: result }
+    }
+
+    /// Returns the distribution that assigns probability one to the given state.
+    pub fn dirac(state_index: StateIndex) -> Self {
+        Self {
+            entries: vec![(state_index, Probability::one())],
+        }
+    }
+
+    /// Returns the state if the distribution assigns probability one to a single state.
+    pub fn as_state(&self) -> Option<StateIndex> {
+        if let [(state_index, _)] = self.entries.as_slice() {
+            Some(*state_index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the distribution where every state is replaced by the given
+    /// state, for example its block in a partition.
+    pub fn map(&self, f: impl Fn(StateIndex) -> StateIndex) -> Self {
+        Self::new(
+            self.entries
+                .iter()
+                .map(|(state_index, probability)| (f(*state_index), probability.clone()))
+                .collect(),
+        )
+    }
+
+    /// Returns the states with a positive probability together with their probability.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &(StateIndex, Probability)> + '_ {
+        self.entries.iter()
+    }
+
+    /// Returns the number of states with a positive probability.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true iff the distribution has no states, which is only the case for the default value.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(state_index) = self.as_state() {
+            write!(f, "{state_index}")
+        } else {
+            write!(
+                f,
+                "{{{}}}",
+                self.entries
+                    .iter()
+                    .format_with(", ", |(state_index, probability), f| f(&format_args!(
+                        "{state_index}: {probability}"
+                    )))
+            )
+        }
+    }
+}
+
+/// A transition with a label to a probability distribution over states.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProbabilisticTransition {
+    pub label: LabelIndex,
+    pub to: DistributionIndex,
+}
+
+/// Represents a probabilistic labelled transition system, where every
+/// transition leads to a probability distribution over states, and the initial
+/// state is also given by a distribution.
+///
+/// # Details
+///
+/// Equal distributions are only stored once, and the transitions refer to them
+/// by their index.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProbabilisticLts<Label> {
+    /// The offsets of the outgoing transitions of every state, followed by a sentinel.
+    states: Vec<usize>,
+    transitions: Vec<ProbabilisticTransition>,
+
+    /// The labels for every index, where the hidden label has index zero.
+    labels: Vec<Label>,
+
+    /// The distinct distributions that are used by the transitions and the initial distribution.
+    distributions: Vec<Distribution>,
+    initial_distribution: DistributionIndex,
+
+    /// The optional specification from which the LTS was generated.
+    specification: Option<LtsSpecification>,
+}
+
+impl<Label: TransitionLabel> ProbabilisticLts<Label> {
+    /// Creates a new probabilistic LTS with the given number of states from
+    /// the transitions, which must be sorted by their source state.
+    ///
+    /// # Details
+    ///
+    /// The first label must be the hidden label, and all states of the
+    /// distributions must be smaller than the number of states.
+    pub fn new(
+        num_of_states: usize,
+        transitions: impl IntoIterator<Item = (StateIndex, ProbabilisticTransition)>,
+        labels: Vec<Label>,
+        distributions: Vec<Distribution>,
+        initial_distribution: DistributionIndex,
+    ) -> Self {
+        assert!(
+            labels.first().is_some_and(|label| label.is_tau_label()),
+            "The first label must be the hidden label."
+        );
+        debug_assert!(
+            distributions
+                .iter()
+                .all(|distribution| distribution.iter().all(|(state_index, _)| *state_index < num_of_states)),
+            "Every state of a distribution should exist"
+        );
+
+        let mut states = Vec::with_capacity(num_of_states + 1);
+        let mut result = Vec::new();
+        for (from, transition) in transitions {
+            assert!(
+                states.len() <= from.value() + 1,
+                "The transitions should be sorted by source"
+            );
+            while states.len() <= from.value() {
+                states.push(result.len());
+            }
+
+            debug_assert!(transition.to < distributions.len(), "The distribution should exist");
+            result.push(transition);
+        }
+
+        // Add the states without outgoing transitions, and the sentinel state.
+        states.resize(num_of_states + 1, result.len());
+
+        Self {
+            states,
+            transitions: result,
+            labels,
+            distributions,
+            initial_distribution,
+            specification: None,
+        }
+    }
+
+    /// Returns the index of the initial distribution.
+    pub fn initial_distribution(&self) -> DistributionIndex {
+        self.initial_distribution
+    }
+
+    /// Returns the outgoing transitions of the given state.
+    pub fn outgoing_transitions(&self, state_index: StateIndex) -> impl Iterator<Item = &ProbabilisticTransition> + '_ {
+        self.transitions[self.states[state_index.value()]..self.states[state_index.value() + 1]].iter()
+    }
+
+    /// Returns the distribution with the given index.
+    pub fn distribution(&self, distribution_index: DistributionIndex) -> &Distribution {
+        &self.distributions[distribution_index]
+    }
+
+    /// Iterates over all states.
+    pub fn iter_states(&self) -> impl Iterator<Item = StateIndex> + '_ {
+        (0..self.num_of_states()).map(StateIndex::new)
+    }
+
+    /// Returns the number of states.
+    pub fn num_of_states(&self) -> usize {
+        // Remove the sentinel state.
+        self.states.len() - 1
+    }
+
+    /// Returns the number of transitions.
+    pub fn num_of_transitions(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Returns the number of distinct distributions.
+    pub fn num_of_distributions(&self) -> usize {
+        self.distributions.len()
+    }
+
+    /// Returns the number of labels.
+    pub fn num_of_labels(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns the labels, where the hidden label has index zero.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// Returns true iff the given label index is the hidden label.
+    pub fn is_hidden_label(&self, label_index: LabelIndex) -> bool {
+        label_index.value() == 0
+    }
+
+    /// Returns the specification that the LTS was generated from, if this is known.
+    pub fn specification(&self) -> Option<&LtsSpecification> {
+        self.specification.as_ref()
+    }
+
+    /// Consumes the LTS and sets the specification from which it was generated.
+    pub fn with_specification(mut self, specification: LtsSpecification) -> Self {
+        self.specification = Some(specification);
+        self
+    }
+
+    /// Consumes the LTS and relabels its transition labels according to the given mapping.
+    pub fn relabel<L: TransitionLabel>(self, labelling: impl Fn(Label) -> L) -> ProbabilisticLts<L> {
+        ProbabilisticLts {
+            states: self.states,
+            transitions: self.transitions,
+            labels: self.labels.into_iter().map(labelling).collect(),
+            distributions: self.distributions,
+            initial_distribution: self.initial_distribution,
+            specification: self.specification,
+        }
+    }
+}
+
+impl<Label: TransitionLabel> fmt::Debug for ProbabilisticLts<Label> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Initial distribution: {}",
+            self.distribution(self.initial_distribution)
+        )?;
+        for state_index in self.iter_states() {
+            for transition in self.outgoing_transitions(state_index) {
+                writeln!(
+                    f,
+                    "{state_index} --[{}]-> {}",
+                    self.labels[transition.label],
+                    self.distribution(transition.to)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that two probabilistic LTSs are equivalent modulo the label indices, for testing purposes.
+#[cfg(test)]
+pub fn check_equivalent_probabilistic<L: TransitionLabel>(lts: &ProbabilisticLts<L>, lts_read: &ProbabilisticLts<L>) {
+    assert_eq!(lts.num_of_states(), lts_read.num_of_states());
+    assert_eq!(lts.num_of_transitions(), lts_read.num_of_transitions());
+    assert_eq!(
+        lts.distribution(lts.initial_distribution()),
+        lts_read.distribution(lts_read.initial_distribution())
+    );
+
+    for state_index in lts.iter_states() {
+        let transitions = |lts: &ProbabilisticLts<L>| -> Vec<(L, Distribution)> {
+            lts.outgoing_transitions(state_index)
+                .map(|transition| {
+                    (
+                        lts.labels()[transition.label].clone(),
+                        lts.distribution(transition.to).clone(),
+                    )
+                })
+                .sorted()
+                .collect()
+        };
+
+        assert_eq!(
+            transitions(lts),
+            transitions(lts_read),
+            "The outgoing transitions of state {state_index} differ"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_new() {
+        let half = Probability::new(1.into(), 2.into());
+        let quarter = Probability::new(1.into(), 4.into());
+
+        let distribution = Distribution::new(vec![
+            (StateIndex::new(2), quarter.clone()),
+            (StateIndex::new(1), half.clone()),
+            (StateIndex::new(2), quarter.clone()),
+            (StateIndex::new(3), Probability::zero()),
+        ]);
+
+        assert_eq!(distribution.len(), 2);
+        assert_eq!(distribution.to_string(), "{1: 1/2, 2: 1/2}");
+        assert_eq!(
+            distribution.map(|_| StateIndex::new(0)),
+            Distribution::dirac(StateIndex::new(0))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+use crate::Distribution;
+use crate::DistributionIndex;
+use crate::LabelIndex;
+use crate::ProbabilisticLts;
+use crate::ProbabilisticTransition;
+use crate::StateIndex;
+use crate::TransitionLabel;
+
+/// This struct helps in building a probabilistic labelled transition system
+/// by accumulating transitions, similar to [crate::LtsBuilder].
+///
+/// # Details
+///
+/// Equal distributions are assigned the same [DistributionIndex], and
+/// duplicate transitions are removed when the LTS is constructed.
+pub struct ProbabilisticLtsBuilder<L> {
+    transitions: Vec<(StateIndex, ProbabilisticTransition)>,
+
+    // This is used to keep track of the label to index mapping.
+    labels_index: HashMap<L, LabelIndex>,
+    labels: Vec<L>,
+
+    // This is used to keep track of the distribution to index mapping.
+    distributions_index: FxHashMap<Distribution, DistributionIndex>,
+    distributions: Vec<Distribution>,
+
+    /// The number of states (derived from the transitions).
+    num_of_states: usize,
+}
+
+impl<L: TransitionLabel> ProbabilisticLtsBuilder<L> {
+    /// Initializes a new empty builder, where the hidden labels are mapped to the tau label.
+    pub fn new(mut labels: Vec<L>, hidden_labels: Vec<String>) -> Self {
+        // Remove duplicates from the labels.
+        labels.sort();
+        labels.dedup();
+
+        // Introduce the fixed 0-indexed tau label.
+        if let Some(tau_pos) = labels.iter().position(|l| l.is_tau_label()) {
+            labels.swap(0, tau_pos);
+        } else {
+            labels.insert(0, L::tau_label());
+        }
+
+        // Ensure that all hidden labels are mapped to the tau action.
+        let mut labels_index = HashMap::new();
+        labels_index.insert(L::tau_label(), LabelIndex::new(0));
+        for (index, label) in labels.iter().enumerate() {
+            if hidden_labels.iter().any(|l| label.matches_label(l)) {
+                labels_index.insert(label.clone(), LabelIndex::new(0));
+            } else {
+                labels_index.insert(label.clone(), LabelIndex::new(index));
+            }
+        }
+
+        Self {
+            transitions: Vec::new(),
+            labels_index,
+            labels,
+            distributions_index: FxHashMap::default(),
+            distributions: Vec::new(),
+            num_of_states: 0,
+        }
+    }
+
+    /// Adds a transition to the given distribution to the builder.
+    pub fn add_transition<Q>(&mut self, from: StateIndex, label: &Q, to: Distribution)
+    where
+        L: Borrow<Q>,
+        Q: ?Sized + ToOwned<Owned = L> + Eq + Hash,
+    {
+        let label_index = if let Some(&index) = self.labels_index.get(label) {
+            index
+        } else {
+            let index = LabelIndex::new(self.labels.len());
+            self.labels_index.insert(label.to_owned(), index);
+            self.labels.push(label.to_owned());
+            index
+        };
+
+        self.num_of_states = self.num_of_states.max(from.value() + 1);
+        let to = self.add_distribution(to);
+        self.transitions
+            .push((from, ProbabilisticTransition { label: label_index, to }));
+    }
+
+    /// Returns the index of the given distribution, which is added when it is new.
+    pub fn add_distribution(&mut self, distribution: Distribution) -> DistributionIndex {
+        if let Some(&index) = self.distributions_index.get(&distribution) {
+            return index;
+        }
+
+        if let Some((max_state, _)) = distribution.iter().last() {
+            self.num_of_states = self.num_of_states.max(max_state.value() + 1);
+        }
+
+        let index = DistributionIndex::new(self.distributions.len());
+        self.distributions_index.insert(distribution.clone(), index);
+        self.distributions.push(distribution);
+        index
+    }
+
+    /// Returns the number of transitions added to the builder.
+    pub fn num_of_transitions(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Returns the number of states added to the builder.
+    pub fn num_of_states(&self) -> usize {
+        self.num_of_states
+    }
+
+    /// Ensures that the builder has at least the given number of states.
+    pub fn require_num_of_states(&mut self, num_of_states: usize) {
+        self.num_of_states = self.num_of_states.max(num_of_states);
+    }
+
+    /// Finalizes the builder and returns the constructed probabilistic LTS.
+    pub fn finish(mut self, initial_distribution: Distribution) -> ProbabilisticLts<L> {
+        let initial_distribution = self.add_distribution(initial_distribution);
+
+        self.transitions.sort_unstable();
+        self.transitions.dedup();
+
+        ProbabilisticLts::new(
+            self.num_of_states,
+            self.transitions,
+            self.labels,
+            self.distributions,
+            initial_distribution,
+        )
+    }
+}
@@ -2,8 +2,12 @@
 
 use rand::Rng;
 
+use crate::Distribution;
 use crate::LabelledTransitionSystem;
 use crate::LtsBuilderFast;
+use crate::ProbabilisticLts;
+use crate::ProbabilisticLtsBuilder;
+use crate::Probability;
 use crate::StateIndex;
 use crate::TransitionLabel;
 use crate::product_lts;
@@ -83,6 +87,58 @@ pub fn random_lts_monolithic<L: TransitionLabel>(
     builder.finish(StateIndex::new(0), true)
 }
 
+/// Generates a probabilistic LTS with the desired number of states, labels and
+/// out degree, where the target of every transition is a distribution over at
+/// most the given number of states with random fractions as probabilities.
+pub fn random_probabilistic_lts<L: TransitionLabel>(
+    rng: &mut impl Rng,
+    num_of_states: usize,
+    num_of_labels: u32,
+    outdegree: usize,
+    support: usize,
+) -> ProbabilisticLts<L> {
+    let labels: Vec<L> = (0..(num_of_labels - 1)).map(|i| L::from_index(i as usize)).collect();
+
+    let mut builder = ProbabilisticLtsBuilder::new(labels.clone(), Vec::new());
+    builder.require_num_of_states(num_of_states.max(1));
+
+    for state_index in 0..num_of_states {
+        for _ in 0..rng.random_range(0..outdegree) {
+            // The last label is the hidden label.
+            let label = rng.random_range(0..num_of_labels as usize);
+            let distribution = random_distribution(rng, num_of_states, support);
+
+            if let Some(label) = labels.get(label) {
+                builder.add_transition(StateIndex::new(state_index), label, distribution);
+            } else {
+                builder.add_transition(StateIndex::new(state_index), &L::tau_label(), distribution);
+            }
+        }
+    }
+
+    builder.finish(Distribution::dirac(StateIndex::new(0)))
+}
+
+/// Returns a distribution over at most the given number of random states, where
+/// the probabilities are multiples of a random fraction.
+fn random_distribution(rng: &mut impl Rng, num_of_states: usize, support: usize) -> Distribution {
+    let size = rng.random_range(1..=support.max(1));
+    let weights: Vec<u64> = (0..size).map(|_| rng.random_range(1..4)).collect();
+    let total: u64 = weights.iter().sum();
+
+    Distribution::new(
+        weights
+            .into_iter()
+            .map(|weight| {
+                (
+                    StateIndex::new(rng.random_range(0..num_of_states.max(1))),
+                    Probability::new(weight.into(), total.into()),
+                )
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
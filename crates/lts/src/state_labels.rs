@@ -0,0 +1,142 @@
+#![forbid(unsafe_code)]
+
+use std::fmt;
+
+use itertools::Itertools;
+
+use crate::StateIndex;
+
+/// Assigns the values of the process parameters to every state of an LTS, such
+/// that the states can be traced back to the states of the original process.
+///
+/// # Details
+///
+/// Every state is labelled by a list of value vectors, where every vector
+/// contains one value for each parameter. A state read from a file has a
+/// single value vector, but a state of a quotient LTS has the value vectors of
+/// all the states that it represents.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateLabels {
+    /// The names and sorts of the process parameters.
+    parameters: Vec<(String, String)>,
+
+    /// The value vectors for every state.
+    values: Vec<Vec<Vec<String>>>,
+}
+
+impl StateLabels {
+    /// Creates state labels for the given parameters, consisting of pairs of
+    /// parameter names and sorts, without any states.
+    pub fn new(parameters: Vec<(String, String)>) -> Self {
+        Self {
+            parameters,
+            values: Vec::new(),
+        }
+    }
+
+    /// Adds the label of the next state, consisting of value vectors that each
+    /// contain one value for every parameter.
+    pub fn push(&mut self, values: Vec<Vec<String>>) {
+        debug_assert!(
+            values.iter().all(|vector| vector.len() == self.parameters.len()),
+            "Every value vector should contain a value for every parameter"
+        );
+
+        self.values.push(values);
+    }
+
+    /// Returns the names and sorts of the process parameters.
+    pub fn parameters(&self) -> &[(String, String)] {
+        &self.parameters
+    }
+
+    /// Returns the value vectors of the given state.
+    pub fn values(&self, state_index: StateIndex) -> &[Vec<String>] {
+        &self.values[state_index]
+    }
+
+    /// Returns the number of labelled states.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true iff there are no labelled states.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the state labels where every state is moved to the index given by the permutation.
+    pub fn permute(self, permutation: impl Fn(StateIndex) -> StateIndex) -> Self {
+        let mut values = vec![Vec::new(); self.values.len()];
+        for (state_index, state_values) in self.values.into_iter().enumerate() {
+            values[permutation(StateIndex::new(state_index))] = state_values;
+        }
+
+        Self {
+            parameters: self.parameters,
+            values,
+        }
+    }
+
+    /// Returns the state labels of a quotient with the given number of states,
+    /// where every state is mapped to the given quotient state. The label of a
+    /// quotient state consists of the (unique) value vectors of all its states.
+    pub fn merge(&self, num_of_states: usize, quotient: impl Fn(StateIndex) -> StateIndex) -> Self {
+        let mut values: Vec<Vec<Vec<String>>> = vec![Vec::new(); num_of_states];
+        for (state_index, state_values) in self.values.iter().enumerate() {
+            values[quotient(StateIndex::new(state_index))].extend(state_values.iter().cloned());
+        }
+
+        for state_values in &mut values {
+            state_values.sort_unstable();
+            state_values.dedup();
+        }
+
+        Self {
+            parameters: self.parameters.clone(),
+            values,
+        }
+    }
+}
+
+/// Prints the values of a single state as `(v_0, ..., v_n)`, or a set of these when the state represents multiple states.
+pub struct StateLabelFormatter<'a>(pub &'a [Vec<String>]);
+
+impl fmt::Display for StateLabelFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [values] = self.0 {
+            write!(f, "({})", values.iter().format(", "))
+        } else {
+            write!(
+                f,
+                "{{{}}}",
+                self.0
+                    .iter()
+                    .format_with(", ", |values, f| f(&format_args!("({})", values.iter().format(", "))))
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_labels_merge() {
+        let mut labels = StateLabels::new(vec![("x".to_string(), "Nat".to_string())]);
+        for value in ["0", "1", "0"] {
+            labels.push(vec![vec![value.to_string()]]);
+        }
+
+        let merged = labels.merge(1, |_| StateIndex::new(0));
+        assert_eq!(merged.len(), 1);
+        assert_eq!(
+            StateLabelFormatter(merged.values(StateIndex::new(0))).to_string(),
+            "{(0), (1)}"
+        );
+
+        let permuted = labels.permute(|state_index| StateIndex::new(2 - state_index.value()));
+        assert_eq!(permuted.values(StateIndex::new(1)), &[vec!["1".to_string()]]);
+    }
+}
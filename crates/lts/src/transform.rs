@@ -0,0 +1,364 @@
+#![forbid(unsafe_code)]
+
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+use regex::Regex;
+
+use merc_syntax::Action;
+use merc_syntax::MultiAction;
+use merc_utilities::MercError;
+
+use crate::LTS;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::LtsBuilderFast;
+use crate::StateIndex;
+use crate::StateLabels;
+use crate::TransitionLabel;
+
+/// Selects actions by their names, which is used to hide and block actions.
+#[derive(Clone, Debug)]
+pub enum ActionFilter {
+    /// Selects the actions with one of the given names.
+    Names(Vec<String>),
+    /// Selects the actions whose complete name matches the regular expression.
+    Regex(Regex),
+}
+
+impl ActionFilter {
+    /// Creates a filter that selects the actions whose name matches the given
+    /// regular expression, where the expression must match the complete name.
+    pub fn regex(pattern: &str) -> Result<ActionFilter, MercError> {
+        Ok(ActionFilter::Regex(Regex::new(&format!("^(?:{pattern})$"))?))
+    }
+
+    /// Returns true iff the action with the given name is selected.
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            ActionFilter::Names(names) => names.iter().any(|n| n == name),
+            ActionFilter::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// Hides the actions selected by the filter, i.e., they are removed from the
+/// multi-actions of the labels, and labels without actions become tau.
+pub fn hide_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    filter: &ActionFilter,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    transform_actions(lts, |multi_action| {
+        Ok(Some(MultiAction {
+            actions: multi_action
+                .actions
+                .iter()
+                .filter(|action| !filter.matches(&action.id))
+                .cloned()
+                .collect(),
+        }))
+    })
+}
+
+/// Removes the transitions of which the label contains an action that is selected by the filter.
+pub fn block_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    filter: &ActionFilter,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    transform_actions(lts, |multi_action| {
+        if multi_action.actions.iter().any(|action| filter.matches(&action.id)) {
+            Ok(None)
+        } else {
+            Ok(Some(multi_action.clone()))
+        }
+    })
+}
+
+/// Only keeps the transitions with a hidden label, or of which the action
+/// names of the label form one of the given multi-action names, such as `a|b`.
+///
+/// # Details
+///
+/// Similar to the allow operator of mCRL2, the order of the names in a
+/// multi-action name is irrelevant but their multiplicity is not, so `a|b`
+/// allows `b(1)|a` but neither `a` nor `a|a|b`.
+pub fn allow_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    allowed: &[String],
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let allowed: Vec<Vec<&str>> = allowed
+        .iter()
+        .map(|names| names.split('|').map(str::trim).sorted_unstable().collect())
+        .collect();
+
+    transform_actions(lts, |multi_action| {
+        let names: Vec<&str> = multi_action
+            .actions
+            .iter()
+            .map(|action| action.id.as_str())
+            .sorted_unstable()
+            .collect();
+
+        if allowed.contains(&names) {
+            Ok(Some(multi_action.clone()))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+/// Removes the states that are not reachable from the initial state.
+///
+/// # Details
+///
+/// The remaining states keep their relative order, so the indices of the
+/// states of an LTS without unreachable states do not change.
+pub fn remove_unreachable_states<L: TransitionLabel>(lts: LabelledTransitionSystem<L>) -> LabelledTransitionSystem<L> {
+    let mut reachable = vec![false; lts.num_of_states()];
+    let mut queue = VecDeque::from([lts.initial_state_index()]);
+    reachable[lts.initial_state_index()] = true;
+
+    while let Some(state_index) = queue.pop_front() {
+        for transition in lts.outgoing_transitions(state_index) {
+            if !reachable[transition.to] {
+                reachable[transition.to] = true;
+                queue.push_back(transition.to);
+            }
+        }
+    }
+
+    // The new index of every reachable state.
+    let mut new_index = vec![StateIndex::new(0); lts.num_of_states()];
+    let mut num_of_states = 0;
+    for state_index in lts.iter_states() {
+        if reachable[state_index] {
+            new_index[state_index] = StateIndex::new(num_of_states);
+            num_of_states += 1;
+        }
+    }
+
+    if num_of_states == lts.num_of_states() {
+        return lts;
+    }
+
+    let mut builder = LtsBuilderFast::with_capacity(lts.labels().to_vec(), Vec::new(), lts.num_of_transitions());
+    let labels: Vec<LabelIndex> = lts.labels().iter().map(|label| builder.add_label(label)).collect();
+    builder.require_num_of_states(num_of_states);
+
+    for state_index in lts.iter_states().filter(|state_index| reachable[*state_index]) {
+        for transition in lts.outgoing_transitions(state_index) {
+            builder.add_transition_index(
+                new_index[state_index],
+                labels[transition.label],
+                new_index[transition.to],
+            );
+        }
+    }
+
+    let state_labels = lts.state_labels().map(|state_labels| {
+        let mut result = StateLabels::new(state_labels.parameters().to_vec());
+        for state_index in lts.iter_states().filter(|state_index| reachable[*state_index]) {
+            result.push(state_labels.values(state_index).to_vec());
+        }
+        result
+    });
+
+    with_annotations(
+        builder.finish(new_index[lts.initial_state_index()], false),
+        &lts,
+        state_labels,
+    )
+}
+
+/// Returns the multi-action of a visible label, where a label that cannot be
+/// parsed as a multi-action, for example an arbitrary label of an `.aut` file,
+/// is treated as a single action without arguments.
+pub fn parse_multi_action<L: TransitionLabel>(label: &L) -> MultiAction {
+    let text = label.to_string();
+    MultiAction::parse(&text).unwrap_or_else(|_| MultiAction {
+        actions: vec![Action {
+            id: text,
+            args: Vec::new(),
+        }],
+    })
+}
+
+/// Returns the label of the given multi-action, where the actions are sorted
+/// such that equal multi-actions result in the same label.
+pub(crate) fn multi_action_label<L: TransitionLabel>(mut multi_action: MultiAction) -> Result<L, MercError> {
+    if multi_action.actions.is_empty() {
+        return Ok(L::tau_label());
+    }
+
+    multi_action.actions.sort_unstable();
+    L::from_string(&multi_action.to_string())
+}
+
+/// Applies the given function to the multi-action of every visible label,
+/// where `None` indicates that the transitions with that label are removed.
+///
+/// # Details
+///
+/// The labels that are not changed by the function are kept as is, and the
+/// resulting labels that are equal are merged.
+pub(crate) fn transform_actions<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    mut function: impl FnMut(&MultiAction) -> Result<Option<MultiAction>, MercError>,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let mut builder = LtsBuilderFast::with_capacity(Vec::new(), Vec::new(), lts.num_of_transitions());
+
+    let mut labels: Vec<Option<LabelIndex>> = Vec::with_capacity(lts.num_of_labels());
+    for (label_index, label) in lts.labels().iter().enumerate() {
+        if lts.is_hidden_label(LabelIndex::new(label_index)) {
+            labels.push(Some(builder.add_label(&L::tau_label())));
+            continue;
+        }
+
+        let multi_action = parse_multi_action(label);
+        let new_label = match function(&multi_action)? {
+            None => None,
+            Some(result) if result == multi_action => Some(label.clone()),
+            Some(result) => Some(multi_action_label(result)?),
+        };
+        labels.push(new_label.map(|new_label| builder.add_label(&new_label)));
+    }
+
+    builder.require_num_of_states(lts.num_of_states());
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            if let Some(label_index) = labels[transition.label] {
+                builder.add_transition_index(state_index, label_index, transition.to);
+            }
+        }
+    }
+
+    // Merging labels can result in duplicate transitions.
+    let state_labels = lts.state_labels().cloned();
+    Ok(with_annotations(
+        builder.finish(lts.initial_state_index(), true),
+        &lts,
+        state_labels,
+    ))
+}
+
+/// Adds the given state labels and the specification of the original LTS to the result.
+fn with_annotations<L: TransitionLabel>(
+    result: LabelledTransitionSystem<L>,
+    original: &LabelledTransitionSystem<L>,
+    state_labels: Option<StateLabels>,
+) -> LabelledTransitionSystem<L> {
+    let result = match state_labels {
+        Some(state_labels) => result.with_state_labels(state_labels),
+        None => result,
+    };
+
+    match original.specification() {
+        Some(specification) => result.with_specification(specification.clone()),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_utilities::random_test;
+
+    use crate::random_lts;
+    use crate::read_aut;
+    use crate::read_lts;
+
+    /// Returns the labels of the transitions of the given LTS as strings, sorted.
+    fn transitions<L: LTS>(lts: &L) -> Vec<String> {
+        lts.iter_states()
+            .flat_map(|state_index| {
+                lts.outgoing_transitions(state_index).map(move |transition| {
+                    format!("{state_index} {} {}", lts.labels()[transition.label], transition.to)
+                })
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    #[test]
+    fn test_hide_actions() {
+        let lts = read_aut(
+            b"des (0,4,3)\n(0,\"a(1)\",1)\n(0,\"b|a(2)\",2)\n(1,c,2)\n(2,\"c d\",0)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let result = hide_actions(lts, &ActionFilter::Names(vec!["a".to_string(), "c d".to_string()])).unwrap();
+        assert_eq!(transitions(&result), vec!["0 b 2", "0 i 1", "1 c 2", "2 i 0"]);
+    }
+
+    #[test]
+    fn test_hide_actions_regex() {
+        let lts = read_aut(b"des (0,3,2)\n(0,r1,1)\n(1,r2,0)\n(1,rs,0)\n" as &[u8], Vec::new()).unwrap();
+
+        let result = hide_actions(lts, &ActionFilter::regex("r[0-9]+").unwrap()).unwrap();
+        assert_eq!(transitions(&result), vec!["0 i 1", "1 i 0", "1 rs 0"]);
+    }
+
+    #[test]
+    fn test_block_and_allow_actions() {
+        let lts = read_aut(
+            b"des (0,4,3)\n(0,\"a(1)\",1)\n(0,\"b|a(2)\",2)\n(1,i,2)\n(2,c,0)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let blocked = block_actions(lts.clone(), &ActionFilter::Names(vec!["b".to_string()])).unwrap();
+        assert_eq!(transitions(&blocked), vec!["0 a(1) 1", "1 i 2", "2 c 0"]);
+
+        let allowed = allow_actions(lts, &["a | b".to_string(), "c".to_string()]).unwrap();
+        assert_eq!(transitions(&allowed), vec!["0 b|a(2) 2", "1 i 2", "2 c 0"]);
+    }
+
+    #[test]
+    fn test_remove_unreachable_states() {
+        let lts = read_aut(b"des (1,3,4)\n(0,a,1)\n(1,b,3)\n(3,c,1)\n" as &[u8], Vec::new()).unwrap();
+
+        let result = remove_unreachable_states(lts);
+        assert_eq!(result.num_of_states(), 2);
+        assert_eq!(result.initial_state_index(), StateIndex::new(0));
+        assert_eq!(transitions(&result), vec!["0 b 1", "1 c 0"]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_remove_unreachable_states_labels() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), vec![]).unwrap();
+        let num_of_states = lts.num_of_states();
+
+        // Blocking all visible actions makes the states after the first visible action unreachable.
+        let result = remove_unreachable_states(block_actions(lts, &ActionFilter::regex(".*").unwrap()).unwrap());
+        assert!(result.num_of_states() < num_of_states);
+        assert_eq!(result.state_labels().unwrap().len(), result.num_of_states());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_hide_actions() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+
+            // Hiding every action is the same as hiding them while reading.
+            let result = hide_actions(lts.clone(), &ActionFilter::regex(".*").unwrap()).unwrap();
+            assert_eq!(result.num_of_states(), lts.num_of_states());
+            assert!(
+                result
+                    .iter_states()
+                    .flat_map(|state_index| result.outgoing_transitions(state_index))
+                    .all(|transition| result.is_hidden_label(transition.label))
+            );
+
+            // Not selecting any action keeps the LTS intact.
+            let result = block_actions(lts.clone(), &ActionFilter::Names(Vec::new())).unwrap();
+            assert_eq!(transitions(&result), transitions(&lts));
+        });
+    }
+}
@@ -80,7 +80,7 @@ impl<K, V: fmt::Debug + Ord> Antichain<K, V> {
     /// Checks the internal consistency of the antichain invariant.
     #[cfg(test)]
     fn check_consistency(&self) {
-        for (_key, values) in &self.storage {
+        for values in self.storage.values() {
             for i in values.iter() {
                 for j in values.iter() {
                     if i == j {
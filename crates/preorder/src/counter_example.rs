@@ -0,0 +1,67 @@
+use std::fmt;
+
+use itertools::Itertools;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::LtsBuilder;
+use merc_lts::StateIndex;
+use merc_lts::TransitionLabel;
+
+/// Describes why the last state of the counter example trace violates the refinement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation<Label> {
+    /// The last action of the trace cannot be performed by the specification.
+    Trace,
+    /// After the trace the implementation can refuse the given set of actions,
+    /// whereas every (stable) specification state enables one of them.
+    Refusal(Vec<Label>),
+    /// After the trace the implementation diverges, whereas the specification does not.
+    Divergence,
+}
+
+/// A counter example that shows that an implementation does not refine a specification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CounterExample<Label> {
+    trace: Vec<Label>,
+    violation: Violation<Label>,
+}
+
+impl<Label: TransitionLabel> CounterExample<Label> {
+    /// Creates a new counter example from the given trace and violation.
+    pub fn new(trace: Vec<Label>, violation: Violation<Label>) -> Self {
+        Self { trace, violation }
+    }
+
+    /// Returns the trace of the implementation leading to the violation. For
+    /// the weak preorders the internal actions are omitted.
+    pub fn trace(&self) -> &[Label] {
+        &self.trace
+    }
+
+    /// Returns the reason why the refinement is violated after the trace.
+    pub fn violation(&self) -> &Violation<Label> {
+        &self.violation
+    }
+
+    /// Returns the counter example as a linear labelled transition system consisting of the trace.
+    pub fn to_lts(&self) -> LabelledTransitionSystem<Label> {
+        let mut builder = LtsBuilder::new(self.trace.clone(), Vec::new());
+        for (index, label) in self.trace.iter().enumerate() {
+            builder.add_transition(StateIndex::new(index), label, StateIndex::new(index + 1));
+        }
+
+        builder.require_num_of_states(self.trace.len() + 1);
+        builder.finish(StateIndex::new(0))
+    }
+}
+
+impl<Label: TransitionLabel> fmt::Display for CounterExample<Label> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "trace: [{}]", self.trace.iter().format(", "))?;
+
+        match &self.violation {
+            Violation::Trace => Ok(()),
+            Violation::Refusal(refusals) => write!(f, ", refusals: {{{}}}", refusals.iter().format(", ")),
+            Violation::Divergence => write!(f, ", divergent"),
+        }
+    }
+}
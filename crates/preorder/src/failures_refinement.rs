@@ -7,19 +7,28 @@
 //! inclusion. All algorithms come in a variant with and without internal steps. It is possible to generate a counter
 //! transition system in case the inclusion is answered by no.
 
+use std::collections::VecDeque;
+
 use log::trace;
 use merc_collections::VecSet;
 use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
 use merc_lts::StateIndex;
+use merc_lts::TransitionLabel;
 use merc_reduction::Equivalence;
 use merc_reduction::Partition;
 use merc_reduction::quotient_lts_block;
+use merc_reduction::quotient_lts_naive;
 use merc_reduction::reduce_lts;
 use merc_reduction::strong_bisim_sigref;
+use merc_reduction::tau_scc_decomposition;
 use merc_utilities::Timing;
 
 use crate::Antichain;
+use crate::CounterExample;
 use crate::RefinementType;
+use crate::Violation;
 
 /// Sets the exploration strategy for the failures refinement algorithm.
 pub enum ExplorationStrategy {
@@ -31,17 +40,20 @@ pub enum ExplorationStrategy {
 /// whether transition system l1 is included in transition system l2, in the
 /// sense of trace inclusions, failures inclusion and divergence failures
 /// inclusion.
+///
+/// When `COUNTER_EXAMPLE` is true and the inclusion does not hold, a counter
+/// example is returned as well. With the [ExplorationStrategy::BFS] strategy
+/// the trace of this counter example is as short as possible.
 pub fn is_failures_refinement<L: LTS, const COUNTER_EXAMPLE: bool>(
     impl_lts: L,
     spec_lts: L,
     refinement: RefinementType,
-    _strategy: ExplorationStrategy,
+    strategy: ExplorationStrategy,
     preprocess: bool,
     timing: &mut Timing,
-) -> bool {
-    let reduction = match refinement {
-        RefinementType::Trace => Equivalence::StrongBisim,
-    };
+) -> (bool, Option<CounterExample<L::Label>>) {
+    // Strong bisimulation preserves all the considered preorders.
+    let reduction = Equivalence::StrongBisim;
 
     // For the preprocessing/quotienting step it makes sense to merge both LTSs
     // together in case that some states are equivalent. So we do this in all branches.
@@ -72,47 +84,239 @@ pub fn is_failures_refinement<L: LTS, const COUNTER_EXAMPLE: bool>(
         impl_lts.merge_disjoint(&spec_lts)
     };
 
-    let mut working = vec![(merged_lts.initial_state_index(), VecSet::singleton(initial_spec))];
+    let (merged_lts, initial_spec) = if refinement.is_weak() {
+        // All states on a tau-cycle are equivalent for the weak preorders, so
+        // these components can be collapsed. The tau self-loops are kept such
+        // that divergences (and instability) are preserved.
+        let mut time_divergence = timing.start("preprocess divergence");
+        let scc_partition = tau_scc_decomposition(&merged_lts);
+        let initial_spec = StateIndex::new(*scc_partition.block_number(initial_spec));
+        let quotient_lts = quotient_lts_naive(&merged_lts, &scc_partition, false);
+        time_divergence.finish();
+
+        (quotient_lts, initial_spec)
+    } else {
+        (merged_lts, initial_spec)
+    };
+
+    let mut time_refinement = timing.start("refinement");
+
+    let initial_spec = if refinement.is_weak() {
+        tau_closure(&merged_lts, VecSet::singleton(initial_spec))
+    } else {
+        VecSet::singleton(initial_spec)
+    };
+
+    let mut working = VecDeque::from([(merged_lts.initial_state_index(), initial_spec.clone(), 0)]);
+
+    // For every explored pair we store the index of its predecessor and the
+    // label of the implementation transition leading to it, such that the
+    // trace can be reconstructed. Only used when a counter example is requested.
+    let mut predecessors: Vec<Option<(usize, LabelIndex)>> = Vec::new();
+    if COUNTER_EXAMPLE {
+        predecessors.push(None);
+    }
 
     // The antichain data structure is used for storing explored states. However, as opposed to a discovered set it
     // allows for pruning additional pairs based on the `antichain` property.
     let mut antichain = Antichain::new();
+    antichain.insert(merged_lts.initial_state_index(), initial_spec);
 
-    while let Some((impl_state, spec)) = working.pop() {
+    let mut result = true;
+    let mut counter_example = None;
+    'outer: while let Some((impl_state, spec, node)) = match strategy {
+        ExplorationStrategy::BFS => working.pop_front(),
+        ExplorationStrategy::DFS => working.pop_back(),
+    } {
         trace!("Checking ({:?}, {:?})", impl_state, spec);
         // pop (impl,spec) from working;
 
+        if refinement == RefinementType::FailuresDivergence {
+            if spec.iter().any(|s| is_divergent(&merged_lts, *s)) {
+                // A divergent specification allows any behaviour afterwards.
+                continue;
+            }
+
+            if is_divergent(&merged_lts, impl_state) {
+                // if divergent(impl) and not divergent(spec) then return false
+                result = false;
+                if COUNTER_EXAMPLE {
+                    counter_example = Some(construct_counter_example(
+                        &merged_lts,
+                        &predecessors,
+                        node,
+                        Violation::Divergence,
+                        refinement.is_weak(),
+                    ));
+                }
+                break;
+            }
+        }
+
+        if refinement.has_refusals() && is_stable(&merged_lts, impl_state, refinement.is_weak()) {
+            // if refusals(impl) not included in refusals(spec) then return false
+            let impl_enabled = enabled(&merged_lts, impl_state, refinement.is_weak());
+            if !spec.iter().any(|s| {
+                is_stable(&merged_lts, *s, refinement.is_weak())
+                    && enabled(&merged_lts, *s, refinement.is_weak()).is_subset(&impl_enabled)
+            }) {
+                trace!("The refusals of {impl_state:?} are not included in the refusals of {spec:?}");
+                result = false;
+                if COUNTER_EXAMPLE {
+                    // The refusals consist of all (visible) labels that are not enabled.
+                    let refusals = (0..merged_lts.num_of_labels())
+                        .map(LabelIndex::new)
+                        .filter(|label| {
+                            !impl_enabled.contains(label)
+                                && (!refinement.is_weak() || !merged_lts.is_hidden_label(*label))
+                        })
+                        .map(|label| merged_lts.labels()[label.value()].clone())
+                        .collect();
+
+                    counter_example = Some(construct_counter_example(
+                        &merged_lts,
+                        &predecessors,
+                        node,
+                        Violation::Refusal(refusals),
+                        refinement.is_weak(),
+                    ));
+                }
+                break;
+            }
+        }
+
         for impl_transition in merged_lts.outgoing_transitions(impl_state) {
-            // spec' := {s' | exists s in spec. s-e->s'};
-            let mut spec_prime = VecSet::new();
-            for s in &spec {
-                for spec_transition in merged_lts.outgoing_transitions(*s) {
-                    if impl_transition.label == spec_transition.label {
-                        spec_prime.insert(spec_transition.to);
+            let spec_prime = if refinement.is_weak() && merged_lts.is_hidden_label(impl_transition.label) {
+                // An internal step of the implementation is matched by not moving in the specification.
+                spec.clone()
+            } else {
+                // spec' := {s' | exists s in spec. s-e->s'};
+                let mut spec_prime = VecSet::new();
+                for s in &spec {
+                    for spec_transition in merged_lts.outgoing_transitions(*s) {
+                        if impl_transition.label == spec_transition.label {
+                            spec_prime.insert(spec_transition.to);
+                        }
                     }
                 }
-            }
+
+                if refinement.is_weak() {
+                    tau_closure(&merged_lts, spec_prime)
+                } else {
+                    spec_prime
+                }
+            };
+
+            // Keep track of the predecessor of the new pair.
+            let next_node = if COUNTER_EXAMPLE {
+                predecessors.push(Some((node, impl_transition.label)));
+                predecessors.len() - 1
+            } else {
+                0
+            };
 
             trace!("spec' = {:?}", spec_prime);
             if spec_prime.is_empty() {
                 // if spec' = {} then
-                return false; //    return false;
+                result = false; //    return false;
+                if COUNTER_EXAMPLE {
+                    counter_example = Some(construct_counter_example(
+                        &merged_lts,
+                        &predecessors,
+                        next_node,
+                        Violation::Trace,
+                        refinement.is_weak(),
+                    ));
+                }
+                break 'outer;
             }
 
             if antichain.insert(impl_transition.to, spec_prime.clone()) {
                 // if antichain_insert(impl,spec') then
-                working.push((impl_transition.to, spec_prime));
+                working.push_back((impl_transition.to, spec_prime, next_node));
+            } else if COUNTER_EXAMPLE {
+                // The pair is not explored, so its predecessor information is not needed.
+                predecessors.pop();
             }
         }
     }
 
-    true
+    time_refinement.finish();
+    (result, counter_example)
+}
+
+/// Reconstructs the trace leading to the given node from the predecessor
+/// information, where internal actions are omitted when `weak` is true.
+fn construct_counter_example<Label: TransitionLabel>(
+    lts: &LabelledTransitionSystem<Label>,
+    predecessors: &[Option<(usize, LabelIndex)>],
+    mut node: usize,
+    violation: Violation<Label>,
+    weak: bool,
+) -> CounterExample<Label> {
+    let mut trace = Vec::new();
+    while let Some((predecessor, label)) = predecessors[node] {
+        if !weak || !lts.is_hidden_label(label) {
+            trace.push(lts.labels()[label.value()].clone());
+        }
+        node = predecessor;
+    }
+
+    trace.reverse();
+    CounterExample::new(trace, violation)
+}
+
+/// Extends the given set of states with all states that can be reached by internal steps.
+fn tau_closure<L: LTS>(lts: &L, states: VecSet<StateIndex>) -> VecSet<StateIndex> {
+    let mut closure = states.clone();
+    let mut stack: Vec<StateIndex> = states.iter().copied().collect();
+
+    while let Some(state) = stack.pop() {
+        for transition in lts.outgoing_transitions(state) {
+            if lts.is_hidden_label(transition.label) && closure.insert(transition.to) {
+                stack.push(transition.to);
+            }
+        }
+    }
+
+    closure
+}
+
+/// Returns true iff the given state can perform an infinite sequence of internal
+/// steps. Assumes that the tau-cycles have been collapsed into tau self-loops.
+fn is_divergent<L: LTS>(lts: &L, state: StateIndex) -> bool {
+    lts.outgoing_transitions(state)
+        .any(|transition| lts.is_hidden_label(transition.label) && transition.to == state)
+}
+
+/// Returns true iff the given state is stable, i.e., it cannot perform an
+/// internal step. Without internal steps every state is stable.
+fn is_stable<L: LTS>(lts: &L, state: StateIndex, weak: bool) -> bool {
+    !weak
+        || !lts
+            .outgoing_transitions(state)
+            .any(|transition| lts.is_hidden_label(transition.label))
+}
+
+/// Returns the set of labels enabled in the given state, excluding the internal
+/// action when `weak` is true.
+fn enabled<L: LTS>(lts: &L, state: StateIndex, weak: bool) -> VecSet<LabelIndex> {
+    let mut result = VecSet::new();
+    for transition in lts.outgoing_transitions(state) {
+        if !weak || !lts.is_hidden_label(transition.label) {
+            result.insert(transition.label);
+        }
+    }
+
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use merc_io::DumpFiles;
+    use merc_lts::LTS;
     use merc_lts::random_lts;
+    use merc_lts::read_aut;
     use merc_lts::write_aut;
     use merc_reduction::Equivalence;
     use merc_reduction::reduce_lts;
@@ -121,7 +325,10 @@ mod tests {
 
     use crate::ExplorationStrategy;
     use crate::RefinementType;
+    use crate::Violation;
     use crate::is_failures_refinement;
+    use crate::refines;
+    use crate::refines_counter_example;
 
     #[test]
     #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
@@ -145,9 +352,208 @@ mod tests {
                     ExplorationStrategy::BFS,
                     false,
                     &mut timing
-                ),
+                )
+                .0,
                 "Strong bisimulation implies trace refinement."
             );
         });
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_refinement_reflexive() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+            let mut timing = Timing::default();
+
+            for refinement in [
+                RefinementType::Trace,
+                RefinementType::WeakTrace,
+                RefinementType::Failures,
+                RefinementType::WeakFailures,
+                RefinementType::FailuresDivergence,
+                RefinementType::Simulation,
+                RefinementType::ReadySimulation,
+            ] {
+                let reduced_lts = reduce_lts(lts.clone(), Equivalence::StrongBisim, &mut timing);
+
+                assert!(
+                    refines(reduced_lts, lts.clone(), refinement, &mut timing),
+                    "Strong bisimulation implies {refinement:?} refinement."
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_failures_refinement() {
+        // The choice between b and c is made after the a-action.
+        let spec_lts = read_aut(
+            b"des (0,3,3)
+(0,a,1)
+(1,b,2)
+(1,c,2)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        // The choice between b and c is made before the a-action.
+        let impl_lts = read_aut(
+            b"des (0,4,4)
+(0,a,1)
+(0,a,2)
+(1,b,3)
+(2,c,3)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        for refinement in [RefinementType::Trace, RefinementType::WeakTrace] {
+            assert!(refines(impl_lts.clone(), spec_lts.clone(), refinement, &mut timing));
+            assert!(refines(spec_lts.clone(), impl_lts.clone(), refinement, &mut timing));
+        }
+
+        for refinement in [
+            RefinementType::Failures,
+            RefinementType::WeakFailures,
+            RefinementType::FailuresDivergence,
+        ] {
+            assert!(
+                !refines(impl_lts.clone(), spec_lts.clone(), refinement, &mut timing),
+                "The implementation can refuse b after a, which the specification cannot."
+            );
+            assert!(refines(spec_lts.clone(), impl_lts.clone(), refinement, &mut timing));
+
+            let counter_example =
+                refines_counter_example(impl_lts.clone(), spec_lts.clone(), refinement, &mut timing).unwrap_err();
+            assert_eq!(counter_example.trace(), ["a"]);
+            assert!(
+                matches!(counter_example.violation(), Violation::Refusal(refusals) if refusals.contains(&"b".to_string()) || refusals.contains(&"c".to_string()))
+            );
+        }
+
+        // Both states after a are simulated by the single state after a in the specification, but they refuse either b or c.
+        assert!(refines(
+            impl_lts.clone(),
+            spec_lts.clone(),
+            RefinementType::Simulation,
+            &mut timing
+        ));
+        assert!(!refines(
+            spec_lts.clone(),
+            impl_lts.clone(),
+            RefinementType::Simulation,
+            &mut timing
+        ));
+        assert!(!refines(
+            impl_lts,
+            spec_lts,
+            RefinementType::ReadySimulation,
+            &mut timing
+        ));
+    }
+
+    #[test]
+    fn test_trace_counter_example() {
+        let spec_lts = read_aut(
+            b"des (0,3,4)
+(0,a,1)
+(1,i,2)
+(2,b,3)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let impl_lts = read_aut(
+            b"des (0,3,4)
+(0,a,1)
+(1,i,2)
+(2,c,3)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        let counter_example =
+            refines_counter_example(impl_lts.clone(), spec_lts.clone(), RefinementType::Trace, &mut timing)
+                .unwrap_err();
+        assert_eq!(counter_example.trace(), ["a", "i", "c"]);
+        assert_eq!(counter_example.violation(), &Violation::Trace);
+
+        // The internal action is omitted from the weak counter example.
+        let counter_example =
+            refines_counter_example(impl_lts, spec_lts, RefinementType::WeakTrace, &mut timing).unwrap_err();
+        assert_eq!(counter_example.trace(), ["a", "c"]);
+
+        let trace_lts = counter_example.to_lts();
+        assert_eq!(trace_lts.num_of_states(), 3);
+        assert_eq!(trace_lts.num_of_transitions(), 2);
+    }
+
+    #[test]
+    fn test_failures_divergence_refinement() {
+        let spec_lts = read_aut(
+            b"des (0,1,2)
+(0,a,1)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        // After the a-action the implementation diverges.
+        let impl_lts = read_aut(
+            b"des (0,3,3)
+(0,a,1)
+(1,i,2)
+(2,i,1)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        assert!(refines(
+            impl_lts.clone(),
+            spec_lts.clone(),
+            RefinementType::WeakTrace,
+            &mut timing
+        ));
+        assert!(refines(
+            impl_lts.clone(),
+            spec_lts.clone(),
+            RefinementType::WeakFailures,
+            &mut timing
+        ));
+        assert!(!refines(
+            spec_lts.clone(),
+            impl_lts.clone(),
+            RefinementType::WeakFailures,
+            &mut timing
+        ));
+        assert!(!refines(
+            impl_lts.clone(),
+            spec_lts.clone(),
+            RefinementType::FailuresDivergence,
+            &mut timing
+        ));
+        assert!(
+            refines(
+                spec_lts.clone(),
+                impl_lts.clone(),
+                RefinementType::FailuresDivergence,
+                &mut timing
+            ),
+            "A divergent specification allows any behaviour."
+        );
+
+        let counter_example =
+            refines_counter_example(impl_lts, spec_lts, RefinementType::FailuresDivergence, &mut timing).unwrap_err();
+        assert_eq!(counter_example.trace(), ["a"]);
+        assert_eq!(counter_example.violation(), &Violation::Divergence);
+    }
 }
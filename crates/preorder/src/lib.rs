@@ -2,9 +2,11 @@
 #![forbid(unsafe_code)]
 
 mod antichain;
+mod counter_example;
 mod failures_refinement;
 mod preorder;
 
 pub use antichain::*;
+pub use counter_example::*;
 pub use failures_refinement::*;
 pub use preorder::*;
@@ -1,24 +1,100 @@
 use merc_lts::LTS;
+use merc_reduction::ready_simulation_preorder;
+use merc_reduction::simulation_preorder;
 use merc_utilities::Timing;
 
+use crate::CounterExample;
 use crate::ExplorationStrategy;
 use crate::is_failures_refinement;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum RefinementType {
+    /// Trace inclusion, where internal actions are treated as visible actions.
     Trace,
+    /// Trace inclusion where internal actions are abstracted from.
+    WeakTrace,
+    /// Failures inclusion, where internal actions are treated as visible actions.
+    Failures,
+    /// Stable failures inclusion where internal actions are abstracted from.
+    WeakFailures,
+    /// Failures-divergence inclusion, as used by the CSP refinement checker FDR.
+    FailuresDivergence,
+    /// Simulation preorder, where internal actions are treated as visible actions.
+    Simulation,
+    /// Ready simulation preorder, where related states moreover enable the same actions.
+    ReadySimulation,
 }
 
+impl RefinementType {
+    /// Returns true iff the refinement abstracts from internal actions.
+    pub fn is_weak(&self) -> bool {
+        matches!(
+            self,
+            RefinementType::WeakTrace | RefinementType::WeakFailures | RefinementType::FailuresDivergence
+        )
+    }
+
+    /// Returns true iff the refinement takes the refusals of states into account.
+    pub fn has_refusals(&self) -> bool {
+        matches!(
+            self,
+            RefinementType::Failures | RefinementType::WeakFailures | RefinementType::FailuresDivergence
+        )
+    }
+
+    /// Returns true iff the refinement is one of the simulation preorders.
+    pub fn is_simulation(&self) -> bool {
+        matches!(self, RefinementType::Simulation | RefinementType::ReadySimulation)
+    }
+}
+
+/// Returns true iff the implementation LTS refines the specification LTS modulo the given preorder.
 pub fn refines<L: LTS>(impl_lts: L, spec_lts: L, preorder: RefinementType, timing: &mut Timing) -> bool {
-    match preorder {
-        RefinementType::Trace => is_failures_refinement::<L, false>(
-            impl_lts,
-            spec_lts,
-            RefinementType::Trace,
-            ExplorationStrategy::BFS,
-            false,
-            timing,
-        ),
+    if preorder.is_simulation() {
+        return is_simulated_by(impl_lts, spec_lts, preorder, timing);
+    }
+
+    let (result, _) =
+        is_failures_refinement::<L, false>(impl_lts, spec_lts, preorder, ExplorationStrategy::BFS, false, timing);
+    result
+}
+
+/// Checks whether the implementation LTS refines the specification LTS modulo
+/// the given preorder. Returns a shortest counter example if this is not the case.
+///
+/// # Panics
+///
+/// Counter examples are not supported for the simulation preorders.
+pub fn refines_counter_example<L: LTS>(
+    impl_lts: L,
+    spec_lts: L,
+    preorder: RefinementType,
+    timing: &mut Timing,
+) -> Result<(), CounterExample<L::Label>> {
+    assert!(
+        !preorder.is_simulation(),
+        "Counter examples are not supported for {preorder:?}"
+    );
+
+    match is_failures_refinement::<L, true>(impl_lts, spec_lts, preorder, ExplorationStrategy::BFS, false, timing) {
+        (true, _) => Ok(()),
+        (false, counter_example) => Err(counter_example.expect("A counter example is generated when refinement fails")),
     }
 }
+
+/// Returns true iff the initial state of the implementation is (ready) simulated by the initial state of the specification.
+fn is_simulated_by<L: LTS>(impl_lts: L, spec_lts: L, preorder: RefinementType, timing: &mut Timing) -> bool {
+    let mut time_merge = timing.start("merge lts");
+    let (merged, spec_initial) = impl_lts.merge_disjoint(&spec_lts);
+    drop(spec_lts); // No longer needed.
+    time_merge.finish();
+
+    let relation = if preorder == RefinementType::ReadySimulation {
+        ready_simulation_preorder(&merged, timing)
+    } else {
+        simulation_preorder(&merged, timing)
+    };
+
+    relation.related(merged.initial_state_index(), spec_initial)
+}
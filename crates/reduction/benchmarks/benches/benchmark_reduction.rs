@@ -0,0 +1,66 @@
+#![forbid(unsafe_code)]
+
+use std::fmt::Write;
+use std::hint::black_box;
+
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::read_aut;
+use merc_reduction::strong_bisim_pt;
+use merc_reduction::strong_bisim_sigref;
+use merc_utilities::Timing;
+
+/// Returns a sequence of `a` actions of the given length, where every state can
+/// also take a `b` action to the final deadlock state. Signature refinement
+/// requires a number of rounds that is linear in the length of the sequence to
+/// distinguish all states.
+fn deep_lts(length: usize) -> LabelledTransitionSystem<String> {
+    let mut aut = format!("des (0,{},{})\n", 2 * length - 1, length + 1);
+    for state_index in 0..length - 1 {
+        writeln!(aut, "({},a,{})", state_index, state_index + 1).unwrap();
+        writeln!(aut, "({},b,{})", state_index, length).unwrap();
+    }
+    writeln!(aut, "({},a,{})", length - 1, length).unwrap();
+
+    read_aut(aut.as_bytes(), Vec::new()).unwrap()
+}
+
+pub fn criterion_benchmark_strong_bisim(c: &mut Criterion) {
+    let examples = [
+        (
+            "abp.aut",
+            read_aut(include_bytes!("../../../../examples/lts/abp.aut") as &[u8], Vec::new()).unwrap(),
+        ),
+        (
+            "selfloops.aut",
+            read_aut(
+                include_bytes!("../../../../examples/lts/selfloops.aut") as &[u8],
+                Vec::new(),
+            )
+            .unwrap(),
+        ),
+        ("deep", deep_lts(1000)),
+    ];
+
+    for (name, lts) in examples {
+        c.bench_function(&format!("strong_bisim_sigref {name}"), |bencher| {
+            bencher.iter(|| {
+                let mut timing = Timing::new();
+                let _ = black_box(strong_bisim_sigref(lts.clone(), &mut timing));
+            });
+        });
+
+        c.bench_function(&format!("strong_bisim_pt {name}"), |bencher| {
+            bencher.iter(|| {
+                let mut timing = Timing::new();
+                let _ = black_box(strong_bisim_pt(lts.clone(), &mut timing));
+            });
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark_strong_bisim,);
+criterion_main!(benches);
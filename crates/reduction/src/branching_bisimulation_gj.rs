@@ -0,0 +1,981 @@
+//! Implements the O(m log n) branching bisimulation algorithm by Groote, Jansen, Keiren and Wijs.
+#![forbid(unsafe_code)]
+
+use std::mem::take;
+
+use log::debug;
+use log::trace;
+
+use merc_io::TimeProgress;
+use merc_lts::IncomingTransitions;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::StateIndex;
+use merc_lts::Transition;
+use merc_utilities::Timing;
+
+use crate::BlockIndex;
+use crate::BlockPartition;
+use crate::BlockPartitionBuilder;
+use crate::IndexedPartition;
+use crate::Partition;
+use crate::preprocess_branching;
+
+/// The index of a constellation, which is a union of blocks.
+type ConstellationIndex = usize;
+
+/// The index of an [OutSlice].
+type OutSliceIndex = usize;
+
+/// The index of a [BlockSlice].
+type BlockSliceIndex = usize;
+
+/// Computes a branching bisimulation partitioning using the O(m log n)
+/// algorithm by Groote, Jansen, Keiren and Wijs.
+///
+/// In every step a block that contains at most half of the states of a
+/// non-trivial constellation becomes a constellation of its own. The blocks
+/// with transitions into the new constellation are split into the states that
+/// can inertly reach such a transition and the other states, and the former are
+/// split again with respect to the remainder of the old constellation. Both
+/// parts of a split are explored in lockstep and only the part that is found
+/// first is split off, so a state is only visited when it ends up in the
+/// smaller half. The transitions of a state with the same label into the same
+/// constellation share a counter, and these counters are grouped per block
+/// into block-to-constellation slices. Blocks with new bottom states are
+/// stabilised under all constellations afterwards.
+///
+/// The preprocessing removes the tau-cycles and sorts the states topologically,
+/// such that every state can inertly reach a bottom state.
+///
+/// The returned mapping relates every state of the input LTS to its state in
+/// the preprocessed LTS, see [preprocess_branching].
+pub fn branching_bisim_gj<L: LTS>(
+    lts: L,
+    timing: &mut Timing,
+) -> (LabelledTransitionSystem<L::Label>, BlockPartition, IndexedPartition) {
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, mapping) = preprocess_branching(lts);
+    let incoming = IncomingTransitions::new(&preprocessed_lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let mut algorithm = Refinement::new(&preprocessed_lts, &incoming);
+    algorithm.stabilise();
+
+    let progress = TimeProgress::new(
+        |(num_of_blocks, num_of_constellations): (usize, usize)| {
+            debug!("Found {num_of_blocks} blocks and {num_of_constellations} constellations...");
+        },
+        1,
+    );
+
+    while let Some(constellation) = algorithm.non_trivial.pop() {
+        progress.print((
+            algorithm.partition.num_of_blocks(),
+            algorithm.constellation_blocks.len(),
+        ));
+
+        algorithm.split_constellation(constellation);
+        algorithm.stabilise();
+    }
+
+    let partition = algorithm.partition;
+    trace!("Refinement partition {partition}");
+    time.finish();
+
+    (preprocessed_lts, partition, mapping)
+}
+
+/// The transitions of a state with the same label into the same constellation,
+/// which share a single counter.
+struct OutSlice {
+    state: StateIndex,
+    label: LabelIndex,
+    constellation: ConstellationIndex,
+
+    /// The number of transitions in this out-slice.
+    count: usize,
+
+    /// The block-to-constellation slice that contains this out-slice, and the position in that slice.
+    block_slice: BlockSliceIndex,
+    block_slice_position: usize,
+
+    /// The position in the out-slices of the state.
+    state_position: usize,
+
+    /// Relates the out-slices into the old and the new constellation of the
+    /// same state and label when a constellation is split, valid when the
+    /// stamp is equal to the current round.
+    partner: OutSliceIndex,
+    partner_stamp: usize,
+}
+
+/// The out-slices of the states in a block with the same label into the same
+/// constellation.
+struct BlockSlice {
+    block: BlockIndex,
+    label: LabelIndex,
+    constellation: ConstellationIndex,
+    out_slices: Vec<OutSliceIndex>,
+
+    /// The position in the slices of the block.
+    position: usize,
+
+    /// The slice that the out-slices are moved to when a block or constellation
+    /// is split, valid when the stamp is equal to the stamp of that split.
+    partner: BlockSliceIndex,
+    partner_stamp: usize,
+
+    /// For a slice into a new constellation this is the slice of the same block
+    /// and label into the remainder of the old constellation, valid when the
+    /// stamp is equal to the current round.
+    co_slice: Option<BlockSliceIndex>,
+    co_stamp: usize,
+
+    /// The number of new bottom states with an out-slice in this slice.
+    hits: usize,
+    hits_stamp: usize,
+}
+
+/// The states with a transition in the splitter under which a block is split.
+enum Splitter<'s> {
+    /// The given states, which are marked with the stamp.
+    Marked { states: &'s [StateIndex], stamp: usize },
+
+    /// The states with an out-slice in the given block-to-constellation slice.
+    Slice(BlockSliceIndex),
+}
+
+struct Refinement<'a, L: LTS> {
+    lts: &'a L,
+    incoming: &'a IncomingTransitions,
+
+    partition: BlockPartition,
+    builder: BlockPartitionBuilder,
+
+    /// The constellation of every block, and the blocks of every constellation.
+    block_constellation: Vec<ConstellationIndex>,
+    constellation_blocks: Vec<Vec<BlockIndex>>,
+
+    /// The constellations that consist of more than one block.
+    non_trivial: Vec<ConstellationIndex>,
+
+    /// The out-slice of every transition, where the transitions are numbered in
+    /// the order of the outgoing transitions.
+    transition_out_slice: Vec<OutSliceIndex>,
+
+    /// The transitions into every state, as the range `incoming_offsets[s]..incoming_offsets[s + 1]` of `incoming_ids`.
+    incoming_offsets: Vec<usize>,
+    incoming_ids: Vec<usize>,
+
+    /// The out-slices and the non-empty out-slices of every state.
+    out_slices: Vec<OutSlice>,
+    state_out_slices: Vec<Vec<OutSliceIndex>>,
+
+    /// The block-to-constellation slices and the non-empty slices of every block.
+    slices: Vec<BlockSlice>,
+    block_slices: Vec<Vec<BlockSliceIndex>>,
+
+    /// The number of inert tau transitions of every state, i.e., to a state in the same block.
+    inert_count: Vec<usize>,
+
+    /// The bottom states of every block, and the position of every bottom state in that list.
+    bottom: Vec<Vec<StateIndex>>,
+    bottom_position: Vec<usize>,
+
+    /// The states of every block that have become bottom states, and must still
+    /// be stabilised under all constellations. The lists can contain states
+    /// that have been moved to another block since.
+    is_new_bottom: Vec<bool>,
+    new_bottom: Vec<Vec<StateIndex>>,
+    new_bottom_blocks: Vec<BlockIndex>,
+
+    /// Every mark below is valid when it is equal to the stamp of the current
+    /// operation, which avoids clearing them.
+    stamp: usize,
+    round: usize,
+    marked: Vec<usize>,
+    reaching: Vec<usize>,
+    other: Vec<usize>,
+    counter: Vec<usize>,
+    counter_stamp: Vec<usize>,
+}
+
+impl<'a, L: LTS> Refinement<'a, L> {
+    /// Creates the initial partition with a single block and constellation,
+    /// where every bottom state is new.
+    fn new(lts: &'a L, incoming: &'a IncomingTransitions) -> Self {
+        let num_of_states = lts.num_of_states();
+        let mut partition = BlockPartition::new(num_of_states);
+        let mut builder = BlockPartitionBuilder::default();
+
+        // The initial partition has all elements marked.
+        let _ = partition.partition_marked_with(BlockIndex::new(0), &mut builder, |_, _| BlockIndex::new(0));
+
+        let mut algorithm = Self {
+            lts,
+            incoming,
+            partition,
+            builder,
+            block_constellation: vec![0],
+            constellation_blocks: vec![vec![BlockIndex::new(0)]],
+            non_trivial: Vec::new(),
+            transition_out_slice: Vec::with_capacity(lts.num_of_transitions()),
+            incoming_offsets: vec![0; num_of_states + 1],
+            incoming_ids: vec![0; lts.num_of_transitions()],
+            out_slices: Vec::new(),
+            state_out_slices: vec![Vec::new(); num_of_states],
+            slices: Vec::new(),
+            block_slices: vec![Vec::new()],
+            inert_count: vec![0; num_of_states],
+            bottom: vec![Vec::new()],
+            bottom_position: vec![usize::MAX; num_of_states],
+            is_new_bottom: vec![false; num_of_states],
+            new_bottom: vec![Vec::new()],
+            new_bottom_blocks: Vec::new(),
+            stamp: 0,
+            round: 0,
+            marked: vec![0; num_of_states],
+            reaching: vec![0; num_of_states],
+            other: vec![0; num_of_states],
+            counter: vec![0; num_of_states],
+            counter_stamp: vec![0; num_of_states],
+        };
+
+        // The out-slice of the current state and the slice of the initial block for every label.
+        let mut label_out_slice = vec![usize::MAX; lts.num_of_labels()];
+        let mut label_slice = vec![usize::MAX; lts.num_of_labels()];
+        let mut targets = Vec::with_capacity(lts.num_of_transitions());
+
+        for state_index in lts.iter_states() {
+            for transition in lts.outgoing_transitions(state_index) {
+                if lts.is_hidden_label(transition.label) {
+                    algorithm.inert_count[state_index] += 1;
+                }
+
+                let mut out_slice = label_out_slice[transition.label];
+                if out_slice == usize::MAX || algorithm.out_slices[out_slice].state != state_index {
+                    if label_slice[transition.label] == usize::MAX {
+                        label_slice[transition.label] =
+                            algorithm.add_block_slice(BlockIndex::new(0), transition.label, 0);
+                    }
+
+                    out_slice =
+                        algorithm.add_out_slice(state_index, transition.label, 0, label_slice[transition.label]);
+                    label_out_slice[transition.label] = out_slice;
+                }
+
+                algorithm.out_slices[out_slice].count += 1;
+                algorithm.transition_out_slice.push(out_slice);
+                targets.push(transition.to);
+            }
+
+            if algorithm.inert_count[state_index] == 0 {
+                algorithm.make_bottom(state_index);
+            }
+        }
+
+        // Sort the transitions on their target state.
+        for to in &targets {
+            algorithm.incoming_offsets[**to + 1] += 1;
+        }
+
+        for state_index in 0..num_of_states {
+            algorithm.incoming_offsets[state_index + 1] += algorithm.incoming_offsets[state_index];
+        }
+
+        let mut position = algorithm.incoming_offsets.clone();
+        for (transition, to) in targets.iter().enumerate() {
+            algorithm.incoming_ids[position[**to]] = transition;
+            position[**to] += 1;
+        }
+
+        algorithm
+    }
+
+    /// Splits a block that contains at most half of the states from the given
+    /// constellation into a constellation of its own, and stabilises the blocks
+    /// with transitions into this new constellation.
+    fn split_constellation(&mut self, constellation: ConstellationIndex) {
+        let blocks = &self.constellation_blocks[constellation];
+        debug_assert!(blocks.len() > 1, "Only non-trivial constellations can be split");
+
+        // The smallest of two blocks contains at most half of the states.
+        let index = if self.partition.block(blocks[0]).len() <= self.partition.block(blocks[1]).len() {
+            0
+        } else {
+            1
+        };
+
+        let splitter_block = self.constellation_blocks[constellation].swap_remove(index);
+        let new_constellation = self.constellation_blocks.len();
+        self.constellation_blocks.push(vec![splitter_block]);
+        self.block_constellation[splitter_block] = new_constellation;
+
+        if self.constellation_blocks[constellation].len() > 1 {
+            self.non_trivial.push(constellation);
+        }
+
+        trace!("Splitting block {splitter_block} from constellation {constellation}");
+        self.round = self.next_stamp();
+        let round = self.round;
+
+        // Move the transitions into the splitter block to new out-slices, which
+        // are shared by the transitions of a state with the same label.
+        let mut splitter_out_slices = Vec::new();
+        let states: Vec<StateIndex> = self.partition.iter_block(splitter_block).collect();
+        for state_index in states {
+            for index in self.incoming_offsets[state_index]..self.incoming_offsets[state_index.value() + 1] {
+                let transition = self.incoming_ids[index];
+                let out_slice = self.transition_out_slice[transition];
+
+                if self.out_slices[out_slice].partner_stamp != round {
+                    let from = self.out_slices[out_slice].state;
+                    let label = self.out_slices[out_slice].label;
+                    let slice = self.out_slices[out_slice].block_slice;
+
+                    if self.slices[slice].partner_stamp != round {
+                        let new_slice = self.add_block_slice(self.slices[slice].block, label, new_constellation);
+                        self.slices[slice].partner = new_slice;
+                        self.slices[slice].partner_stamp = round;
+                        self.slices[new_slice].co_slice = Some(slice);
+                        self.slices[new_slice].co_stamp = round;
+                    }
+
+                    let new_out_slice = self.add_out_slice(from, label, new_constellation, self.slices[slice].partner);
+                    self.out_slices[out_slice].partner = new_out_slice;
+                    self.out_slices[out_slice].partner_stamp = round;
+                    self.out_slices[new_out_slice].partner = out_slice;
+                    self.out_slices[new_out_slice].partner_stamp = round;
+                    splitter_out_slices.push(new_out_slice);
+                }
+
+                let new_out_slice = self.out_slices[out_slice].partner;
+                self.out_slices[out_slice].count -= 1;
+                self.out_slices[new_out_slice].count += 1;
+                self.transition_out_slice[transition] = new_out_slice;
+
+                if self.out_slices[out_slice].count == 0 {
+                    self.remove_out_slice(out_slice);
+                }
+            }
+        }
+
+        // The tau transitions of the splitter block into its old constellation are no longer ignored.
+        let tau_slice = self.block_slices[splitter_block].iter().copied().find(|slice| {
+            self.lts.is_hidden_label(self.slices[*slice].label) && self.slices[*slice].constellation == constellation
+        });
+        if let Some(slice) = tau_slice {
+            let label = self.slices[slice].label;
+            let bottom: Vec<StateIndex> = self.bottom[splitter_block]
+                .iter()
+                .copied()
+                .filter(|state_index| !self.has_transition(*state_index, label, constellation))
+                .collect();
+
+            if !bottom.is_empty() {
+                self.split(splitter_block, Splitter::Slice(slice), &bottom);
+            }
+        }
+
+        // Split the blocks with transitions into the new constellation per label.
+        splitter_out_slices.sort_unstable_by_key(|out_slice| self.out_slices[*out_slice].label);
+
+        let mut start = 0;
+        while start < splitter_out_slices.len() {
+            let label = self.out_slices[splitter_out_slices[start]].label;
+            let end = start
+                + splitter_out_slices[start..]
+                    .iter()
+                    .take_while(|out_slice| self.out_slices[**out_slice].label == label)
+                    .count();
+
+            // The blocks change while splitting, so these are determined per label.
+            let mut sources: Vec<(BlockIndex, StateIndex, OutSliceIndex)> = splitter_out_slices[start..end]
+                .iter()
+                .map(|out_slice| {
+                    let state_index = self.out_slices[*out_slice].state;
+                    (self.partition.block_number(state_index), state_index, *out_slice)
+                })
+                .collect();
+            sources.sort_unstable();
+
+            let stamp = self.next_stamp();
+            for (_, state_index, _) in &sources {
+                self.marked[*state_index] = stamp;
+            }
+
+            for group in sources.chunk_by(|left, right| left.0 == right.0) {
+                let block_index = group[0].0;
+                if self.is_ignored(block_index, label, new_constellation) {
+                    continue;
+                }
+
+                let states: Vec<StateIndex> = group.iter().map(|(_, state_index, _)| *state_index).collect();
+                let reaching = match self.split(block_index, Splitter::Marked { states: &states, stamp }, &[]) {
+                    Some((reaching, _)) => reaching,
+                    None => block_index,
+                };
+
+                // The bottom states of the reaching part that only have
+                // transitions into the new constellation cannot reach the
+                // remainder of the old constellation.
+                if self.is_ignored(reaching, label, constellation) {
+                    continue;
+                }
+
+                let mut co_slice = None;
+                let mut bottom = Vec::new();
+                for (_, state_index, out_slice) in group {
+                    if self.partition.block_number(*state_index) == reaching {
+                        if co_slice.is_none() {
+                            co_slice = self.co_slice(self.out_slices[*out_slice].block_slice);
+                        }
+
+                        if self.bottom_position[*state_index] != usize::MAX
+                            && self.out_slices[self.out_slices[*out_slice].partner].count == 0
+                        {
+                            bottom.push(*state_index);
+                        }
+                    }
+                }
+
+                if let Some(slice) = co_slice {
+                    if !bottom.is_empty() {
+                        self.split(reaching, Splitter::Slice(slice), &bottom);
+                    }
+                }
+            }
+
+            start = end;
+        }
+    }
+
+    /// Stabilises all blocks with new bottom states under all constellations.
+    fn stabilise(&mut self) {
+        while let Some(block_index) = self.new_bottom_blocks.pop() {
+            self.stabilise_new_bottom(block_index);
+        }
+    }
+
+    /// Splits the block under a slice that does not contain all new bottom
+    /// states, or otherwise marks the new bottom states as stable.
+    fn stabilise_new_bottom(&mut self, block_index: BlockIndex) {
+        let new_bottom: Vec<StateIndex> = take(&mut self.new_bottom[block_index])
+            .into_iter()
+            .filter(|state_index| {
+                self.is_new_bottom[*state_index] && self.partition.block_number(*state_index) == block_index
+            })
+            .collect();
+
+        if new_bottom.is_empty() {
+            return;
+        }
+
+        // Count the new bottom states in every slice of the block.
+        let stamp = self.next_stamp();
+        for state_index in &new_bottom {
+            for out_slice in &self.state_out_slices[*state_index] {
+                let slice = &mut self.slices[self.out_slices[*out_slice].block_slice];
+                if slice.hits_stamp != stamp {
+                    slice.hits_stamp = stamp;
+                    slice.hits = 0;
+                }
+
+                slice.hits += 1;
+            }
+        }
+
+        let splitter = self.block_slices[block_index].iter().copied().find(|slice| {
+            let slice = &self.slices[*slice];
+            !self.is_ignored(block_index, slice.label, slice.constellation)
+                && (slice.hits_stamp != stamp || slice.hits < new_bottom.len())
+        });
+
+        if let Some(slice) = splitter {
+            let label = self.slices[slice].label;
+            let constellation = self.slices[slice].constellation;
+            let bottom: Vec<StateIndex> = new_bottom
+                .iter()
+                .copied()
+                .filter(|state_index| !self.has_transition(*state_index, label, constellation))
+                .collect();
+
+            // Both parts are stabilised again afterwards.
+            self.new_bottom[block_index] = new_bottom;
+            self.new_bottom_blocks.push(block_index);
+
+            let result = self.split(block_index, Splitter::Slice(slice), &bottom);
+            debug_assert!(result.is_some(), "The block should be unstable under the slice");
+        } else {
+            for state_index in new_bottom {
+                self.is_new_bottom[state_index] = false;
+            }
+        }
+    }
+
+    /// Splits the block into the states that can inertly reach a transition in
+    /// the splitter and the other states. For [Splitter::Slice] the `bottom`
+    /// states must be exactly the bottom states without such a transition.
+    ///
+    /// Both parts are explored in lockstep, one transition at a time, and only
+    /// the part that is completed first is split off. The other exploration is
+    /// abandoned, and a part is no longer explored when it contains more than
+    /// half of the states. Returns the reaching and the other block when the
+    /// block was split.
+    fn split(
+        &mut self,
+        block_index: BlockIndex,
+        splitter: Splitter,
+        bottom: &[StateIndex],
+    ) -> Option<(BlockIndex, BlockIndex)> {
+        let incoming = self.incoming;
+        let stamp = self.next_stamp();
+        let half = self.partition.block(block_index).len() / 2;
+
+        let mut reaching = Vec::new();
+        let mut reaching_index = 0;
+        let mut reaching_seed = 0;
+        let mut reaching_incoming = None;
+
+        let mut other = Vec::new();
+        let mut other_index = 0;
+        let mut other_seed = 0;
+        let mut other_incoming = None;
+
+        let (states, is_reaching) = loop {
+            if reaching.len() <= half {
+                // The states with a transition in the splitter, and the states that can inertly reach these.
+                if let Some(transition) = next_transition(reaching_incoming.as_mut()) {
+                    let from = transition.to;
+                    if self.partition.block_number(from) == block_index && self.reaching[from] != stamp {
+                        self.reaching[from] = stamp;
+                        reaching.push(from);
+                    }
+                } else if reaching_index < reaching.len() {
+                    reaching_incoming = Some(incoming.incoming_silent_transitions(reaching[reaching_index]));
+                    reaching_index += 1;
+                } else if let Some(state_index) = self.splitter_state(&splitter, reaching_seed) {
+                    reaching_seed += 1;
+                    if self.reaching[state_index] != stamp {
+                        self.reaching[state_index] = stamp;
+                        reaching.push(state_index);
+                    }
+                } else {
+                    break (reaching, true);
+                }
+            }
+
+            if other.len() <= half {
+                // The bottom states without a transition in the splitter, and
+                // the states without such a transition whose inert transitions
+                // all lead to these.
+                if let Some(transition) = next_transition(other_incoming.as_mut()) {
+                    let from = transition.to;
+                    if self.partition.block_number(from) == block_index && self.other[from] != stamp {
+                        if self.counter_stamp[from] != stamp {
+                            self.counter_stamp[from] = stamp;
+                            self.counter[from] = self.inert_count[from];
+                        }
+
+                        self.counter[from] -= 1;
+                        if self.counter[from] == 0 && !self.has_splitter_transition(&splitter, from) {
+                            self.other[from] = stamp;
+                            other.push(from);
+                        }
+                    }
+                } else if other_index < other.len() {
+                    other_incoming = Some(incoming.incoming_silent_transitions(other[other_index]));
+                    other_index += 1;
+                } else if let Some(state_index) = self.bottom_state(&splitter, block_index, bottom, &mut other_seed) {
+                    if self.other[state_index] != stamp {
+                        self.other[state_index] = stamp;
+                        other.push(state_index);
+                    }
+                } else {
+                    break (other, false);
+                }
+            }
+        };
+
+        if states.is_empty() || states.len() == self.partition.block(block_index).len() {
+            return None;
+        }
+
+        for state_index in &states {
+            self.partition.mark_element(*state_index);
+        }
+
+        let new_block_index = BlockIndex::new(self.partition.num_of_blocks());
+        let _ = self
+            .partition
+            .partition_marked_with(block_index, &mut self.builder, |_, _| BlockIndex::new(0));
+        debug_assert_eq!(
+            self.partition.num_of_blocks(),
+            new_block_index.value() + 1,
+            "The block should be split in two"
+        );
+
+        self.update_split(block_index, new_block_index);
+
+        let found_block = self.partition.block_number(states[0]);
+        let remaining_block = if found_block == block_index {
+            new_block_index
+        } else {
+            block_index
+        };
+
+        if is_reaching {
+            Some((found_block, remaining_block))
+        } else {
+            Some((remaining_block, found_block))
+        }
+    }
+
+    /// Updates the administration after the states of the new block have been split off from the given block.
+    fn update_split(&mut self, block_index: BlockIndex, new_block_index: BlockIndex) {
+        trace!("Split block {block_index} into {new_block_index}");
+
+        let constellation = self.block_constellation[block_index];
+        self.block_constellation.push(constellation);
+        self.constellation_blocks[constellation].push(new_block_index);
+        if self.constellation_blocks[constellation].len() == 2 {
+            self.non_trivial.push(constellation);
+        }
+
+        self.block_slices.push(Vec::new());
+        self.bottom.push(Vec::new());
+        self.new_bottom.push(Vec::new());
+
+        // Move the bottom states and the out-slices to the new block.
+        let stamp = self.next_stamp();
+        let mut new_slices = Vec::new();
+        let states: Vec<StateIndex> = self.partition.iter_block(new_block_index).collect();
+        for state_index in &states {
+            if self.bottom_position[*state_index] != usize::MAX {
+                self.remove_bottom(*state_index, block_index);
+                self.add_bottom(*state_index, new_block_index);
+            }
+
+            if self.is_new_bottom[*state_index] {
+                self.new_bottom[new_block_index].push(*state_index);
+            }
+
+            for index in 0..self.state_out_slices[*state_index].len() {
+                let out_slice = self.state_out_slices[*state_index][index];
+                let slice = self.out_slices[out_slice].block_slice;
+                if self.slices[slice].partner_stamp != stamp {
+                    let new_slice = self.add_block_slice(
+                        new_block_index,
+                        self.slices[slice].label,
+                        self.slices[slice].constellation,
+                    );
+                    self.slices[slice].partner = new_slice;
+                    self.slices[slice].partner_stamp = stamp;
+                    new_slices.push((slice, new_slice));
+                }
+
+                self.move_out_slice(out_slice, self.slices[slice].partner);
+            }
+        }
+
+        // The slices into a new constellation of the new block must refer to their counterparts of the new block.
+        for (slice, new_slice) in new_slices {
+            if self.slices[slice].co_stamp == self.round {
+                if let Some(co_slice) = self.slices[slice].co_slice {
+                    if self.slices[co_slice].partner_stamp == stamp {
+                        self.slices[new_slice].co_slice = Some(self.slices[co_slice].partner);
+                        self.slices[new_slice].co_stamp = self.round;
+                    }
+                }
+            }
+        }
+
+        // The tau transitions between both blocks are no longer inert.
+        let lts = self.lts;
+        let incoming = self.incoming;
+        for state_index in states {
+            for transition in lts.outgoing_transitions(state_index) {
+                if lts.is_hidden_label(transition.label) && self.partition.block_number(transition.to) == block_index {
+                    self.inert_count[state_index] -= 1;
+                    if self.inert_count[state_index] == 0 {
+                        self.make_bottom(state_index);
+                    }
+                }
+            }
+
+            for transition in incoming.incoming_silent_transitions(state_index) {
+                let from = transition.to;
+                if self.partition.block_number(from) == block_index {
+                    self.inert_count[from] -= 1;
+                    if self.inert_count[from] == 0 {
+                        self.make_bottom(from);
+                    }
+                }
+            }
+        }
+
+        if !self.new_bottom[new_block_index].is_empty() {
+            self.new_bottom_blocks.push(new_block_index);
+        }
+    }
+
+    /// Returns the state with the given index in the splitter.
+    fn splitter_state(&self, splitter: &Splitter, index: usize) -> Option<StateIndex> {
+        match splitter {
+            Splitter::Marked { states, .. } => states.get(index).copied(),
+            Splitter::Slice(slice) => self.slices[*slice]
+                .out_slices
+                .get(index)
+                .map(|out_slice| self.out_slices[*out_slice].state),
+        }
+    }
+
+    /// Returns the next bottom state of the block without a transition in the splitter.
+    fn bottom_state(
+        &self,
+        splitter: &Splitter,
+        block_index: BlockIndex,
+        bottom: &[StateIndex],
+        index: &mut usize,
+    ) -> Option<StateIndex> {
+        match splitter {
+            Splitter::Marked { stamp, .. } => {
+                // Skipping the marked states is accounted to the marked states.
+                while let Some(state_index) = self.bottom[block_index].get(*index).copied() {
+                    *index += 1;
+                    if self.marked[state_index] != *stamp {
+                        return Some(state_index);
+                    }
+                }
+
+                None
+            }
+            Splitter::Slice(_) => {
+                let result = bottom.get(*index).copied();
+                *index += 1;
+                result
+            }
+        }
+    }
+
+    /// Returns true iff the state has a transition in the splitter.
+    fn has_splitter_transition(&self, splitter: &Splitter, state_index: StateIndex) -> bool {
+        match splitter {
+            Splitter::Marked { stamp, .. } => self.marked[state_index] == *stamp,
+            Splitter::Slice(slice) => self.has_transition(
+                state_index,
+                self.slices[*slice].label,
+                self.slices[*slice].constellation,
+            ),
+        }
+    }
+
+    /// Returns true iff the state has a `label`-transition into the constellation.
+    ///
+    /// This scans the out-slices of the state, which are at most one per label
+    /// and constellation that the state has transitions into.
+    fn has_transition(&self, state_index: StateIndex, label: LabelIndex, constellation: ConstellationIndex) -> bool {
+        self.state_out_slices[state_index].iter().any(|out_slice| {
+            let out_slice = &self.out_slices[*out_slice];
+            out_slice.label == label && out_slice.constellation == constellation
+        })
+    }
+
+    /// Returns the slice into the remainder of the old constellation for a slice into the new constellation.
+    fn co_slice(&self, slice: BlockSliceIndex) -> Option<BlockSliceIndex> {
+        if self.slices[slice].co_stamp != self.round {
+            return None;
+        }
+
+        self.slices[slice]
+            .co_slice
+            .filter(|co_slice| !self.slices[*co_slice].out_slices.is_empty())
+    }
+
+    /// Returns true iff the transitions with the given label into the constellation are inert for the block.
+    fn is_ignored(&self, block_index: BlockIndex, label: LabelIndex, constellation: ConstellationIndex) -> bool {
+        self.lts.is_hidden_label(label) && self.block_constellation[block_index] == constellation
+    }
+
+    /// Returns a fresh stamp.
+    fn next_stamp(&mut self) -> usize {
+        self.stamp += 1;
+        self.stamp
+    }
+
+    /// Adds an empty out-slice to the given slice.
+    fn add_out_slice(
+        &mut self,
+        state_index: StateIndex,
+        label: LabelIndex,
+        constellation: ConstellationIndex,
+        slice: BlockSliceIndex,
+    ) -> OutSliceIndex {
+        let out_slice = self.out_slices.len();
+        self.out_slices.push(OutSlice {
+            state: state_index,
+            label,
+            constellation,
+            count: 0,
+            block_slice: slice,
+            block_slice_position: self.slices[slice].out_slices.len(),
+            state_position: self.state_out_slices[state_index].len(),
+            partner: 0,
+            partner_stamp: 0,
+        });
+
+        self.slices[slice].out_slices.push(out_slice);
+        self.state_out_slices[state_index].push(out_slice);
+        out_slice
+    }
+
+    /// Adds an empty slice to the given block.
+    fn add_block_slice(
+        &mut self,
+        block_index: BlockIndex,
+        label: LabelIndex,
+        constellation: ConstellationIndex,
+    ) -> BlockSliceIndex {
+        let slice = self.slices.len();
+        self.slices.push(BlockSlice {
+            block: block_index,
+            label,
+            constellation,
+            out_slices: Vec::new(),
+            position: self.block_slices[block_index].len(),
+            partner: 0,
+            partner_stamp: 0,
+            co_slice: None,
+            co_stamp: 0,
+            hits: 0,
+            hits_stamp: 0,
+        });
+
+        self.block_slices[block_index].push(slice);
+        slice
+    }
+
+    /// Moves the out-slice to the given slice.
+    fn move_out_slice(&mut self, out_slice: OutSliceIndex, slice: BlockSliceIndex) {
+        self.remove_from_block_slice(out_slice);
+
+        self.out_slices[out_slice].block_slice = slice;
+        self.out_slices[out_slice].block_slice_position = self.slices[slice].out_slices.len();
+        self.slices[slice].out_slices.push(out_slice);
+    }
+
+    /// Removes the empty out-slice from its slice and its state.
+    fn remove_out_slice(&mut self, out_slice: OutSliceIndex) {
+        debug_assert_eq!(
+            self.out_slices[out_slice].count, 0,
+            "Only empty out-slices can be removed"
+        );
+        self.remove_from_block_slice(out_slice);
+
+        let state_index = self.out_slices[out_slice].state;
+        let position = self.out_slices[out_slice].state_position;
+        self.state_out_slices[state_index].swap_remove(position);
+        if let Some(moved) = self.state_out_slices[state_index].get(position).copied() {
+            self.out_slices[moved].state_position = position;
+        }
+    }
+
+    /// Removes the out-slice from its slice, and removes the slice from its block when it becomes empty.
+    fn remove_from_block_slice(&mut self, out_slice: OutSliceIndex) {
+        let slice = self.out_slices[out_slice].block_slice;
+        let position = self.out_slices[out_slice].block_slice_position;
+        self.slices[slice].out_slices.swap_remove(position);
+        if let Some(moved) = self.slices[slice].out_slices.get(position).copied() {
+            self.out_slices[moved].block_slice_position = position;
+        }
+
+        if self.slices[slice].out_slices.is_empty() {
+            let block_index = self.slices[slice].block;
+            let position = self.slices[slice].position;
+            self.block_slices[block_index].swap_remove(position);
+            if let Some(moved) = self.block_slices[block_index].get(position).copied() {
+                self.slices[moved].position = position;
+            }
+        }
+    }
+
+    /// Adds the state to the bottom states of the block.
+    fn add_bottom(&mut self, state_index: StateIndex, block_index: BlockIndex) {
+        self.bottom_position[state_index] = self.bottom[block_index].len();
+        self.bottom[block_index].push(state_index);
+    }
+
+    /// Removes the state from the bottom states of the block.
+    fn remove_bottom(&mut self, state_index: StateIndex, block_index: BlockIndex) {
+        let position = self.bottom_position[state_index];
+        self.bottom[block_index].swap_remove(position);
+        if let Some(moved) = self.bottom[block_index].get(position).copied() {
+            self.bottom_position[moved] = position;
+        }
+
+        self.bottom_position[state_index] = usize::MAX;
+    }
+
+    /// Marks the state, which has no inert transitions left, as a new bottom state of its block.
+    fn make_bottom(&mut self, state_index: StateIndex) {
+        let block_index = self.partition.block_number(state_index);
+        self.add_bottom(state_index, block_index);
+        self.is_new_bottom[state_index] = true;
+
+        if self.new_bottom[block_index].is_empty() {
+            self.new_bottom_blocks.push(block_index);
+        }
+
+        self.new_bottom[block_index].push(state_index);
+    }
+}
+
+/// Returns the next transition of the iterator, if there is one.
+fn next_transition(transitions: Option<&mut impl Iterator<Item = Transition>>) -> Option<Transition> {
+    transitions.and_then(Iterator::next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_io::DumpFiles;
+    use merc_lts::random_lts;
+    use merc_lts::write_aut;
+    use merc_utilities::random_test;
+
+    use crate::branching_bisim_sigref;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_branching_bisim_gj() {
+        random_test(100, |rng| {
+            let mut files = DumpFiles::new("test_random_branching_bisim_gj");
+
+            let lts = random_lts(rng, 10, 3, 3);
+            files.dump("input.aut", |w| write_aut(w, &lts)).unwrap();
+            let mut timing = Timing::new();
+
+            let (preprocessed_lts, result_partition, _) = branching_bisim_gj(lts.clone(), &mut timing);
+            let (_, expected_partition, _) = branching_bisim_sigref(lts, &mut timing);
+
+            // Both algorithms use the same preprocessing, so the partitions can be compared directly.
+            assert_eq!(result_partition.num_of_blocks(), expected_partition.num_of_blocks());
+            for state_index in preprocessed_lts.iter_states() {
+                for other_state_index in preprocessed_lts.iter_states() {
+                    assert_eq!(
+                        result_partition.block_number(state_index) == result_partition.block_number(other_state_index),
+                        expected_partition.block_number(state_index)
+                            == expected_partition.block_number(other_state_index),
+                        "States {state_index} and {other_state_index} are related differently"
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,210 @@
+#![forbid(unsafe_code)]
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use itertools::Itertools;
+
+use merc_lts::ActionFilter;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::LtsBuilder;
+use merc_lts::StateIndex;
+use merc_lts::TransitionLabel;
+use merc_lts::parse_multi_action;
+
+use crate::Partition;
+use crate::has_tau_loop;
+use crate::tau_scc_decomposition;
+
+/// The result of a query on an LTS, consisting of a state that satisfies the
+/// query and a shortest trace from the initial state to that state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness<Label> {
+    trace: Vec<Label>,
+    state: StateIndex,
+}
+
+impl<Label: TransitionLabel> Witness<Label> {
+    /// Returns the trace from the initial state to the witness, including the internal actions.
+    pub fn trace(&self) -> &[Label] {
+        &self.trace
+    }
+
+    /// Returns the state at the end of the trace.
+    pub fn state(&self) -> StateIndex {
+        self.state
+    }
+
+    /// Returns the witness as a linear labelled transition system consisting of the trace.
+    pub fn to_lts(&self) -> LabelledTransitionSystem<Label> {
+        let mut builder = LtsBuilder::new(self.trace.clone(), Vec::new());
+        for (index, label) in self.trace.iter().enumerate() {
+            builder.add_transition(StateIndex::new(index), label, StateIndex::new(index + 1));
+        }
+
+        builder.require_num_of_states(self.trace.len() + 1);
+        builder.finish(StateIndex::new(0))
+    }
+}
+
+impl<Label: TransitionLabel> fmt::Display for Witness<Label> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "state {}, trace: [{}]", self.state, self.trace.iter().format(", "))
+    }
+}
+
+/// Returns a reachable state without outgoing transitions, if it exists.
+pub fn find_deadlock<L: LTS>(lts: &L) -> Option<Witness<L::Label>> {
+    shortest_trace(lts, |state_index| {
+        lts.outgoing_transitions(state_index).next().is_none()
+    })
+}
+
+/// Returns a reachable state that lies on a cycle of internal transitions, if it exists.
+pub fn find_livelock<L: LTS>(lts: &L) -> Option<Witness<L::Label>> {
+    if !has_tau_loop(lts) {
+        return None;
+    }
+
+    // A state lies on a cycle when its strongly connected component has another state, or it has a self loop.
+    let partition = tau_scc_decomposition(lts);
+    let mut block_sizes = vec![0usize; partition.num_of_blocks()];
+    for state_index in lts.iter_states() {
+        block_sizes[partition.block_number(state_index)] += 1;
+    }
+
+    shortest_trace(lts, |state_index| {
+        block_sizes[partition.block_number(state_index)] > 1
+            || lts
+                .outgoing_transitions(state_index)
+                .any(|transition| lts.is_hidden_label(transition.label) && transition.to == state_index)
+    })
+}
+
+/// Returns a shortest trace ending with a transition labelled by an action
+/// selected by the filter, if it exists. The witness is the target of that
+/// transition.
+pub fn find_action<L: LTS>(lts: &L, filter: &ActionFilter) -> Option<Witness<L::Label>> {
+    let selected: Vec<bool> = lts
+        .labels()
+        .iter()
+        .enumerate()
+        .map(|(label_index, label)| {
+            !lts.is_hidden_label(LabelIndex::new(label_index))
+                && parse_multi_action(label)
+                    .actions
+                    .iter()
+                    .any(|action| filter.matches(&action.id))
+        })
+        .collect();
+
+    // The states are explored in breadth first order, so the first state with a selected transition is the closest.
+    let mut witness = None;
+    shortest_trace(lts, |state_index| {
+        witness = lts
+            .outgoing_transitions(state_index)
+            .find(|transition| selected[transition.label]);
+        witness.is_some()
+    })
+    .map(|mut result| {
+        let transition = witness.expect("The transition is found by the search");
+        result.trace.push(lts.labels()[transition.label].clone());
+        result.state = transition.to;
+        result
+    })
+}
+
+/// Performs a breadth first search from the initial state, and returns the
+/// first state that satisfies the predicate together with a shortest trace.
+fn shortest_trace<L: LTS>(lts: &L, mut predicate: impl FnMut(StateIndex) -> bool) -> Option<Witness<L::Label>> {
+    // The transition through which every state was discovered first.
+    let mut predecessor: Vec<Option<(StateIndex, LabelIndex)>> = vec![None; lts.num_of_states()];
+    let mut discovered = vec![false; lts.num_of_states()];
+
+    let mut queue = VecDeque::from([lts.initial_state_index()]);
+    discovered[lts.initial_state_index()] = true;
+
+    while let Some(state_index) = queue.pop_front() {
+        if predicate(state_index) {
+            let mut trace = Vec::new();
+            let mut current = state_index;
+            while let Some((previous, label_index)) = predecessor[current] {
+                trace.push(lts.labels()[label_index].clone());
+                current = previous;
+            }
+
+            trace.reverse();
+            return Some(Witness {
+                trace,
+                state: state_index,
+            });
+        }
+
+        for transition in lts.outgoing_transitions(state_index) {
+            if !discovered[transition.to] {
+                discovered[transition.to] = true;
+                predecessor[transition.to] = Some((state_index, transition.label));
+                queue.push_back(transition.to);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_lts::read_aut;
+
+    #[test]
+    fn test_find_deadlock() {
+        let lts = read_aut(
+            b"des (0,4,4)\n(0,a,1)\n(1,b,0)\n(0,c,2)\n(2,i,3)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let witness = find_deadlock(&lts).unwrap();
+        assert_eq!(witness.state(), StateIndex::new(3));
+        assert_eq!(witness.trace(), ["c", "i"]);
+        assert_eq!(witness.to_lts().num_of_transitions(), 2);
+    }
+
+    #[test]
+    fn test_find_livelock() {
+        let lts = read_aut(
+            b"des (0,4,3)\n(0,a,1)\n(1,b,2)\n(2,i,1)\n(1,i,2)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+        let witness = find_livelock(&lts).unwrap();
+        assert_eq!(witness.state(), StateIndex::new(1));
+        assert_eq!(witness.trace(), ["a"]);
+
+        // A cycle containing a visible action is not a livelock.
+        let lts = read_aut(b"des (0,2,2)\n(0,a,1)\n(1,i,0)\n" as &[u8], Vec::new()).unwrap();
+        assert!(find_livelock(&lts).is_none());
+        assert!(find_deadlock(&lts).is_none());
+    }
+
+    #[test]
+    fn test_find_action() {
+        let lts = read_aut(
+            b"des (0,4,4)\n(0,a,1)\n(1,\"b(1)\",2)\n(0,c,3)\n(3,\"b(2)\",0)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let witness = find_action(&lts, &ActionFilter::Names(vec!["b".to_string()])).unwrap();
+        assert_eq!(witness.trace(), ["a", "b(1)"]);
+        assert_eq!(witness.state(), StateIndex::new(2));
+
+        assert!(find_action(&lts, &ActionFilter::Names(vec!["d".to_string()])).is_none());
+    }
+}
@@ -1,19 +1,35 @@
 #![forbid(unsafe_code)]
 
 use merc_lts::LTS;
+use merc_lts::StateIndex;
 use merc_utilities::Timing;
 
 use crate::Equivalence;
 use crate::Partition;
+use crate::branching_bisim_gj;
 use crate::branching_bisim_sigref;
 use crate::branching_bisim_sigref_naive;
+use crate::dpbranching_bisim_sigref_naive;
+use crate::ready_simulation_preorder;
+use crate::reduce_lts;
+use crate::simulation_preorder;
+use crate::strong_bisim_pt;
 use crate::strong_bisim_sigref;
 use crate::strong_bisim_sigref_naive;
 use crate::weak_bisim_sigref_naive;
 use crate::weak_bisimulation;
 
 // Compare two LTSs for equivalence using the given algorithm.
+//
+// For the trace equivalences both LTSs are first reduced separately, and for
+// [Equivalence::TauStar] the tau-star reductions are compared modulo strong bisimulation.
 pub fn compare_lts<L: LTS>(equivalence: Equivalence, left: L, right: L, timing: &mut Timing) -> bool {
+    if let Equivalence::Trace | Equivalence::WeakTrace | Equivalence::TauStar = equivalence {
+        let left = reduce_lts(left, equivalence, timing);
+        let right = reduce_lts(right, equivalence, timing);
+        return compare_lts(Equivalence::StrongBisim, left, right, timing);
+    }
+
     let mut time_merge = timing.start("merge lts");
     let (merged, rhs_initial) = left.merge_disjoint(&right);
     drop(right); // No longer needed.
@@ -22,11 +38,13 @@ pub fn compare_lts<L: LTS>(equivalence: Equivalence, left: L, right: L, timing:
     // Reduce the merged LTS modulo the given equivalence and return the partition
     match equivalence {
         Equivalence::WeakBisim => {
-            let (lts, partition) = weak_bisimulation(merged, timing);
+            let (lts, partition, mapping) = weak_bisimulation(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
             partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
         }
         Equivalence::WeakBisimSigref => {
-            let (lts, partition) = weak_bisim_sigref_naive(merged, timing);
+            let (lts, partition, mapping) = weak_bisim_sigref_naive(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
             partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
         }
         Equivalence::StrongBisim => {
@@ -37,13 +55,160 @@ pub fn compare_lts<L: LTS>(equivalence: Equivalence, left: L, right: L, timing:
             let (lts, partition) = strong_bisim_sigref_naive(merged, timing);
             partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
         }
+        Equivalence::StrongBisimPT => {
+            let (lts, partition) = strong_bisim_pt(merged, timing);
+            partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
+        }
         Equivalence::BranchingBisim => {
-            let (lts, partition) = branching_bisim_sigref(merged, timing);
+            let (lts, partition, mapping) = branching_bisim_sigref(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
             partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
         }
         Equivalence::BranchingBisimNaive => {
-            let (lts, partition) = branching_bisim_sigref_naive(merged, timing);
+            let (lts, partition, mapping) = branching_bisim_sigref_naive(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
             partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
         }
+        Equivalence::BranchingBisimGJ => {
+            let (lts, partition, mapping) = branching_bisim_gj(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
+            partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
+        }
+        Equivalence::DpBranchingBisim => {
+            let (lts, partition, mapping) = dpbranching_bisim_sigref_naive(merged, timing);
+            let rhs_initial = StateIndex::new(*mapping.block_number(rhs_initial));
+            partition.block_number(lts.initial_state_index()) == partition.block_number(rhs_initial)
+        }
+        Equivalence::Simulation => {
+            let relation = simulation_preorder(&merged, timing);
+            relation.related(merged.initial_state_index(), rhs_initial)
+                && relation.related(rhs_initial, merged.initial_state_index())
+        }
+        Equivalence::ReadySimulation => {
+            let relation = ready_simulation_preorder(&merged, timing);
+            relation.related(merged.initial_state_index(), rhs_initial)
+                && relation.related(rhs_initial, merged.initial_state_index())
+        }
+        Equivalence::Trace | Equivalence::WeakTrace | Equivalence::TauStar => {
+            unreachable!("The trace equivalences are handled above")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use merc_lts::random_lts;
+    use merc_lts::read_aut;
+    use merc_utilities::random_test;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_compare_reflexive() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+            let mut timing = Timing::new();
+
+            for equivalence in [
+                Equivalence::WeakBisim,
+                Equivalence::WeakBisimSigref,
+                Equivalence::StrongBisim,
+                Equivalence::StrongBisimNaive,
+                Equivalence::StrongBisimPT,
+                Equivalence::BranchingBisim,
+                Equivalence::BranchingBisimNaive,
+                Equivalence::BranchingBisimGJ,
+                Equivalence::DpBranchingBisim,
+                Equivalence::Trace,
+                Equivalence::WeakTrace,
+                Equivalence::TauStar,
+                Equivalence::Simulation,
+                Equivalence::ReadySimulation,
+            ] {
+                assert!(
+                    compare_lts(equivalence, lts.clone(), lts.clone(), &mut timing),
+                    "An LTS should be {equivalence:?} equivalent to itself"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_compare_divergence() {
+        // A state with an internal loop is branching bisimilar, but not divergence-preserving branching bisimilar, to a deadlock.
+        let divergent = read_aut(b"des (0,2,2)\n(0,a,1)\n(1,i,1)\n" as &[u8], Vec::new()).unwrap();
+        let deadlock = read_aut(b"des (0,1,2)\n(0,a,1)\n" as &[u8], Vec::new()).unwrap();
+        let mut timing = Timing::new();
+
+        assert!(compare_lts(
+            Equivalence::BranchingBisim,
+            divergent.clone(),
+            deadlock.clone(),
+            &mut timing
+        ));
+        assert!(!compare_lts(
+            Equivalence::DpBranchingBisim,
+            divergent,
+            deadlock,
+            &mut timing
+        ));
+    }
+
+    #[test]
+    fn test_compare_weak_trace() {
+        // The choice between a and b is made internally on the left, but not on the right.
+        let left = read_aut(
+            b"des (0,4,5)\n(0,i,1)\n(0,i,2)\n(1,a,3)\n(2,b,4)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+        let right = read_aut(b"des (0,2,3)\n(0,a,1)\n(0,b,2)\n" as &[u8], Vec::new()).unwrap();
+        let mut timing = Timing::new();
+
+        assert!(compare_lts(
+            Equivalence::WeakTrace,
+            left.clone(),
+            right.clone(),
+            &mut timing
+        ));
+        assert!(compare_lts(
+            Equivalence::TauStar,
+            left.clone(),
+            right.clone(),
+            &mut timing
+        ));
+        assert!(!compare_lts(
+            Equivalence::Trace,
+            left.clone(),
+            right.clone(),
+            &mut timing
+        ));
+        assert!(!compare_lts(Equivalence::BranchingBisim, left, right, &mut timing));
+    }
+
+    #[test]
+    fn test_compare_simulation() {
+        let left = read_aut(
+            b"des (0,5,5)\n(0,a,1)\n(0,a,2)\n(1,b,3)\n(2,b,4)\n(2,c,4)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+        let right = read_aut(b"des (0,3,3)\n(0,a,1)\n(1,b,2)\n(1,c,2)\n" as &[u8], Vec::new()).unwrap();
+        let mut timing = Timing::new();
+
+        assert!(compare_lts(
+            Equivalence::Simulation,
+            left.clone(),
+            right.clone(),
+            &mut timing
+        ));
+        assert!(!compare_lts(
+            Equivalence::ReadySimulation,
+            left.clone(),
+            right.clone(),
+            &mut timing
+        ));
+        assert!(!compare_lts(Equivalence::StrongBisim, left, right, &mut timing));
     }
 }
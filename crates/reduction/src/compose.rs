@@ -0,0 +1,251 @@
+#![forbid(unsafe_code)]
+
+use std::cmp::Reverse;
+
+use log::info;
+use rustc_hash::FxHashSet;
+
+use merc_lts::CompositionSpecification;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::TransitionLabel;
+use merc_lts::apply_composition_operators;
+use merc_lts::hide_closed_actions;
+use merc_lts::parallel_lts;
+use merc_lts::parse_multi_action;
+use merc_utilities::MercError;
+use merc_utilities::Timing;
+
+use crate::Equivalence;
+use crate::reduce_lts;
+
+/// Computes the parallel composition of the given components according to the
+/// specification, where the components are composed from left to right.
+///
+/// # Details
+///
+/// When an equivalence is given every intermediate product, and the final
+/// result, is reduced modulo that equivalence. This is only sound when the
+/// equivalence is a congruence for the parallel composition and the operators
+/// of the specification, which is for example the case for strong and
+/// branching bisimulation.
+pub fn compose_lts<L: TransitionLabel>(
+    components: Vec<LabelledTransitionSystem<L>>,
+    specification: &CompositionSpecification,
+    equivalence: Option<Equivalence>,
+    timing: &mut Timing,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    if components.len() != specification.components.len() || components.is_empty() {
+        return Err(format!(
+            "Expected {} components, but {} were given.",
+            specification.components.len(),
+            components.len()
+        )
+        .into());
+    }
+
+    let reduce = |lts: LabelledTransitionSystem<L>, timing: &mut Timing| match equivalence {
+        Some(equivalence) => reduce_lts(lts, equivalence, timing),
+        None => lts,
+    };
+
+    let mut components = components.into_iter();
+    let mut result = components.next().expect("There is at least one component");
+    for component in components {
+        let mut time_compose = timing.start("compose");
+        let product = parallel_lts(&result, &component, specification)?;
+        time_compose.finish();
+
+        info!(
+            "Intermediate product has {} states and {} transitions",
+            product.num_of_states(),
+            product.num_of_transitions()
+        );
+        result = reduce(product, timing);
+    }
+
+    let mut time_operators = timing.start("apply operators");
+    let result = apply_composition_operators(result, specification)?;
+    time_operators.finish();
+
+    Ok(reduce(result, timing))
+}
+
+/// Computes the parallel composition of the given components according to the
+/// specification using compositional minimisation, also known as smart
+/// reduction, where the result is reduced modulo the given equivalence.
+///
+/// # Details
+///
+/// Every step composes a pair of components that can synchronise, preferring
+/// the pair with the smallest estimated product and then the one with the
+/// most synchronisations. Afterwards the actions that can no longer synchronise with the remaining
+/// components are hidden, and the product is reduced. The sizes of the
+/// products are recorded as statistics in the timing. As for [compose_lts]
+/// this requires the equivalence to be a congruence.
+pub fn smart_reduction<L: TransitionLabel>(
+    components: Vec<LabelledTransitionSystem<L>>,
+    specification: &CompositionSpecification,
+    equivalence: Equivalence,
+    timing: &mut Timing,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    if components.len() != specification.components.len() || components.is_empty() {
+        return Err(format!(
+            "Expected {} components, but {} were given.",
+            specification.components.len(),
+            components.len()
+        )
+        .into());
+    }
+
+    let mut names = specification.components.clone();
+    let mut components = components;
+
+    // Every component is taken out in turn to hide its local actions, which keeps the original order.
+    for _ in 0..components.len() {
+        let component = components.remove(0);
+        let component = hide_closed_actions(component, &components, specification)?;
+        components.push(reduce_lts(component, equivalence, timing));
+    }
+
+    let synchronisations = specification.synchronisations();
+    let mut step = 1;
+    while components.len() > 1 {
+        let alphabets: Vec<FxHashSet<String>> = components.iter().map(alphabet).collect();
+
+        let (left, right) = (0..components.len())
+            .flat_map(|left| (left + 1..components.len()).map(move |right| (left, right)))
+            .min_by_key(|(left, right)| {
+                let shared = synchronisations
+                    .iter()
+                    .filter(|names| {
+                        names.iter().any(|name| alphabets[*left].contains(name))
+                            && names.iter().any(|name| alphabets[*right].contains(name))
+                    })
+                    .count();
+                let estimate = components[*left]
+                    .num_of_states()
+                    .saturating_mul(components[*right].num_of_states());
+
+                (shared == 0, estimate, Reverse(shared))
+            })
+            .expect("There are at least two components");
+
+        // The right index is larger, so removing it first keeps the left index valid.
+        let right_component = components.remove(right);
+        let left_component = components.remove(left);
+        let right_name = names.remove(right);
+        let left_name = names.remove(left);
+        let name = format!("({left_name} || {right_name})");
+
+        let mut time_compose = timing.start("compose");
+        let product = parallel_lts(&left_component, &right_component, specification)?;
+        time_compose.finish();
+        timing.record(
+            &format!("step {step} {name}"),
+            format!(
+                "{} states and {} transitions",
+                product.num_of_states(),
+                product.num_of_transitions()
+            ),
+        );
+
+        let product = hide_closed_actions(product, &components, specification)?;
+        let reduced = reduce_lts(product, equivalence, timing);
+        timing.record(
+            &format!("step {step} reduced"),
+            format!(
+                "{} states and {} transitions",
+                reduced.num_of_states(),
+                reduced.num_of_transitions()
+            ),
+        );
+
+        components.push(reduced);
+        names.push(name);
+        step += 1;
+    }
+
+    let result = components.pop().expect("There is exactly one component left");
+    let mut time_operators = timing.start("apply operators");
+    let result = apply_composition_operators(result, specification)?;
+    time_operators.finish();
+
+    Ok(reduce_lts(result, equivalence, timing))
+}
+
+/// Returns the names of the actions that occur in the visible labels of the LTS.
+fn alphabet<L: TransitionLabel>(lts: &LabelledTransitionSystem<L>) -> FxHashSet<String> {
+    lts.labels()
+        .iter()
+        .enumerate()
+        .filter(|(label_index, _)| !lts.is_hidden_label(LabelIndex::new(*label_index)))
+        .flat_map(|(_, label)| parse_multi_action(label).actions)
+        .map(|action| action.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_lts::random_lts_monolithic;
+    use merc_utilities::random_test;
+
+    use crate::compare_lts;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_compose_lts() {
+        random_test(100, |rng| {
+            let components: Vec<LabelledTransitionSystem<String>> =
+                (0..3).map(|_| random_lts_monolithic(rng, 6, 3, 2)).collect();
+            let specification =
+                CompositionSpecification::parse("hide({x}, allow({x, c, a|c}, comm({a|b -> x}, P0 || P1 || P2)))")
+                    .unwrap();
+
+            let mut timing = Timing::new();
+            let expected = compose_lts(components.clone(), &specification, None, &mut timing).unwrap();
+            let result = compose_lts(
+                components,
+                &specification,
+                Some(Equivalence::BranchingBisim),
+                &mut timing,
+            )
+            .unwrap();
+
+            assert!(result.num_of_states() <= expected.num_of_states());
+            assert!(compare_lts(Equivalence::BranchingBisim, result, expected, &mut timing));
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_smart_reduction() {
+        random_test(100, |rng| {
+            let components: Vec<LabelledTransitionSystem<String>> =
+                (0..4).map(|_| random_lts_monolithic(rng, 5, 3, 2)).collect();
+            let specification = CompositionSpecification::parse(
+                "rename({c -> y}, hide({x}, allow({x, c, a|c}, comm({a|b -> x}, P0 || P1 || P2 || P3))))",
+            )
+            .unwrap();
+
+            let mut timing = Timing::new();
+            let expected = compose_lts(components.clone(), &specification, None, &mut timing).unwrap();
+            let result = smart_reduction(components, &specification, Equivalence::BranchingBisim, &mut timing).unwrap();
+
+            assert!(compare_lts(Equivalence::BranchingBisim, result, expected, &mut timing));
+        });
+    }
+
+    #[test]
+    fn test_compose_lts_wrong_number_of_components() {
+        let specification = CompositionSpecification::parse("P || Q").unwrap();
+        let mut timing = Timing::new();
+
+        assert!(compose_lts::<String>(Vec::new(), &specification, None, &mut timing).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+#![forbid(unsafe_code)]
+
+use log::debug;
+use rustc_hash::FxHashMap;
+
+use merc_collections::VecSet;
+use merc_io::LargeFormatter;
+use merc_io::TimeProgress;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::LtsBuilderFast;
+use merc_lts::StateIndex;
+
+/// Returns a deterministic LTS with the same traces as the given LTS using the
+/// subset construction. When `weak` is true the internal actions are abstracted
+/// from, and the resulting LTS has the same weak traces and no internal actions.
+///
+/// Note that the resulting LTS can be exponentially larger than the input LTS.
+pub fn determinise<L: LTS>(lts: &L, weak: bool) -> LabelledTransitionSystem<L::Label> {
+    let mut builder = LtsBuilderFast::new(lts.labels().into(), Vec::new());
+
+    // Every set of states becomes a single state in the deterministic LTS.
+    let mut indices: FxHashMap<VecSet<StateIndex>, StateIndex> = FxHashMap::default();
+    let mut queue = Vec::new();
+
+    let initial = closure(lts, VecSet::singleton(lts.initial_state_index()), weak);
+    indices.insert(initial.clone(), StateIndex::new(0));
+    queue.push(initial);
+
+    let progress = TimeProgress::new(
+        |num_of_states: usize| {
+            debug!("Found {} deterministic states...", LargeFormatter(num_of_states));
+        },
+        1,
+    );
+
+    // The successors of the current set per label.
+    let mut successors: FxHashMap<LabelIndex, VecSet<StateIndex>> = FxHashMap::default();
+    while let Some(states) = queue.pop() {
+        progress.print(indices.len());
+        let from = indices[&states];
+
+        successors.clear();
+        for state_index in &states {
+            for transition in lts.outgoing_transitions(*state_index) {
+                if !weak || !lts.is_hidden_label(transition.label) {
+                    successors.entry(transition.label).or_default().insert(transition.to);
+                }
+            }
+        }
+
+        // Ensure that the resulting LTS does not depend on the iteration order of the hash map.
+        let mut labels: Vec<LabelIndex> = successors.keys().copied().collect();
+        labels.sort_unstable();
+
+        for label in labels {
+            let next = closure(lts, successors.remove(&label).unwrap_or_default(), weak);
+
+            let num_of_states = indices.len();
+            let to = *indices.entry(next.clone()).or_insert_with(|| {
+                queue.push(next);
+                StateIndex::new(num_of_states)
+            });
+
+            builder.add_transition(from, &lts.labels()[label], to);
+        }
+    }
+
+    builder.require_num_of_states(indices.len());
+    let result = builder.finish(StateIndex::new(0), false);
+
+    // The states are sets of original states, so only the specification is kept.
+    match lts.specification() {
+        Some(specification) => result.with_specification(specification.clone()),
+        None => result,
+    }
+}
+
+/// Extends the given set of states with all states reachable by internal steps when `weak` is true.
+fn closure<L: LTS>(lts: &L, states: VecSet<StateIndex>, weak: bool) -> VecSet<StateIndex> {
+    if !weak {
+        return states;
+    }
+
+    let mut result = states.clone();
+    let mut stack: Vec<StateIndex> = states.iter().copied().collect();
+    while let Some(state_index) = stack.pop() {
+        for transition in lts.outgoing_transitions(state_index) {
+            if lts.is_hidden_label(transition.label) && result.insert(transition.to) {
+                stack.push(transition.to);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_io::DumpFiles;
+    use merc_lts::random_lts;
+    use merc_lts::write_aut;
+    use merc_utilities::random_test;
+
+    /// Returns true iff the given LTS is deterministic.
+    fn is_deterministic<L: LTS>(lts: &L) -> bool {
+        lts.iter_states().all(|state_index| {
+            let mut labels: Vec<LabelIndex> = lts
+                .outgoing_transitions(state_index)
+                .map(|transition| transition.label)
+                .collect();
+            let num_of_transitions = labels.len();
+            labels.sort_unstable();
+            labels.dedup();
+            labels.len() == num_of_transitions
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_determinise() {
+        random_test(100, |rng| {
+            let mut files = DumpFiles::new("test_random_determinise");
+
+            let lts = random_lts(rng, 5, 3, 2);
+            files.dump("input.aut", |w| write_aut(w, &lts)).unwrap();
+
+            let deterministic_lts = determinise(&lts, false);
+            files
+                .dump("deterministic.aut", |w| write_aut(w, &deterministic_lts))
+                .unwrap();
+            assert!(is_deterministic(&deterministic_lts));
+
+            let weak_lts = determinise(&lts, true);
+            files.dump("weak.aut", |w| write_aut(w, &weak_lts)).unwrap();
+            assert!(is_deterministic(&weak_lts));
+            assert!(
+                weak_lts.iter_states().all(|state_index| weak_lts
+                    .outgoing_transitions(state_index)
+                    .all(|transition| !weak_lts.is_hidden_label(transition.label))),
+                "The weak determinisation should not contain internal actions"
+            );
+        });
+    }
+}
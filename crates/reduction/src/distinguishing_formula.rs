@@ -0,0 +1,622 @@
+#![forbid(unsafe_code)]
+
+//! Computes Hennessy-Milner formulas that distinguish two states that are not
+//! bisimilar, following the approach of
+//!
+//! R. Cleaveland. On automatically explaining bisimulation inequivalence. CAV 1990.
+//!
+//! and its extension to branching bisimulation by H. Korver. Computing
+//! distinguishing formulas for branching bisimulation. CAV 1991.
+
+use log::debug;
+use rustc_hash::FxHashMap;
+use rustc_hash::FxHashSet;
+
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::StateIndex;
+use merc_syntax::ActFrm;
+use merc_syntax::Action;
+use merc_syntax::FixedPointOperator;
+use merc_syntax::ModalityOperator;
+use merc_syntax::MultiAction;
+use merc_syntax::RegFrm;
+use merc_syntax::Span;
+use merc_syntax::StateFrm;
+use merc_syntax::StateFrmOp;
+use merc_syntax::StateFrmUnaryOp;
+use merc_syntax::StateVarDecl;
+use merc_utilities::MercError;
+use merc_utilities::Timing;
+
+use crate::BlockIndex;
+use crate::Equivalence;
+use crate::IndexedPartition;
+use crate::Partition;
+use crate::SignatureBuilder;
+use crate::branching_bisim_signature;
+use crate::strong_bisim_signature;
+
+/// Returns a formula that holds in the initial state of the left LTS, but not
+/// in the initial state of the right LTS, or `None` when both are equivalent
+/// modulo the given equivalence.
+///
+/// Only strong and branching bisimulation are supported. The formula is derived
+/// from the sequence of partitions computed by naive signature refinement,
+/// which is considerably slower than the reduction algorithms themselves.
+pub fn distinguishing_formula<L: LTS>(
+    equivalence: Equivalence,
+    left: L,
+    right: L,
+    timing: &mut Timing,
+) -> Result<Option<StateFrm>, MercError> {
+    let branching = match equivalence {
+        Equivalence::StrongBisim | Equivalence::StrongBisimNaive | Equivalence::StrongBisimPT => false,
+        Equivalence::BranchingBisim | Equivalence::BranchingBisimNaive | Equivalence::BranchingBisimGJ => true,
+        _ => {
+            return Err(format!("Distinguishing formulas are not supported for {equivalence:?}").into());
+        }
+    };
+
+    let mut time_merge = timing.start("merge lts");
+    let (merged, rhs_initial) = left.merge_disjoint(&right);
+    drop(right); // No longer needed.
+    time_merge.finish();
+
+    let mut time_formula = timing.start("distinguishing formula");
+    let history = PartitionHistory::new(&merged, merged.initial_state_index(), rhs_initial, branching);
+
+    let result = if history.is_distinguished(merged.initial_state_index(), rhs_initial) {
+        let mut generator = FormulaGenerator {
+            lts: &merged,
+            history: &history,
+            branching,
+            cache: FxHashMap::default(),
+            num_of_variables: 0,
+        };
+
+        Some(generator.distinguish(merged.initial_state_index(), rhs_initial))
+    } else {
+        None
+    };
+
+    time_formula.finish();
+    Ok(result)
+}
+
+/// Stores the partitions computed by every round of signature refinement,
+/// together with a representative state for every block.
+struct PartitionHistory {
+    /// The partition after every round, where the first partition consists of a single block.
+    partitions: Vec<IndexedPartition>,
+
+    /// For every round the representative state of every block.
+    representatives: Vec<Vec<StateIndex>>,
+}
+
+impl PartitionHistory {
+    /// Refines the partition until it is stable, or the two given states are
+    /// in different blocks.
+    fn new<L: LTS>(lts: &L, left: StateIndex, right: StateIndex, branching: bool) -> Self {
+        let mut partitions = vec![IndexedPartition::new(lts.num_of_states())];
+        let mut representatives = vec![vec![lts.initial_state_index()]];
+
+        let mut builder = SignatureBuilder::default();
+        let mut visited = FxHashSet::default();
+        let mut stack = Vec::new();
+
+        loop {
+            let partition = partitions.last().expect("There is at least one partition");
+
+            // The new block of every state is determined by its old block and its signature.
+            let mut id: FxHashMap<(BlockIndex, SignatureBuilder), BlockIndex> = FxHashMap::default();
+            let mut next_partition = IndexedPartition::new(lts.num_of_states());
+            let mut next_representatives = Vec::new();
+
+            for state_index in lts.iter_states() {
+                signature(
+                    state_index,
+                    lts,
+                    partition,
+                    branching,
+                    &mut builder,
+                    &mut visited,
+                    &mut stack,
+                );
+
+                let block = *id
+                    .entry((partition.block_number(state_index), builder.clone()))
+                    .or_insert_with(|| {
+                        next_representatives.push(state_index);
+                        BlockIndex::new(next_representatives.len() - 1)
+                    });
+                next_partition.set_block(state_index, block);
+            }
+
+            let stable = next_representatives.len() == representatives.last().map_or(0, |r| r.len());
+            partitions.push(next_partition);
+            representatives.push(next_representatives);
+
+            debug!(
+                "Round {}, found {} blocks",
+                partitions.len() - 1,
+                representatives.last().map_or(0, |r| r.len())
+            );
+
+            let partition = partitions.last().expect("There is at least one partition");
+            if stable || partition.block_number(left) != partition.block_number(right) {
+                break;
+            }
+        }
+
+        Self {
+            partitions,
+            representatives,
+        }
+    }
+
+    /// Returns true iff the given states are in different blocks of the last partition.
+    fn is_distinguished(&self, left: StateIndex, right: StateIndex) -> bool {
+        let partition = self.partitions.last().expect("There is at least one partition");
+        partition.block_number(left) != partition.block_number(right)
+    }
+
+    /// Returns the first round in which the given states are in different blocks.
+    fn split_round(&self, left: StateIndex, right: StateIndex) -> usize {
+        self.partitions
+            .iter()
+            .position(|partition| partition.block_number(left) != partition.block_number(right))
+            .expect("The states should be distinguished by the history")
+    }
+}
+
+/// Computes the (strong or branching) signature of the given state w.r.t. the partition.
+fn signature<L: LTS>(
+    state_index: StateIndex,
+    lts: &L,
+    partition: &IndexedPartition,
+    branching: bool,
+    builder: &mut SignatureBuilder,
+    visited: &mut FxHashSet<StateIndex>,
+    stack: &mut Vec<StateIndex>,
+) {
+    if branching {
+        branching_bisim_signature(state_index, lts, partition, builder, visited, stack);
+    } else {
+        strong_bisim_signature(state_index, lts, partition, builder);
+    }
+}
+
+struct FormulaGenerator<'a, L: LTS> {
+    lts: &'a L,
+    history: &'a PartitionHistory,
+    branching: bool,
+
+    /// Caches the formulas distinguishing two blocks in a given round.
+    cache: FxHashMap<(usize, BlockIndex, BlockIndex), StateFrm>,
+
+    /// The number of fixed point variables introduced so far.
+    num_of_variables: usize,
+}
+
+impl<L: LTS> FormulaGenerator<'_, L> {
+    /// Returns a formula that holds in all states of the block of `left`, but
+    /// in none of the states of the block of `right`, in the first round that
+    /// they are split.
+    fn distinguish(&mut self, left: StateIndex, right: StateIndex) -> StateFrm {
+        let round = self.history.split_round(left, right);
+        let partition = &self.history.partitions[round];
+        let key = (round, partition.block_number(left), partition.block_number(right));
+
+        if let Some(formula) = self.cache.get(&key) {
+            return formula.clone();
+        }
+
+        // Both blocks have the same parent block, so they differ in their signature w.r.t. the previous round.
+        let left_signature = self.signature(left, round - 1);
+        let right_signature = self.signature(right, round - 1);
+
+        let formula =
+            if let Some((label, block)) = left_signature.iter().find(|element| !right_signature.contains(element)) {
+                self.distinguish_signature(left, right, *label, *block, &right_signature, round - 1)
+            } else {
+                let (label, block) = right_signature
+                    .iter()
+                    .find(|element| !left_signature.contains(element))
+                    .expect("The signatures of split blocks must differ");
+                negation(self.distinguish_signature(right, left, *label, *block, &left_signature, round - 1))
+            };
+
+        self.cache.insert(key, formula.clone());
+        formula
+    }
+
+    /// Returns a formula that holds in the block of `left`, which can perform an `label` transition
+    /// into the given block, but not in the block of `right`, with the given signature, which cannot.
+    fn distinguish_signature(
+        &mut self,
+        left: StateIndex,
+        right: StateIndex,
+        label: LabelIndex,
+        block: BlockIndex,
+        right_signature: &SignatureBuilder,
+        round: usize,
+    ) -> StateFrm {
+        let partition = &self.history.partitions[round];
+        let target = self.history.representatives[round][block.value()];
+
+        // Distinguish the target from all blocks reachable by the right state with the same label.
+        let mut targets: Vec<BlockIndex> = right_signature
+            .iter()
+            .filter(|(right_label, _)| *right_label == label)
+            .map(|(_, block)| *block)
+            .collect();
+        if self.branching && self.lts.is_hidden_label(label) {
+            // An inert internal step of the right state ends in its own block.
+            targets.push(partition.block_number(right));
+        }
+
+        let mut post = StateFrm::True;
+        for other in targets {
+            let formula = self.distinguish(target, self.history.representatives[round][other.value()]);
+            post = conjunction(post, formula);
+        }
+
+        if !self.branching {
+            return diamond(self.action(label), post);
+        }
+
+        // The formula must hold on the inert path of the left state, which stays in
+        // the current block, but not in the other blocks reachable by internal steps.
+        let current = partition.block_number(left);
+        let mut exits = FxHashSet::default();
+        for state_index in self.lts.iter_states() {
+            if partition.block_number(state_index) == current {
+                for transition in self.lts.outgoing_transitions(state_index) {
+                    let to_block = partition.block_number(transition.to);
+                    if self.lts.is_hidden_label(transition.label) && to_block != current {
+                        exits.insert(to_block);
+                    }
+                }
+            }
+        }
+
+        let mut exits: Vec<BlockIndex> = exits.into_iter().collect();
+        exits.sort_unstable();
+
+        let mut pre = StateFrm::True;
+        for other in exits {
+            let formula = self.distinguish(left, self.history.representatives[round][other.value()]);
+            pre = conjunction(pre, formula);
+        }
+
+        if pre == StateFrm::True {
+            // <tau*><a>post
+            StateFrm::Modality {
+                operator: ModalityOperator::Diamond,
+                formula: RegFrm::Sequence {
+                    lhs: Box::new(RegFrm::Iteration(Box::new(self.action(LabelIndex::new(0))))),
+                    rhs: Box::new(self.action(label)),
+                },
+                expr: Box::new(post),
+            }
+        } else {
+            // mu X. pre && (<a>post || <tau>X)
+            let variable = format!("X{}", self.num_of_variables);
+            self.num_of_variables += 1;
+
+            let step = StateFrm::Binary {
+                op: StateFrmOp::Disjunction,
+                lhs: Box::new(diamond(self.action(label), post)),
+                rhs: Box::new(diamond(
+                    self.action(LabelIndex::new(0)),
+                    StateFrm::Id(variable.clone(), Vec::new()),
+                )),
+            };
+
+            StateFrm::FixedPoint {
+                operator: FixedPointOperator::Least,
+                variable: StateVarDecl {
+                    identifier: variable,
+                    arguments: Vec::new(),
+                    span: Span { start: 0, end: 0 },
+                },
+                body: Box::new(conjunction(pre, step)),
+            }
+        }
+    }
+
+    /// Returns the signature of the given state w.r.t. the partition of the given round.
+    fn signature(&self, state_index: StateIndex, round: usize) -> SignatureBuilder {
+        let mut builder = SignatureBuilder::default();
+        signature(
+            state_index,
+            self.lts,
+            &self.history.partitions[round],
+            self.branching,
+            &mut builder,
+            &mut FxHashSet::default(),
+            &mut Vec::new(),
+        );
+        builder
+    }
+
+    /// Returns the regular formula that matches exactly the given label.
+    fn action(&self, label: LabelIndex) -> RegFrm {
+        let actions = if self.lts.is_hidden_label(label) {
+            Vec::new()
+        } else {
+            vec![Action {
+                id: self.lts.labels()[label.value()].to_string(),
+                args: Vec::new(),
+            }]
+        };
+
+        RegFrm::Action(ActFrm::MultAct(MultiAction { actions }))
+    }
+}
+
+/// Returns `<formula>expr`.
+fn diamond(formula: RegFrm, expr: StateFrm) -> StateFrm {
+    StateFrm::Modality {
+        operator: ModalityOperator::Diamond,
+        formula,
+        expr: Box::new(expr),
+    }
+}
+
+/// Returns `lhs && rhs`, where trivial conjuncts are omitted.
+fn conjunction(lhs: StateFrm, rhs: StateFrm) -> StateFrm {
+    if lhs == StateFrm::True {
+        rhs
+    } else if rhs == StateFrm::True {
+        lhs
+    } else {
+        StateFrm::Binary {
+            op: StateFrmOp::Conjunction,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+}
+
+/// Returns `!formula`.
+fn negation(formula: StateFrm) -> StateFrm {
+    StateFrm::Unary {
+        op: StateFrmUnaryOp::Negation,
+        expr: Box::new(formula),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_io::DumpFiles;
+    use merc_lts::LabelledTransitionSystem;
+    use merc_lts::random_lts;
+    use merc_lts::read_aut;
+    use merc_lts::write_aut;
+    use merc_utilities::random_test;
+    use rand::Rng;
+
+    use crate::compare_lts;
+
+    /// Returns the states that satisfy the given formula, only supports the
+    /// fragment generated by [distinguishing_formula].
+    fn evaluate<L: LTS>(lts: &L, formula: &StateFrm, environment: &mut FxHashMap<String, Vec<bool>>) -> Vec<bool> {
+        match formula {
+            StateFrm::True => vec![true; lts.num_of_states()],
+            StateFrm::False => vec![false; lts.num_of_states()],
+            StateFrm::Id(identifier, _) => environment[identifier].clone(),
+            StateFrm::Unary {
+                op: StateFrmUnaryOp::Negation,
+                expr,
+            } => evaluate(lts, expr, environment).iter().map(|value| !value).collect(),
+            StateFrm::Binary { op, lhs, rhs } => {
+                let lhs = evaluate(lts, lhs, environment);
+                let rhs = evaluate(lts, rhs, environment);
+                match op {
+                    StateFrmOp::Conjunction => lhs.iter().zip(rhs).map(|(l, r)| *l && r).collect(),
+                    StateFrmOp::Disjunction => lhs.iter().zip(rhs).map(|(l, r)| *l || r).collect(),
+                    _ => unimplemented!("Unsupported operator {op:?}"),
+                }
+            }
+            StateFrm::Modality {
+                operator: ModalityOperator::Diamond,
+                formula,
+                expr,
+            } => {
+                let target = evaluate(lts, expr, environment);
+                evaluate_diamond(lts, formula, target)
+            }
+            StateFrm::FixedPoint {
+                operator: FixedPointOperator::Least,
+                variable,
+                body,
+            } => {
+                let mut current = vec![false; lts.num_of_states()];
+                loop {
+                    environment.insert(variable.identifier.clone(), current.clone());
+                    let next = evaluate(lts, body, environment);
+                    if next == current {
+                        return current;
+                    }
+                    current = next;
+                }
+            }
+            _ => unimplemented!("Unsupported formula {formula}"),
+        }
+    }
+
+    /// Returns the states that can reach a target state by a path matching the regular formula.
+    fn evaluate_diamond<L: LTS>(lts: &L, formula: &RegFrm, target: Vec<bool>) -> Vec<bool> {
+        match formula {
+            RegFrm::Action(ActFrm::MultAct(action)) => lts
+                .iter_states()
+                .map(|state_index| {
+                    lts.outgoing_transitions(state_index).any(|transition| {
+                        let matches = if action.actions.is_empty() {
+                            lts.is_hidden_label(transition.label)
+                        } else {
+                            !lts.is_hidden_label(transition.label)
+                                && lts.labels()[transition.label.value()].to_string() == action.actions[0].id
+                        };
+
+                        matches && target[transition.to]
+                    })
+                })
+                .collect(),
+            RegFrm::Sequence { lhs, rhs } => {
+                let target = evaluate_diamond(lts, rhs, target);
+                evaluate_diamond(lts, lhs, target)
+            }
+            RegFrm::Iteration(body) => {
+                let mut current = target.clone();
+                loop {
+                    let step = evaluate_diamond(lts, body, current.clone());
+                    let next: Vec<bool> = target.iter().zip(step).map(|(t, s)| *t || s).collect();
+                    if next == current {
+                        return current;
+                    }
+                    current = next;
+                }
+            }
+            _ => unimplemented!("Unsupported regular formula {formula}"),
+        }
+    }
+
+    /// Checks that the formula distinguishes the initial states of both LTSs.
+    fn check_formula<L: LTS + Clone>(left: &L, right: &L, formula: &StateFrm) {
+        let (merged, rhs_initial) = left.clone().merge_disjoint(right);
+        let result = evaluate(&merged, formula, &mut FxHashMap::default());
+
+        assert!(
+            result[merged.initial_state_index()],
+            "The formula {formula} should hold in the left LTS"
+        );
+        assert!(
+            !result[rhs_initial],
+            "The formula {formula} should not hold in the right LTS"
+        );
+    }
+
+    /// Returns a copy of the given LTS with a different initial state.
+    fn with_initial_state<L: LTS>(lts: &L, initial_state: StateIndex) -> LabelledTransitionSystem<L::Label> {
+        LabelledTransitionSystem::new(
+            initial_state,
+            Some(lts.num_of_states()),
+            || {
+                lts.iter_states().flat_map(|state_index| {
+                    lts.outgoing_transitions(state_index)
+                        .map(move |transition| (state_index, transition.label, transition.to))
+                })
+            },
+            lts.labels().to_vec(),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_distinguishing_formula() {
+        random_test(100, |rng| {
+            let mut files = DumpFiles::new("test_random_distinguishing_formula");
+
+            // Compare two states of the same LTS, which are often only distinguished after several rounds.
+            let left = random_lts(rng, 4, 3, 2);
+            let right = with_initial_state(&left, StateIndex::new(rng.random_range(0..left.num_of_states())));
+            files.dump("left.aut", |w| write_aut(w, &left)).unwrap();
+            files.dump("right.aut", |w| write_aut(w, &right)).unwrap();
+
+            let mut timing = Timing::new();
+            for equivalence in [Equivalence::StrongBisim, Equivalence::BranchingBisim] {
+                let equivalent = compare_lts(equivalence, left.clone(), right.clone(), &mut timing);
+                let formula = distinguishing_formula(equivalence, left.clone(), right.clone(), &mut timing).unwrap();
+
+                assert_eq!(
+                    equivalent,
+                    formula.is_none(),
+                    "The formula should only exist for inequivalent LTSs"
+                );
+                if let Some(formula) = formula {
+                    check_formula(&left, &right, &formula);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_distinguishing_formula() {
+        // The choice between b and c is made after the a-action.
+        let left = read_aut(
+            b"des (0,3,3)
+(0,a,1)
+(1,b,2)
+(1,c,2)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        // The choice between b and c is made before the a-action.
+        let right = read_aut(
+            b"des (0,4,4)
+(0,a,1)
+(0,a,2)
+(1,b,3)
+(2,c,3)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        for equivalence in [Equivalence::StrongBisim, Equivalence::BranchingBisim] {
+            let formula = distinguishing_formula(equivalence, left.clone(), right.clone(), &mut timing)
+                .unwrap()
+                .expect("The LTSs are not bisimilar");
+            check_formula(&left, &right, &formula);
+        }
+
+        assert!(
+            distinguishing_formula(Equivalence::WeakBisim, left, right, &mut timing).is_err(),
+            "Weak bisimulation is not supported"
+        );
+    }
+
+    #[test]
+    fn test_branching_distinguishing_formula() {
+        let left = read_aut(
+            b"des (0,2,3)
+(0,a,1)
+(0,c,2)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        // The internal step is not inert, since afterwards c is no longer possible.
+        let right = read_aut(
+            b"des (0,3,4)
+(0,i,1)
+(1,a,2)
+(0,c,3)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        let formula = distinguishing_formula(Equivalence::BranchingBisim, left.clone(), right.clone(), &mut timing)
+            .unwrap()
+            .expect("The LTSs are not branching bisimilar");
+
+        assert!(
+            matches!(formula, StateFrm::FixedPoint { .. }),
+            "The formula {formula} should require c to remain possible before the a-action"
+        );
+        check_formula(&left, &right, &formula);
+    }
+}
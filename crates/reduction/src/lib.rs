@@ -1,25 +1,43 @@
 #![doc = include_str!("../README.md")]
 
 mod block_partition;
+mod branching_bisimulation_gj;
+mod check;
 mod compare;
+mod compose;
+mod determinise;
+mod distinguishing_formula;
 mod indexed_partition;
+mod probabilistic_bisimulation;
 mod quotient;
 mod reduce;
 mod scc_decomposition;
 mod signature_refinement;
 mod signatures;
 mod simple_block_partition;
+mod simulation;
 mod sort_topological;
+mod strong_bisimulation_pt;
+mod tau_star;
 mod weak_bisimulation;
 
 pub use block_partition::*;
+pub use branching_bisimulation_gj::*;
+pub use check::*;
 pub use compare::*;
+pub use compose::*;
+pub use determinise::*;
+pub use distinguishing_formula::*;
 pub use indexed_partition::*;
+pub use probabilistic_bisimulation::*;
 pub use quotient::*;
 pub use reduce::*;
 pub use scc_decomposition::*;
 pub use signature_refinement::*;
 pub use signatures::*;
 pub use simple_block_partition::*;
+pub use simulation::*;
 pub use sort_topological::*;
+pub use strong_bisimulation_pt::*;
+pub use tau_star::*;
 pub use weak_bisimulation::*;
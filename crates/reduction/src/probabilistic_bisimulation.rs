@@ -0,0 +1,259 @@
+#![forbid(unsafe_code)]
+
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+
+use merc_io::TimeProgress;
+use merc_lts::Distribution;
+use merc_lts::DistributionIndex;
+use merc_lts::ProbabilisticLts;
+use merc_lts::ProbabilisticLtsBuilder;
+use merc_lts::StateIndex;
+use merc_lts::TransitionLabel;
+use merc_utilities::Timing;
+
+use crate::BlockIndex;
+use crate::BlockPartition;
+use crate::BlockPartitionBuilder;
+use crate::Partition;
+use crate::SignatureBuilder;
+
+/// Computes a strong probabilistic bisimulation partitioning using signature
+/// refinement.
+///
+/// # Details
+///
+/// Two states are strongly probabilistic bisimilar iff for every transition of
+/// the one state there is a transition of the other state with the same label
+/// such that both distributions assign the same probability to every block.
+/// The signature of a state therefore consists of pairs of labels and the
+/// classes of its distributions lifted to the current partition.
+pub fn strong_probabilistic_bisim_sigref<L: TransitionLabel>(
+    lts: ProbabilisticLts<L>,
+    timing: &mut Timing,
+) -> (ProbabilisticLts<L>, BlockPartition) {
+    let mut time = timing.start("reduction");
+
+    let mut partition = BlockPartition::new(lts.num_of_states());
+    let mut partition_builder = BlockPartitionBuilder::default();
+    let mut builder = SignatureBuilder::default();
+
+    // The class of every distribution lifted to the current partition, where equal lifted distributions have the same class.
+    let mut lifted_classes: FxHashMap<Distribution, BlockIndex> = FxHashMap::default();
+    let mut distribution_class = vec![BlockIndex::new(0); lts.num_of_distributions()];
+
+    // The dense number for the signature of every state in the block that is being split.
+    let mut signature_ids: FxHashMap<SignatureBuilder, BlockIndex> = FxHashMap::default();
+    let mut state_number = vec![BlockIndex::new(0); lts.num_of_states()];
+    let mut block_states = Vec::new();
+
+    let progress = TimeProgress::new(
+        |(iteration, blocks)| {
+            debug!("Iteration {iteration}, found {blocks} blocks...",);
+        },
+        5,
+    );
+
+    let mut iteration = 0;
+    loop {
+        progress.print((iteration, partition.num_of_blocks()));
+
+        lifted_classes.clear();
+        for (index, class) in distribution_class.iter_mut().enumerate() {
+            let lifted = lts
+                .distribution(DistributionIndex::new(index))
+                .map(|state_index| StateIndex::new(partition.block_number(state_index).value()));
+
+            let next_class = BlockIndex::new(lifted_classes.len());
+            *class = *lifted_classes.entry(lifted).or_insert(next_class);
+        }
+
+        // Split every block of the current partition on the signatures of its states.
+        let mut changed = false;
+        for block_index in (0..partition.num_of_blocks()).map(BlockIndex::new) {
+            signature_ids.clear();
+            block_states.clear();
+            block_states.extend(partition.iter_block(block_index));
+
+            for &state_index in &block_states {
+                builder.clear();
+                builder.extend(
+                    lts.outgoing_transitions(state_index)
+                        .map(|transition| (transition.label, distribution_class[transition.to])),
+                );
+                builder.sort_unstable();
+                builder.dedup();
+
+                trace!("State {state_index} signature {builder:?}");
+
+                let next_number = BlockIndex::new(signature_ids.len());
+                state_number[state_index] = if let Some(number) = signature_ids.get(&builder) {
+                    *number
+                } else {
+                    signature_ids.insert(builder.clone(), next_number);
+                    next_number
+                };
+            }
+
+            if signature_ids.len() > 1 {
+                for &state_index in &block_states {
+                    partition.mark_element(state_index);
+                }
+
+                // The new blocks are only considered in the next iteration.
+                let _ = partition.partition_marked_with(block_index, &mut partition_builder, |state_index, _| {
+                    state_number[state_index]
+                });
+                changed = true;
+            }
+        }
+
+        iteration += 1;
+        if !changed {
+            break;
+        }
+    }
+
+    debug!("Refinement partition {partition}");
+    time.finish();
+
+    (lts, partition)
+}
+
+/// Returns the quotient of the probabilistic LTS for the given partition,
+/// where every state is replaced by its block and the distributions are lifted
+/// to the blocks.
+pub fn quotient_probabilistic_lts<L: TransitionLabel>(
+    lts: &ProbabilisticLts<L>,
+    partition: &impl Partition,
+) -> ProbabilisticLts<L> {
+    let block = |state_index: StateIndex| StateIndex::new(partition.block_number(state_index).value());
+
+    let mut builder = ProbabilisticLtsBuilder::new(lts.labels().to_vec(), Vec::new());
+    builder.require_num_of_states(partition.num_of_blocks());
+
+    // Bisimilar states have the same lifted transitions, which are only added once by the builder.
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            builder.add_transition(
+                block(state_index),
+                &lts.labels()[transition.label],
+                lts.distribution(transition.to).map(block),
+            );
+        }
+    }
+
+    let result = builder.finish(lts.distribution(lts.initial_distribution()).map(block));
+    match lts.specification() {
+        Some(specification) => result.with_specification(specification.clone()),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_lts::LTS;
+    use merc_lts::Probability;
+    use merc_lts::random_lts;
+    use merc_lts::random_probabilistic_lts;
+    use merc_utilities::random_test;
+
+    use crate::strong_bisim_sigref;
+
+    #[test]
+    fn test_strong_probabilistic_bisim_sigref() {
+        let half = || Probability::new(1.into(), 2.into());
+        let third = Probability::new(1.into(), 3.into());
+        let two_thirds = Probability::new(2.into(), 3.into());
+        let distribution = |entries: &[(usize, Probability)]| {
+            Distribution::new(
+                entries
+                    .iter()
+                    .map(|(state_index, probability)| (StateIndex::new(*state_index), probability.clone()))
+                    .collect(),
+            )
+        };
+
+        // States 0 and 1 reach the deadlock states 3 and 4 and the looping state 5 with equal probabilities, but state 2 does not.
+        let mut builder = ProbabilisticLtsBuilder::new(vec!["a".to_string(), "b".to_string()], Vec::new());
+        builder.add_transition(StateIndex::new(0), "a", distribution(&[(3, half()), (5, half())]));
+        builder.add_transition(StateIndex::new(1), "a", distribution(&[(4, half()), (5, half())]));
+        builder.add_transition(StateIndex::new(2), "a", distribution(&[(3, third), (5, two_thirds)]));
+        builder.add_transition(StateIndex::new(5), "b", Distribution::dirac(StateIndex::new(5)));
+        let lts = builder.finish(Distribution::dirac(StateIndex::new(0)));
+
+        let (lts, partition) = strong_probabilistic_bisim_sigref(lts, &mut Timing::new());
+        assert_eq!(partition.num_of_blocks(), 4);
+        assert_eq!(
+            partition.block_number(StateIndex::new(0)),
+            partition.block_number(StateIndex::new(1))
+        );
+        assert_eq!(
+            partition.block_number(StateIndex::new(3)),
+            partition.block_number(StateIndex::new(4))
+        );
+
+        let quotient = quotient_probabilistic_lts(&lts, &partition);
+        assert_eq!(quotient.num_of_states(), 4);
+        assert_eq!(quotient.num_of_transitions(), 3);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_strong_probabilistic_bisim_dirac() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+
+            // An LTS where every distribution assigns probability one to a single state.
+            let mut builder = ProbabilisticLtsBuilder::new(lts.labels().to_vec(), Vec::new());
+            builder.require_num_of_states(lts.num_of_states());
+            for state_index in lts.iter_states() {
+                for transition in lts.outgoing_transitions(state_index) {
+                    builder.add_transition(
+                        state_index,
+                        &lts.labels()[transition.label],
+                        Distribution::dirac(transition.to),
+                    );
+                }
+            }
+            let probabilistic_lts = builder.finish(Distribution::dirac(lts.initial_state_index()));
+
+            let mut timing = Timing::new();
+            let (_, expected_partition) = strong_bisim_sigref(lts, &mut timing);
+            let (_, result_partition) = strong_probabilistic_bisim_sigref(probabilistic_lts, &mut timing);
+
+            // Both partitions relate the same states.
+            for left in 0..result_partition.len() {
+                for right in 0..result_partition.len() {
+                    let (left, right) = (StateIndex::new(left), StateIndex::new(right));
+                    assert_eq!(
+                        result_partition.block_number(left) == result_partition.block_number(right),
+                        expected_partition.block_number(left) == expected_partition.block_number(right),
+                        "States {left} and {right} are related differently"
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_strong_probabilistic_bisim_quotient() {
+        random_test(100, |rng| {
+            let lts = random_probabilistic_lts::<String>(rng, 20, 3, 3, 2);
+
+            let mut timing = Timing::new();
+            let (lts, partition) = strong_probabilistic_bisim_sigref(lts, &mut timing);
+            let quotient = quotient_probabilistic_lts(&lts, &partition);
+
+            // The quotient cannot be reduced any further.
+            let (quotient, partition) = strong_probabilistic_bisim_sigref(quotient, &mut timing);
+            assert_eq!(partition.num_of_blocks(), quotient.num_of_states());
+        });
+    }
+}
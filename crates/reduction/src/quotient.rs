@@ -72,10 +72,41 @@ pub fn quotient_lts_naive<L: LTS>(
     }
 
     builder.require_num_of_states(partition.num_of_blocks());
-    builder.finish(
+    let quotient_lts = builder.finish(
         StateIndex::new(partition.block_number(lts.initial_state_index()).value()),
         true,
-    )
+    );
+    merge_state_labels(quotient_lts, lts, partition)
+}
+
+/// The same as [quotient_lts_naive] where the tau-loops are eliminated, except
+/// that tau self-loops are preserved. These self-loops indicate divergence, see
+/// [crate::preprocess_dpbranching].
+pub fn quotient_lts_divergence<L: LTS>(lts: &L, partition: &impl Partition) -> LabelledTransitionSystem<L::Label> {
+    let mut builder = LtsBuilderFast::with_capacity(lts.labels().into(), Vec::new(), partition.num_of_blocks());
+
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            let block = partition.block_number(state_index);
+            let to_block = partition.block_number(transition.to);
+
+            // Inert tau transitions are removed, unless they are self-loops.
+            if !(lts.is_hidden_label(transition.label) && block == to_block && state_index != transition.to) {
+                builder.add_transition(
+                    StateIndex::new(block.value()),
+                    &lts.labels()[transition.label],
+                    StateIndex::new(to_block.value()),
+                );
+            }
+        }
+    }
+
+    builder.require_num_of_states(partition.num_of_blocks());
+    let quotient_lts = builder.finish(
+        StateIndex::new(partition.block_number(lts.initial_state_index()).value()),
+        true,
+    );
+    merge_state_labels(quotient_lts, lts, partition)
 }
 
 /// Optimised implementation for block partitions.
@@ -131,8 +162,30 @@ pub fn quotient_lts_block<L: LTS, const BRANCHING: bool>(
     }
 
     builder.require_num_of_states(partition.num_of_blocks());
-    builder.finish(
+    let quotient_lts = builder.finish(
         StateIndex::new(partition.block_number(lts.initial_state_index()).value()),
         true,
-    )
+    );
+    merge_state_labels(quotient_lts, lts, partition)
+}
+
+/// Adds the state labels and the specification of the given LTS to its
+/// quotient, where every block is labelled by the state labels of all its
+/// states.
+fn merge_state_labels<L: LTS>(
+    mut quotient_lts: LabelledTransitionSystem<L::Label>,
+    lts: &L,
+    partition: &impl Partition,
+) -> LabelledTransitionSystem<L::Label> {
+    if let Some(state_labels) = lts.state_labels() {
+        quotient_lts = quotient_lts.with_state_labels(state_labels.merge(partition.num_of_blocks(), |state_index| {
+            StateIndex::new(partition.block_number(state_index).value())
+        }));
+    }
+
+    if let Some(specification) = lts.specification() {
+        quotient_lts = quotient_lts.with_specification(specification.clone());
+    }
+
+    quotient_lts
 }
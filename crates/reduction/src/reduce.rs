@@ -1,15 +1,26 @@
 #![forbid(unsafe_code)]
 
+use log::warn;
 use merc_lts::LTS;
 use merc_lts::LabelledTransitionSystem;
 use merc_utilities::Timing;
 
+use crate::branching_bisim_gj;
 use crate::branching_bisim_sigref;
 use crate::branching_bisim_sigref_naive;
+use crate::branching_bisim_sigref_parallel;
+use crate::determinise;
+use crate::dpbranching_bisim_sigref_naive;
 use crate::quotient_lts_block;
+use crate::quotient_lts_divergence;
 use crate::quotient_lts_naive;
+use crate::ready_simulation_preorder;
+use crate::simulation_preorder;
+use crate::strong_bisim_pt;
 use crate::strong_bisim_sigref;
 use crate::strong_bisim_sigref_naive;
+use crate::strong_bisim_sigref_parallel;
+use crate::tau_star_reduce;
 use crate::weak_bisim_sigref_naive;
 use crate::weak_bisimulation;
 
@@ -22,20 +33,37 @@ pub enum Equivalence {
     WeakBisimSigref,
     StrongBisim,
     StrongBisimNaive,
+    /// Strong bisimulation using the algorithm by Paige and Tarjan.
+    StrongBisimPT,
     BranchingBisim,
     BranchingBisimNaive,
+    /// Branching bisimulation using the O(m log n) algorithm by Groote, Jansen, Keiren and Wijs.
+    BranchingBisimGJ,
+    /// Divergence-preserving branching bisimulation.
+    #[cfg_attr(feature = "clap", value(name = "dpbranching-bisim"))]
+    DpBranchingBisim,
+    /// Trace equivalence, where internal actions are treated as visible actions.
+    Trace,
+    /// Trace equivalence where internal actions are abstracted from.
+    WeakTrace,
+    /// Removes all internal actions while preserving the weak traces.
+    TauStar,
+    /// Simulation equivalence, i.e., states simulate each other.
+    Simulation,
+    /// Ready simulation equivalence, i.e., states ready simulate each other.
+    ReadySimulation,
 }
 
 /// Reduces the given LTS modulo the given equivalence using signature refinement
 pub fn reduce_lts<L: LTS>(lts: L, equivalence: Equivalence, timing: &mut Timing) -> LabelledTransitionSystem<L::Label> {
     let (result, mut timer) = match equivalence {
         Equivalence::WeakBisim => {
-            let (lts, partition) = weak_bisimulation(lts, timing);
+            let (lts, partition, _) = weak_bisimulation(lts, timing);
             let quotient_time = timing.start("quotient");
             (quotient_lts_naive(&lts, &partition, true), quotient_time)
         }
         Equivalence::WeakBisimSigref => {
-            let (lts, partition) = weak_bisim_sigref_naive(lts, timing);
+            let (lts, partition, _) = weak_bisim_sigref_naive(lts, timing);
             let quotient_time = timing.start("quotient");
             (quotient_lts_naive(&lts, &partition, true), quotient_time)
         }
@@ -49,18 +77,167 @@ pub fn reduce_lts<L: LTS>(lts: L, equivalence: Equivalence, timing: &mut Timing)
             let quotient_time = timing.start("quotient");
             (quotient_lts_naive(&lts, &partition, false), quotient_time)
         }
+        Equivalence::StrongBisimPT => {
+            let (lts, partition) = strong_bisim_pt(lts, timing);
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_block::<_, false>(&lts, &partition), quotient_time)
+        }
         Equivalence::BranchingBisim => {
-            let (lts, partition) = branching_bisim_sigref(lts, timing);
+            let (lts, partition, _) = branching_bisim_sigref(lts, timing);
             let quotient_time = timing.start("quotient");
             (quotient_lts_block::<_, true>(&lts, &partition), quotient_time)
         }
         Equivalence::BranchingBisimNaive => {
-            let (lts, partition) = branching_bisim_sigref_naive(lts, timing);
+            let (lts, partition, _) = branching_bisim_sigref_naive(lts, timing);
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_naive(&lts, &partition, true), quotient_time)
+        }
+        Equivalence::BranchingBisimGJ => {
+            let (lts, partition, _) = branching_bisim_gj(lts, timing);
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_block::<_, true>(&lts, &partition), quotient_time)
+        }
+        Equivalence::DpBranchingBisim => {
+            let (lts, partition, _) = dpbranching_bisim_sigref_naive(lts, timing);
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_divergence(&lts, &partition), quotient_time)
+        }
+        Equivalence::Trace => {
+            // Minimising before and after the determinisation keeps the subset construction small.
+            let lts = reduce_lts(lts, Equivalence::StrongBisim, timing);
+            let mut determinise_time = timing.start("determinise");
+            let lts = determinise(&lts, false);
+            determinise_time.finish();
+            return reduce_lts(lts, Equivalence::StrongBisim, timing);
+        }
+        Equivalence::WeakTrace => {
+            let lts = reduce_lts(lts, Equivalence::BranchingBisim, timing);
+            let mut determinise_time = timing.start("determinise");
+            let lts = determinise(&lts, true);
+            determinise_time.finish();
+            return reduce_lts(lts, Equivalence::StrongBisim, timing);
+        }
+        Equivalence::TauStar => {
+            let tau_star_time = timing.start("tau star");
+            (tau_star_reduce(&lts), tau_star_time)
+        }
+        Equivalence::Simulation => {
+            let partition = simulation_preorder(&lts, timing).equivalence();
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_naive(&lts, &partition, false), quotient_time)
+        }
+        Equivalence::ReadySimulation => {
+            let partition = ready_simulation_preorder(&lts, timing).equivalence();
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_naive(&lts, &partition, false), quotient_time)
+        }
+    };
+
+    timer.finish();
+    result
+}
+
+/// Reduces the given LTS modulo the given equivalence, where the signatures are
+/// computed in parallel by the given number of threads.
+///
+/// Only strong and branching bisimulation are supported, for the other
+/// equivalences this falls back to [reduce_lts].
+pub fn reduce_lts_parallel<L: LTS + Sync>(
+    lts: L,
+    equivalence: Equivalence,
+    num_of_threads: usize,
+    timing: &mut Timing,
+) -> LabelledTransitionSystem<L::Label>
+where
+    L::Label: Send + Sync,
+{
+    let (result, mut timer) = match equivalence {
+        Equivalence::StrongBisim | Equivalence::StrongBisimNaive => {
+            let (lts, partition) = strong_bisim_sigref_parallel(lts, num_of_threads, timing);
+            let quotient_time = timing.start("quotient");
+            (quotient_lts_naive(&lts, &partition, false), quotient_time)
+        }
+        Equivalence::BranchingBisim | Equivalence::BranchingBisimNaive => {
+            let (lts, partition, _) = branching_bisim_sigref_parallel(lts, num_of_threads, timing);
             let quotient_time = timing.start("quotient");
             (quotient_lts_naive(&lts, &partition, true), quotient_time)
         }
+        _ => {
+            warn!("Parallel reduction is not supported for {equivalence:?}, using the sequential algorithm");
+            return reduce_lts(lts, equivalence, timing);
+        }
     };
 
     timer.finish();
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_lts::StateLabels;
+    use merc_lts::read_aut;
+    use merc_lts::read_lts;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_reduce_lts_specification() {
+        let lts = read_lts(include_bytes!("../../../examples/lts/abp.lts").as_ref(), Vec::new()).unwrap();
+
+        for equivalence in [
+            Equivalence::BranchingBisim,
+            Equivalence::DpBranchingBisim,
+            Equivalence::WeakTrace,
+            Equivalence::TauStar,
+        ] {
+            let mut timing = Timing::new();
+            let result = reduce_lts(lts.clone(), equivalence, &mut timing);
+
+            assert_eq!(
+                result.specification(),
+                lts.specification(),
+                "The specification should be kept by {equivalence:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reduce_lts_state_labels() {
+        // The states 1 and 2 form a divergent tau-cycle.
+        let lts = read_aut(
+            b"des (0,4,4)\n(0,a,1)\n(1,i,2)\n(2,i,1)\n(2,b,3)\n" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut state_labels = StateLabels::new(vec![("x".to_string(), "Nat".to_string())]);
+        for state_index in lts.iter_states() {
+            state_labels.push(vec![vec![state_index.to_string()]]);
+        }
+        let lts = lts.with_state_labels(state_labels);
+
+        for equivalence in [Equivalence::BranchingBisim, Equivalence::DpBranchingBisim] {
+            let mut timing = Timing::new();
+            let result = reduce_lts(lts.clone(), equivalence, &mut timing);
+
+            let state_labels = result
+                .state_labels()
+                .unwrap_or_else(|| panic!("The state labels should be kept by {equivalence:?}"));
+            assert_eq!(state_labels.len(), result.num_of_states());
+
+            let mut values: Vec<String> = result
+                .iter_states()
+                .flat_map(|state_index| state_labels.values(state_index).iter().flatten().cloned())
+                .collect();
+            values.sort_unstable();
+            assert_eq!(
+                values,
+                ["0", "1", "2", "3"],
+                "Every state should be represented by {equivalence:?}"
+            );
+        }
+    }
+}
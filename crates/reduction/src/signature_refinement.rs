@@ -1,6 +1,9 @@
 use std::mem::swap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use bumpalo::Bump;
+use dashmap::DashMap;
 use log::debug;
 use log::info;
 use log::trace;
@@ -10,6 +13,8 @@ use merc_lts::LTS;
 use merc_lts::LabelIndex;
 use merc_lts::LabelledTransitionSystem;
 use merc_lts::StateIndex;
+use rayon::prelude::*;
+use rustc_hash::FxBuildHasher;
 use rustc_hash::FxHashMap;
 use rustc_hash::FxHashSet;
 
@@ -25,8 +30,11 @@ use crate::SignatureBuilder;
 use crate::branching_bisim_signature;
 use crate::branching_bisim_signature_inductive;
 use crate::branching_bisim_signature_sorted;
+use crate::branching_bisim_signature_sorted_with;
+use crate::dpbranching_bisim_signature_sorted;
 use crate::is_tau_hat;
 use crate::preprocess_branching;
+use crate::preprocess_dpbranching;
 use crate::strong_bisim_signature;
 use crate::weak_bisim_signature_sorted;
 use crate::weak_bisim_signature_sorted_taus;
@@ -62,13 +70,35 @@ pub fn strong_bisim_sigref_naive<L: LTS>(lts: L, timing: &mut Timing) -> (L, Ind
     (lts, partition)
 }
 
+/// Computes a strong bisimulation partitioning using signature refinement,
+/// where the signatures are computed in parallel by the given number of threads.
+///
+/// The resulting partition is identical to the one of [strong_bisim_sigref_naive].
+pub fn strong_bisim_sigref_parallel<L: LTS + Sync>(
+    lts: L,
+    num_of_threads: usize,
+    timing: &mut Timing,
+) -> (L, IndexedPartition) {
+    let mut time = timing.start("reduction");
+    let partition =
+        signature_refinement_parallel::<_, _, false>(&lts, num_of_threads, |state_index, partition, _, builder| {
+            strong_bisim_signature(state_index, &lts, partition, builder);
+        });
+
+    time.finish();
+    (lts, partition)
+}
+
 /// Computes a branching bisimulation partitioning using signature refinement
+///
+/// The returned mapping relates every state of the input LTS to its state in
+/// the preprocessed LTS, see [preprocess_branching].
 pub fn branching_bisim_sigref<L: LTS>(
     lts: L,
     timing: &mut Timing,
-) -> (LabelledTransitionSystem<L::Label>, BlockPartition) {
+) -> (LabelledTransitionSystem<L::Label>, BlockPartition, IndexedPartition) {
     let mut timepre = timing.start("preprocess");
-    let preprocessed_lts = preprocess_branching(lts);
+    let (preprocessed_lts, mapping) = preprocess_branching(lts);
     let incoming = IncomingTransitions::new(&preprocessed_lts);
     timepre.finish();
 
@@ -123,17 +153,19 @@ pub fn branching_bisim_sigref<L: LTS>(
 
     time.finish();
 
-    // Combine the SCC partition with the branching bisimulation partition.
-    (preprocessed_lts, partition)
+    (preprocessed_lts, partition, mapping)
 }
 
 /// Computes a branching bisimulation partitioning using signature refinement without dirty blocks.
+///
+/// The returned mapping relates every state of the input LTS to its state in
+/// the preprocessed LTS, see [preprocess_branching].
 pub fn branching_bisim_sigref_naive<L: LTS>(
     lts: L,
     timing: &mut Timing,
-) -> (LabelledTransitionSystem<L::Label>, IndexedPartition) {
+) -> (LabelledTransitionSystem<L::Label>, IndexedPartition, IndexedPartition) {
     let mut timepre = timing.start("preprocess");
-    let preprocessed_lts = preprocess_branching(lts);
+    let (preprocessed_lts, mapping) = preprocess_branching(lts);
     timepre.finish();
 
     let mut time = timing.start("reduction");
@@ -169,16 +201,83 @@ pub fn branching_bisim_sigref_naive<L: LTS>(
     );
     time.finish();
 
-    (preprocessed_lts, partition)
+    (preprocessed_lts, partition, mapping)
+}
+
+/// Computes a branching bisimulation partitioning using signature refinement,
+/// where the signatures are computed in parallel by the given number of threads.
+///
+/// The resulting partition is identical to the one of
+/// [branching_bisim_sigref_naive], and the returned mapping relates every state
+/// of the input LTS to its state in the preprocessed LTS, see
+/// [preprocess_branching].
+pub fn branching_bisim_sigref_parallel<L: LTS>(
+    lts: L,
+    num_of_threads: usize,
+    timing: &mut Timing,
+) -> (LabelledTransitionSystem<L::Label>, IndexedPartition, IndexedPartition)
+where
+    L::Label: Send + Sync,
+{
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, mapping) = preprocess_branching(lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let partition = signature_refinement_parallel::<_, _, true>(
+        &preprocessed_lts,
+        num_of_threads,
+        |state_index, partition, signatures, builder| {
+            branching_bisim_signature_sorted_with(
+                state_index,
+                &preprocessed_lts,
+                partition,
+                |state_index| signatures.get(state_index),
+                builder,
+            );
+        },
+    );
+    time.finish();
+
+    (preprocessed_lts, partition, mapping)
+}
+
+/// Computes a divergence-preserving branching bisimulation partitioning using
+/// signature refinement without dirty blocks.
+///
+/// The returned LTS contains a tau self-loop for every divergent state, and the
+/// returned mapping relates every state of the input LTS to its state in the
+/// preprocessed LTS, see [preprocess_dpbranching].
+pub fn dpbranching_bisim_sigref_naive<L: LTS>(
+    lts: L,
+    timing: &mut Timing,
+) -> (LabelledTransitionSystem<L::Label>, IndexedPartition, IndexedPartition) {
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, mapping) = preprocess_dpbranching(lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let partition = signature_refinement_naive::<_, _, false>(
+        &preprocessed_lts,
+        |state_index, partition, state_to_signature, builder| {
+            dpbranching_bisim_signature_sorted(state_index, &preprocessed_lts, partition, state_to_signature, builder);
+        },
+    );
+    time.finish();
+
+    (preprocessed_lts, partition, mapping)
 }
 
 /// Computes a branching bisimulation partitioning using signature refinement without dirty blocks.
+///
+/// The returned mapping relates every state of the input LTS to its state in
+/// the preprocessed LTS, see [preprocess_branching].
 pub fn weak_bisim_sigref_naive<L: LTS>(
     lts: L,
     timing: &mut Timing,
-) -> (LabelledTransitionSystem<L::Label>, IndexedPartition) {
+) -> (LabelledTransitionSystem<L::Label>, IndexedPartition, IndexedPartition) {
     let mut timepre = timing.start("preprocess");
-    let preprocessed_lts = preprocess_branching(lts);
+    let (preprocessed_lts, mapping) = preprocess_branching(lts);
     timepre.finish();
 
     let mut time = timing.start("reduction");
@@ -191,7 +290,7 @@ pub fn weak_bisim_sigref_naive<L: LTS>(
     );
     time.finish();
 
-    (preprocessed_lts, partition)
+    (preprocessed_lts, partition, mapping)
 }
 
 /// General signature refinement algorithm that accepts an arbitrary signature
@@ -463,6 +562,211 @@ where
     partition
 }
 
+/// General signature refinement algorithm where the signatures of all states
+/// are computed in parallel, and inserted into a shared signature table.
+///
+/// For branching signatures the states are processed level by level, where the
+/// level of a state is one more than the maximum level of its inert tau
+/// successors. The signatures of all states in a level, also within the same
+/// block, are computed in parallel since the signatures of their inert tau
+/// successors are available, see [branching_bisim_signature_sorted]. The blocks
+/// are numbered in the order in which their first state occurs, such that the
+/// resulting partition does not depend on the scheduling of the threads and is
+/// identical to the partition computed by [signature_refinement_naive].
+fn signature_refinement_parallel<L, F, const BRANCHING: bool>(
+    lts: &L,
+    num_of_threads: usize,
+    signature: F,
+) -> IndexedPartition
+where
+    L: LTS + Sync,
+    F: Fn(StateIndex, &IndexedPartition, &ComputedSignatures, &mut SignatureBuilder) + Sync,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_of_threads)
+        .build()
+        .expect("Failed to create the thread pool");
+
+    let mut partition = IndexedPartition::new(lts.num_of_states());
+    let mut next_partition = IndexedPartition::new(lts.num_of_states());
+
+    // Maps every signature to a unique key, which depends on the scheduling of the threads.
+    let table: DashMap<SignatureBuilder, usize, FxBuildHasher> = DashMap::with_hasher(FxBuildHasher);
+    let num_of_keys = AtomicUsize::new(0);
+    let mut state_to_key = vec![0usize; lts.num_of_states()];
+    let mut key_to_block: Vec<Option<BlockIndex>> = Vec::new();
+
+    // (branching) The level of every state, the states ordered by their level, the position of every state in this
+    // order, and the offsets at which the levels start.
+    let mut level = vec![0usize; lts.num_of_states()];
+    let mut order = vec![StateIndex::new(0); lts.num_of_states()];
+    let mut position = vec![0usize; lts.num_of_states()];
+    let mut level_offsets: Vec<usize> = Vec::new();
+
+    // (branching) The signatures and keys of the states, in the order of their positions.
+    let mut signatures: Vec<SignatureBuilder> = Vec::new();
+    let mut position_to_key: Vec<usize> = Vec::new();
+    if BRANCHING {
+        signatures.resize_with(lts.num_of_states(), SignatureBuilder::default);
+        position_to_key.resize(lts.num_of_states(), 0);
+    }
+
+    // Refine partitions until stable.
+    let mut old_count = 1;
+    let mut num_of_blocks = 0;
+    let mut iteration = 0;
+
+    let progress = TimeProgress::new(
+        |(iteration, blocks)| {
+            debug!("Iteration {iteration}, found {blocks} blocks...",);
+        },
+        5,
+    );
+
+    // Returns the unique key of the given signature.
+    let key = |builder: &SignatureBuilder| {
+        if let Some(key) = table.get(builder.as_slice()) {
+            *key
+        } else {
+            *table
+                .entry(builder.clone())
+                .or_insert_with(|| num_of_keys.fetch_add(1, Ordering::Relaxed))
+        }
+    };
+
+    while old_count != num_of_blocks {
+        old_count = num_of_blocks;
+        progress.print((iteration, old_count));
+        swap(&mut partition, &mut next_partition);
+
+        table.clear();
+        num_of_keys.store(0, Ordering::Relaxed);
+
+        if BRANCHING {
+            // The inert tau successors of a state have a lower index, since the LTS is sorted topologically.
+            level_offsets.clear();
+            for state_index in lts.iter_states() {
+                level[state_index] = 0;
+                for transition in lts.outgoing_transitions(state_index) {
+                    if lts.is_hidden_label(transition.label)
+                        && partition.block_number(state_index) == partition.block_number(transition.to)
+                    {
+                        debug_assert!(
+                            transition.to < state_index,
+                            "The LTS must be sorted topologically, but {state_index} has inert successor {}",
+                            transition.to
+                        );
+                        level[state_index] = level[state_index].max(level[transition.to] + 1);
+                    }
+                }
+
+                if level_offsets.len() <= level[state_index] + 1 {
+                    level_offsets.resize(level[state_index] + 2, 0);
+                }
+                level_offsets[level[state_index] + 1] += 1;
+            }
+
+            // Order the states by their level, where the states within a level remain in increasing order.
+            for index in 1..level_offsets.len() {
+                level_offsets[index] += level_offsets[index - 1];
+            }
+
+            let mut next_position = level_offsets.clone();
+            for state_index in lts.iter_states() {
+                let state_position = &mut next_position[level[state_index]];
+                position[state_index] = *state_position;
+                order[*state_position] = state_index;
+                *state_position += 1;
+            }
+
+            for bounds in level_offsets.windows(2) {
+                let (computed, remaining) = signatures.split_at_mut(bounds[0]);
+                let computed = ComputedSignatures::new(&position, computed);
+
+                pool.install(|| {
+                    remaining[..bounds[1] - bounds[0]]
+                        .par_iter_mut()
+                        .zip(&mut position_to_key[bounds[0]..bounds[1]])
+                        .zip(order[bounds[0]..bounds[1]].par_iter())
+                        .for_each(|((builder, state_key), state_index)| {
+                            signature(*state_index, &partition, &computed, builder);
+                            *state_key = key(builder);
+                        })
+                });
+            }
+
+            for (state_index, key) in state_to_key.iter_mut().enumerate() {
+                *key = position_to_key[position[state_index]];
+            }
+        } else {
+            pool.install(|| {
+                state_to_key.par_iter_mut().enumerate().for_each_init(
+                    SignatureBuilder::default,
+                    |builder, (state_index, state_key)| {
+                        signature(
+                            StateIndex::new(state_index),
+                            &partition,
+                            &ComputedSignatures::new(&position, &[]),
+                            builder,
+                        );
+                        *state_key = key(builder);
+                    },
+                )
+            });
+        }
+
+        // Number the blocks in the order of their first state.
+        key_to_block.clear();
+        key_to_block.resize(num_of_keys.load(Ordering::Relaxed), None);
+        num_of_blocks = 0;
+
+        for (state_index, key) in state_to_key.iter().enumerate() {
+            let block = *key_to_block[*key].get_or_insert_with(|| {
+                num_of_blocks += 1;
+                BlockIndex::new(num_of_blocks - 1)
+            });
+
+            next_partition.set_block(StateIndex::new(state_index), block);
+        }
+
+        iteration += 1;
+
+        debug_assert!(
+            iteration <= lts.num_of_states().max(2),
+            "There can never be more splits than number of states, but at least two iterations for stability"
+        );
+    }
+
+    trace!("Refinement partition {partition}");
+    partition
+}
+
+/// The signatures of the states in the lower levels that have been computed in
+/// the current iteration of [signature_refinement_parallel].
+struct ComputedSignatures<'a> {
+    /// The position of every state in the order of the levels.
+    position: &'a [usize],
+
+    /// The signatures of the states in the order of their positions.
+    signatures: &'a [SignatureBuilder],
+}
+
+impl<'a> ComputedSignatures<'a> {
+    fn new(position: &'a [usize], signatures: &'a [SignatureBuilder]) -> Self {
+        Self { position, signatures }
+    }
+
+    /// Returns the signature of the given state, which must be in a lower level.
+    fn get(&self, state_index: StateIndex) -> &'a [(LabelIndex, BlockIndex)] {
+        let position = self.position[state_index];
+        debug_assert!(
+            position < self.signatures.len(),
+            "The signature of state {state_index} has not been computed yet"
+        );
+        &self.signatures[position]
+    }
+}
+
 /// Returns true iff the given partition is a strong bisimulation partition
 pub fn is_valid_refinement<F, P>(lts: &impl LTS, partition: &P, mut compute_signature: F) -> bool
 where
@@ -522,6 +826,7 @@ mod tests {
     use test_log::test;
 
     use merc_lts::random_lts;
+    use merc_lts::read_aut;
     use merc_utilities::Timing;
     use merc_utilities::random_test;
 
@@ -590,8 +895,8 @@ mod tests {
             let lts = random_lts(rng, 10, 3, 3);
             let mut timing = Timing::new();
 
-            let (_result_lts, result_partition) = branching_bisim_sigref(lts.clone(), &mut timing);
-            let (_expected_lts, expected_partition) = branching_bisim_sigref_naive(lts, &mut timing);
+            let (_result_lts, result_partition, _) = branching_bisim_sigref(lts.clone(), &mut timing);
+            let (_expected_lts, expected_partition, _) = branching_bisim_sigref_naive(lts, &mut timing);
 
             // There is no preprocessing so this works.
             assert!(equal_partitions(&result_partition, &expected_partition));
@@ -622,7 +927,7 @@ mod tests {
             let lts = random_lts(rng, 10, 3, 3);
             let mut timing = Timing::new();
 
-            let (preprocessed_lts, branching_partition) = branching_bisim_sigref_naive(lts, &mut timing);
+            let (preprocessed_lts, branching_partition, _) = branching_bisim_sigref_naive(lts, &mut timing);
             let strong_partition = strong_bisim_sigref_naive(preprocessed_lts.clone(), &mut timing).1;
             is_refinement(&preprocessed_lts, &strong_partition, &branching_partition);
         });
@@ -635,9 +940,68 @@ mod tests {
             let lts = random_lts(rng, 10, 3, 3);
             let mut timing = Timing::new();
 
-            let (preprocessed_lts, weak_partition) = weak_bisim_sigref_naive(lts, &mut timing);
+            let (preprocessed_lts, weak_partition, _) = weak_bisim_sigref_naive(lts, &mut timing);
             let strong_partition = strong_bisim_sigref_naive(preprocessed_lts.clone(), &mut timing).1;
             is_refinement(&preprocessed_lts, &strong_partition, &weak_partition);
         });
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri is too slow
+    fn test_random_dpbranching_bisim_sigref_naive() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+            let mut timing = Timing::new();
+
+            let (preprocessed_lts, dpbranching_partition, _) = dpbranching_bisim_sigref_naive(lts.clone(), &mut timing);
+            let strong_partition = strong_bisim_sigref_naive(preprocessed_lts.clone(), &mut timing).1;
+            is_refinement(&preprocessed_lts, &strong_partition, &dpbranching_partition);
+
+            // Both preprocessings collapse the same strongly connected components.
+            let (_, branching_partition, _) = branching_bisim_sigref_naive(lts, &mut timing);
+            is_refinement(&preprocessed_lts, &dpbranching_partition, &branching_partition);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri is too slow
+    fn test_random_sigref_parallel() {
+        random_test(100, |rng| {
+            let lts = random_lts(rng, 10, 3, 3);
+            let mut timing = Timing::new();
+
+            let (_, result_partition) = strong_bisim_sigref_parallel(lts.clone(), 4, &mut timing);
+            let (_, expected_partition) = strong_bisim_sigref_naive(lts.clone(), &mut timing);
+            assert!(result_partition.iter().eq(expected_partition.iter()));
+
+            let (_, result_partition, _) = branching_bisim_sigref_parallel(lts.clone(), 4, &mut timing);
+            let (_, expected_partition, _) = branching_bisim_sigref_naive(lts, &mut timing);
+            assert!(result_partition.iter().eq(expected_partition.iter()));
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_sigref_parallel_single_block() {
+        // A binary tree of tau transitions where every state has an a-loop, so all states are in a single block.
+        let num_of_states = 1000;
+        let mut aut = format!("des (0,{},{})\n", 2 * num_of_states - 1, num_of_states);
+        for state in 0..num_of_states {
+            aut.push_str(&format!("({state},a,{state})\n"));
+            if state > 0 {
+                aut.push_str(&format!("({state},i,{})\n", (state - 1) / 2));
+            }
+        }
+
+        let lts = read_aut(aut.as_bytes(), Vec::new()).unwrap();
+        let mut timing = Timing::new();
+
+        let (_, expected_partition, _) = branching_bisim_sigref_parallel(lts.clone(), 1, &mut timing);
+        assert_eq!(expected_partition.num_of_blocks(), 1);
+
+        for num_of_threads in [2, 4, 8] {
+            let (_, result_partition, _) = branching_bisim_sigref_parallel(lts.clone(), num_of_threads, &mut timing);
+            assert!(result_partition.iter().eq(expected_partition.iter()));
+        }
+    }
 }
@@ -11,8 +11,10 @@ use merc_lts::StateIndex;
 use rustc_hash::FxHashSet;
 
 use crate::BlockIndex;
+use crate::IndexedPartition;
 use crate::Partition;
 use crate::quotient_lts_naive;
+use crate::reorder_partition;
 
 use super::BlockPartition;
 use super::sort_topological;
@@ -181,6 +183,25 @@ pub fn branching_bisim_signature_sorted(
     partition: &impl Partition,
     state_to_signature: &[Signature],
     builder: &mut SignatureBuilder,
+) {
+    branching_bisim_signature_sorted_with(
+        state_index,
+        lts,
+        partition,
+        |state_index| state_to_signature[state_index].as_slice(),
+        builder,
+    );
+}
+
+/// The same as [branching_bisim_signature_sorted], but the signatures of the
+/// inert tau successors, which have already been computed, are obtained by
+/// `signature_of`.
+pub fn branching_bisim_signature_sorted_with<'a>(
+    state_index: StateIndex,
+    lts: &impl LTS,
+    partition: &impl Partition,
+    signature_of: impl Fn(StateIndex) -> &'a [(LabelIndex, BlockIndex)],
+    builder: &mut SignatureBuilder,
 ) {
     builder.clear();
 
@@ -190,7 +211,7 @@ pub fn branching_bisim_signature_sorted(
         if partition.block_number(state_index) == to_block {
             if lts.is_hidden_label(transition.label) {
                 // Inert tau transition, take signature from the outgoing tau-transition.
-                builder.extend(state_to_signature[transition.to].as_slice());
+                builder.extend(signature_of(transition.to));
             } else {
                 builder.push((transition.label, to_block));
             }
@@ -205,6 +226,34 @@ pub fn branching_bisim_signature_sorted(
     builder.dedup();
 }
 
+/// The signature for divergence-preserving branching bisimulation, where a
+/// state is divergent iff it can reach a tau self-loop by inert tau-steps.
+///
+/// ```plain
+///     sig(s, pi) = sig_branching(s, pi) U { (tau, pi(s)) | s is divergent }
+/// ```
+///
+/// Assumes that the input LTS is preprocessed by [preprocess_dpbranching].
+pub fn dpbranching_bisim_signature_sorted(
+    state_index: StateIndex,
+    lts: &impl LTS,
+    partition: &impl Partition,
+    state_to_signature: &[Signature],
+    builder: &mut SignatureBuilder,
+) {
+    // The signature of inert tau successors is included, so the divergence of these states is inherited.
+    branching_bisim_signature_sorted(state_index, lts, partition, state_to_signature, builder);
+
+    if let Some(transition) = lts
+        .outgoing_transitions(state_index)
+        .find(|transition| lts.is_hidden_label(transition.label) && transition.to == state_index)
+    {
+        builder.push((transition.label, partition.block_number(state_index)));
+        builder.sort_unstable();
+        builder.dedup();
+    }
+}
+
 /// The inductive version of [branching_bisim_signature_sorted]. Assumes that
 /// the input LTS has no tau-cycles, and is topologically sorted.
 pub fn branching_bisim_signature_inductive(
@@ -298,8 +347,46 @@ pub fn weak_bisim_signature_sorted_taus(
 
 /// Perform the preprocessing necessary for branching bisimulation with the
 /// sorted signature see [branching_bisim_signature_sorted].
-pub fn preprocess_branching<L: LTS>(lts: L) -> LabelledTransitionSystem<L::Label> {
+///
+/// Returns the preprocessed LTS and a partition that maps every state of the
+/// input LTS to its corresponding state in the preprocessed LTS.
+pub fn preprocess_branching<L: LTS>(lts: L) -> (LabelledTransitionSystem<L::Label>, IndexedPartition) {
+    preprocess_branching_impl::<L, false>(lts)
+}
+
+/// The same as [preprocess_branching], but every state of the preprocessed LTS
+/// that corresponds to a divergent tau-strongly connected component, i.e., one
+/// that contains a tau-cycle, obtains a tau self-loop.
+pub fn preprocess_dpbranching<L: LTS>(lts: L) -> (LabelledTransitionSystem<L::Label>, IndexedPartition) {
+    preprocess_branching_impl::<L, true>(lts)
+}
+
+fn preprocess_branching_impl<L: LTS, const DIVERGENCE: bool>(
+    lts: L,
+) -> (LabelledTransitionSystem<L::Label>, IndexedPartition) {
     let scc_partition = tau_scc_decomposition(&lts);
+
+    // A component is divergent if it consists of multiple states, or a single state with a tau self-loop.
+    let mut divergent = vec![false; if DIVERGENCE { scc_partition.num_of_blocks() } else { 0 }];
+    if DIVERGENCE {
+        let mut size = vec![0usize; scc_partition.num_of_blocks()];
+        for state_index in lts.iter_states() {
+            let block = scc_partition.block_number(state_index);
+            size[block] += 1;
+
+            if lts
+                .outgoing_transitions(state_index)
+                .any(|transition| lts.is_hidden_label(transition.label) && transition.to == state_index)
+            {
+                divergent[block] = true;
+            }
+        }
+
+        for (block, size) in size.iter().enumerate() {
+            divergent[block] |= *size > 1;
+        }
+    }
+
     let tau_loop_free_lts = quotient_lts_naive(&lts, &scc_partition, true);
     drop(lts);
 
@@ -311,5 +398,52 @@ pub fn preprocess_branching<L: LTS>(lts: L) -> LabelledTransitionSystem<L::Label
     )
     .expect("After quotienting, the LTS should not contain cycles");
 
-    LabelledTransitionSystem::new_from_permutation(tau_loop_free_lts, |i| topological_permutation[i])
+    let mapping = reorder_partition(scc_partition, |block| {
+        BlockIndex::new(*topological_permutation[StateIndex::new(*block)])
+    });
+    let sorted_lts = LabelledTransitionSystem::new_from_permutation(tau_loop_free_lts, |i| topological_permutation[i]);
+
+    if DIVERGENCE {
+        // Add the tau self-loops to the divergent states.
+        let divergent_states: Vec<StateIndex> = divergent
+            .iter()
+            .enumerate()
+            .filter(|(_, divergent)| **divergent)
+            .map(|(block, _)| topological_permutation[block])
+            .collect();
+
+        let tau = LabelIndex::new(0);
+        let mut divergent_lts = LabelledTransitionSystem::new(
+            sorted_lts.initial_state_index(),
+            Some(sorted_lts.num_of_states()),
+            || {
+                sorted_lts
+                    .iter_states()
+                    .flat_map(|state_index| {
+                        sorted_lts
+                            .outgoing_transitions(state_index)
+                            .map(move |transition| (state_index, transition.label, transition.to))
+                    })
+                    .chain(
+                        divergent_states
+                            .iter()
+                            .map(|state_index| (*state_index, tau, *state_index)),
+                    )
+            },
+            sorted_lts.labels().to_vec(),
+        );
+
+        // The states are unchanged, so the state labels and the specification are kept.
+        if let Some(state_labels) = sorted_lts.state_labels() {
+            divergent_lts = divergent_lts.with_state_labels(state_labels.clone());
+        }
+
+        if let Some(specification) = sorted_lts.specification() {
+            divergent_lts = divergent_lts.with_specification(specification.clone());
+        }
+
+        (divergent_lts, mapping)
+    } else {
+        (sorted_lts, mapping)
+    }
 }
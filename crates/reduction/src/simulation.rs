@@ -0,0 +1,437 @@
+//! Implements the partition-relation based algorithm for the simulation
+//! preorder, following the approach of Gentilini, Piazza and Policriti.
+#![forbid(unsafe_code)]
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use bitvec::vec::BitVec;
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+
+use merc_io::TimeProgress;
+use merc_lts::IncomingTransitions;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::StateIndex;
+use merc_utilities::Timing;
+
+use crate::BlockIndex;
+use crate::BlockPartition;
+use crate::BlockPartitionBuilder;
+use crate::IndexedPartition;
+use crate::Partition;
+
+/// Type alias because we use bitvec for the rows of the relation.
+type BitArray = BitVec<u64, Lsb0>;
+
+/// A preorder on the states of an LTS that is represented by a partition of
+/// the states together with a relation on its blocks. A state `s` is related
+/// to `t` iff the block of `s` is related to the block of `t`.
+pub struct PartitionRelation {
+    partition: BlockPartition,
+
+    /// For every block the set of blocks that it is related to.
+    relation: Vec<BitArray>,
+}
+
+impl PartitionRelation {
+    /// Returns the partition of the states.
+    pub fn partition(&self) -> &BlockPartition {
+        &self.partition
+    }
+
+    /// Returns true iff the state `left` is related to (is simulated by) the state `right`.
+    pub fn related(&self, left: StateIndex, right: StateIndex) -> bool {
+        self.is_block_related(self.partition.block_number(left), self.partition.block_number(right))
+    }
+
+    /// Returns the partition induced by the kernel of the preorder, i.e., the
+    /// states that are related in both directions are in the same block.
+    pub fn equivalence(&self) -> IndexedPartition {
+        let mut block_to_class: Vec<Option<BlockIndex>> = vec![None; self.partition.num_of_blocks()];
+        let mut num_of_classes = 0;
+
+        for block_index in (0..self.partition.num_of_blocks()).map(BlockIndex::new) {
+            if block_to_class[block_index].is_some() {
+                continue;
+            }
+
+            // All blocks that are related in both directions form a single class.
+            let class = BlockIndex::new(num_of_classes);
+            num_of_classes += 1;
+            for other_block_index in self.relation[block_index].iter_ones().map(BlockIndex::new) {
+                if self.is_block_related(other_block_index, block_index) {
+                    block_to_class[other_block_index] = Some(class);
+                }
+            }
+        }
+
+        let mut result = IndexedPartition::new(self.partition.len());
+        for state_index in (0..self.partition.len()).map(StateIndex::new) {
+            let class =
+                block_to_class[self.partition.block_number(state_index)].expect("Every block is assigned to a class");
+            result.set_block(state_index, class);
+        }
+
+        result
+    }
+
+    /// Returns true iff the given blocks are related.
+    fn is_block_related(&self, left: BlockIndex, right: BlockIndex) -> bool {
+        self.relation[left][right.value()]
+    }
+
+    /// Removes the given pair from the relation.
+    fn remove(&mut self, left: BlockIndex, right: BlockIndex) {
+        self.relation[left].set(right.value(), false);
+    }
+
+    /// Extends the relation for blocks that have been split off from the given
+    /// block, which inherit its related blocks in both directions.
+    fn split(&mut self, block_index: BlockIndex, new_block_index: BlockIndex) {
+        debug_assert_eq!(
+            new_block_index.value(),
+            self.relation.len(),
+            "Blocks are split off in order"
+        );
+
+        let row = self.relation[block_index].clone();
+        self.relation.push(row);
+
+        for row in &mut self.relation {
+            let related = row[block_index.value()];
+            row.push(related);
+        }
+    }
+}
+
+/// Computes the simulation preorder on the states of the given LTS, where
+/// internal actions are treated as visible actions.
+pub fn simulation_preorder<L: LTS>(lts: &L, timing: &mut Timing) -> PartitionRelation {
+    partition_relation::<L, false>(lts, timing)
+}
+
+/// Computes the ready simulation preorder on the states of the given LTS,
+/// where related states must moreover enable the same actions.
+pub fn ready_simulation_preorder<L: LTS>(lts: &L, timing: &mut Timing) -> PartitionRelation {
+    partition_relation::<L, true>(lts, timing)
+}
+
+/// Computes the greatest (ready) simulation by refining a partition-relation
+/// pair until it is stable.
+///
+/// Invariant: the (ready) simulation preorder is included in the relation.
+///
+/// For every label `a` and block `C`, the predecessors of `C` and the
+/// predecessors of the blocks related to `C` are computed. The partition is
+/// split such that every block is either included in or disjoint from these
+/// sets. Afterwards, a block `B` that can reach `C` can no longer be simulated
+/// by a block `D` that cannot reach a block simulating `C`.
+///
+/// This requires O(|Act| * k * (n + m + k^2)) time per iteration, where `k` is
+/// the number of blocks in the result.
+fn partition_relation<L: LTS, const READY: bool>(lts: &L, timing: &mut Timing) -> PartitionRelation {
+    let mut time = timing.start("reduction");
+    let incoming = IncomingTransitions::new(lts);
+
+    // The initial partition groups the states by their enabled actions.
+    let mut partition = BlockPartition::new(lts.num_of_states());
+    let mut builder = BlockPartitionBuilder::default();
+    let mut enabled_to_block: FxHashMap<Vec<LabelIndex>, BlockIndex> = FxHashMap::default();
+    let _ = partition.partition_marked_with(BlockIndex::new(0), &mut builder, |state_index, _| {
+        let num_of_blocks = enabled_to_block.len();
+        *enabled_to_block
+            .entry(enabled(lts, state_index))
+            .or_insert(BlockIndex::new(num_of_blocks))
+    });
+
+    // Initially, a block is related to the blocks that enable (at least) the same actions.
+    let block_enabled: Vec<Vec<LabelIndex>> = (0..partition.num_of_blocks())
+        .map(|block_index| {
+            let state_index = partition
+                .iter_block(BlockIndex::new(block_index))
+                .next()
+                .expect("Blocks are not empty");
+            enabled(lts, state_index)
+        })
+        .collect();
+
+    let mut relation = PartitionRelation {
+        relation: block_enabled
+            .iter()
+            .map(|left| {
+                block_enabled
+                    .iter()
+                    .map(|right| {
+                        if READY {
+                            left == right
+                        } else {
+                            left.iter().all(|label| right.contains(label))
+                        }
+                    })
+                    .collect()
+            })
+            .collect(),
+        partition,
+    };
+
+    let progress = TimeProgress::new(
+        |num_of_blocks: usize| {
+            debug!("Found {} blocks...", num_of_blocks);
+        },
+        1,
+    );
+
+    // Keeps track of the states in pre_a(C) and in pre_a(rel(C)) respectively.
+    let mut predecessors = bitvec![u64, Lsb0; 0; lts.num_of_states()];
+    let mut related_predecessors = bitvec![u64, Lsb0; 0; lts.num_of_states()];
+    let mut marked_states = Vec::new();
+    let mut representatives = Vec::new();
+
+    let mut iteration = 0;
+    loop {
+        let mut stable = true;
+
+        for label in (0..lts.num_of_labels()).map(LabelIndex::new) {
+            let mut block_index = BlockIndex::new(0);
+            while block_index.value() < relation.partition.num_of_blocks() {
+                progress.print(relation.partition.num_of_blocks());
+
+                // Compute pre_a(rel(C)) and pre_a(C).
+                predecessors.fill(false);
+                related_predecessors.fill(false);
+                for related_block_index in relation.relation[block_index].iter_ones().map(BlockIndex::new) {
+                    for state_index in relation.partition.iter_block(related_block_index) {
+                        for transition in incoming.incoming_transitions(state_index) {
+                            if transition.label == label {
+                                related_predecessors.set(*transition.to, true);
+                                if related_block_index == block_index {
+                                    predecessors.set(*transition.to, true);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Split the blocks such that they are either included in, or disjoint from, both sets.
+                for states in [&related_predecessors, &predecessors] {
+                    marked_states.clear();
+                    marked_states.extend(states.iter_ones().map(StateIndex::new));
+
+                    if split(&mut relation, &mut builder, &marked_states, &mut representatives) {
+                        stable = false;
+                    }
+                }
+
+                // Remove the pairs (B, D) where B is included in pre_a(C) and D is disjoint from pre_a(rel(C)).
+                for state_index in representatives.drain(..) {
+                    let block = relation.partition.block_number(state_index);
+                    for other_block in relation.relation[block].clone().iter_ones().map(BlockIndex::new) {
+                        let other_state_index = relation
+                            .partition
+                            .iter_block(other_block)
+                            .next()
+                            .expect("Blocks are not empty");
+
+                        if !related_predecessors[*other_state_index] {
+                            trace!("Block {other_block} no longer simulates block {block}");
+                            relation.remove(block, other_block);
+                            stable = false;
+                        }
+                    }
+                }
+
+                block_index = BlockIndex::new(block_index.value() + 1);
+            }
+        }
+
+        iteration += 1;
+        if stable {
+            break;
+        }
+    }
+
+    debug!(
+        "Simulation preorder has {} blocks after {} iterations",
+        relation.partition.num_of_blocks(),
+        iteration
+    );
+    time.finish();
+    relation
+}
+
+/// Splits every block that contains one of the given states into the part
+/// with and without these states. Returns true iff a new block was introduced.
+///
+/// For every block that is included in the given states afterwards, a
+/// representative state is stored in `representatives`.
+fn split(
+    relation: &mut PartitionRelation,
+    builder: &mut BlockPartitionBuilder,
+    states: &[StateIndex],
+    representatives: &mut Vec<StateIndex>,
+) -> bool {
+    representatives.clear();
+    for state_index in states {
+        if !relation
+            .partition
+            .block(relation.partition.block_number(*state_index))
+            .has_marked()
+        {
+            representatives.push(*state_index);
+        }
+
+        relation.partition.mark_element(*state_index);
+    }
+
+    let mut changed = false;
+    for state_index in representatives.iter() {
+        let block_index = relation.partition.block_number(*state_index);
+        let old_num_of_blocks = relation.partition.num_of_blocks();
+
+        // The marked states are split off from the unmarked states.
+        let _ = relation
+            .partition
+            .partition_marked_with(block_index, builder, |_, _| BlockIndex::new(0));
+
+        // The new blocks inherit the relation of the original block.
+        for new_block_index in (old_num_of_blocks..relation.partition.num_of_blocks()).map(BlockIndex::new) {
+            relation.split(block_index, new_block_index);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Returns the sorted list of actions enabled in the given state.
+fn enabled<L: LTS>(lts: &L, state_index: StateIndex) -> Vec<LabelIndex> {
+    let mut result: Vec<LabelIndex> = lts
+        .outgoing_transitions(state_index)
+        .map(|transition| transition.label)
+        .collect();
+    result.sort_unstable();
+    result.dedup();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_io::DumpFiles;
+    use merc_lts::random_lts;
+    use merc_lts::read_aut;
+    use merc_lts::write_aut;
+    use merc_utilities::random_test;
+
+    use crate::Equivalence;
+    use crate::compare_lts;
+    use crate::reduce_lts;
+
+    /// Computes the greatest (ready) simulation by a naive fixed point iteration on pairs of states.
+    fn naive_simulation<L: LTS>(lts: &L, ready: bool) -> Vec<Vec<bool>> {
+        let mut relation: Vec<Vec<bool>> = lts
+            .iter_states()
+            .map(|state_index| {
+                lts.iter_states()
+                    .map(|other_state_index| !ready || enabled(lts, state_index) == enabled(lts, other_state_index))
+                    .collect()
+            })
+            .collect();
+
+        let mut stable = false;
+        while !stable {
+            stable = true;
+            for state_index in lts.iter_states() {
+                for other_state_index in lts.iter_states() {
+                    if relation[state_index][other_state_index]
+                        && !lts.outgoing_transitions(state_index).all(|transition| {
+                            lts.outgoing_transitions(other_state_index).any(|other_transition| {
+                                other_transition.label == transition.label
+                                    && relation[transition.to][other_transition.to]
+                            })
+                        })
+                    {
+                        relation[state_index][other_state_index] = false;
+                        stable = false;
+                    }
+                }
+            }
+        }
+
+        relation
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_simulation_preorder() {
+        random_test(100, |rng| {
+            let mut files = DumpFiles::new("test_random_simulation_preorder");
+
+            let lts = random_lts(rng, 10, 3, 3);
+            files.dump("input.aut", |w| write_aut(w, &lts)).unwrap();
+            let mut timing = Timing::new();
+
+            for ready in [false, true] {
+                let relation = if ready {
+                    ready_simulation_preorder(&lts, &mut timing)
+                } else {
+                    simulation_preorder(&lts, &mut timing)
+                };
+                let expected = naive_simulation(&lts, ready);
+
+                for state_index in lts.iter_states() {
+                    for other_state_index in lts.iter_states() {
+                        assert_eq!(
+                            relation.related(state_index, other_state_index),
+                            expected[state_index][other_state_index],
+                            "States {state_index} and {other_state_index} are incorrectly related (ready: {ready})"
+                        );
+                    }
+                }
+            }
+
+            // The quotient modulo simulation equivalence is simulation equivalent to the original.
+            for equivalence in [Equivalence::Simulation, Equivalence::ReadySimulation] {
+                let reduced_lts = reduce_lts(lts.clone(), equivalence, &mut timing);
+                files.dump("reduced.aut", |w| write_aut(w, &reduced_lts)).unwrap();
+                assert!(compare_lts(equivalence, reduced_lts, lts.clone(), &mut timing));
+            }
+        });
+    }
+
+    #[test]
+    fn test_simulation_preorder() {
+        let lts = read_aut(
+            b"des (0,7,10)
+(0,a,1)
+(1,b,2)
+(3,a,4)
+(3,a,5)
+(4,b,6)
+(7,a,8)
+(8,b,9)
+(8,c,9)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let mut timing = Timing::new();
+        let relation = simulation_preorder(&lts, &mut timing);
+        assert!(relation.related(StateIndex::new(0), StateIndex::new(3)));
+        assert!(relation.related(StateIndex::new(3), StateIndex::new(0)));
+        assert!(relation.related(StateIndex::new(0), StateIndex::new(7)));
+        assert!(!relation.related(StateIndex::new(7), StateIndex::new(0)));
+
+        // State 3 can refuse b after an a-action, and state 7 can perform c after an a-action.
+        let relation = ready_simulation_preorder(&lts, &mut timing);
+        assert!(relation.related(StateIndex::new(0), StateIndex::new(3)));
+        assert!(!relation.related(StateIndex::new(3), StateIndex::new(0)));
+        assert!(!relation.related(StateIndex::new(0), StateIndex::new(7)));
+    }
+}
@@ -0,0 +1,269 @@
+//! Implements the strong bisimulation algorithm by Paige and Tarjan.
+#![forbid(unsafe_code)]
+
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+
+use merc_io::TimeProgress;
+use merc_lts::IncomingTransitions;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::StateIndex;
+use merc_utilities::Timing;
+
+use crate::BlockIndex;
+use crate::BlockPartition;
+use crate::BlockPartitionBuilder;
+use crate::Partition;
+
+/// The index of a constellation, which is a union of blocks.
+type ConstellationIndex = usize;
+
+/// Computes a strong bisimulation partitioning using the algorithm of Paige and
+/// Tarjan, adapted to labelled transition systems.
+///
+/// The partition into blocks is refined with respect to a coarser partition
+/// into constellations. In every step a block that contains at most half of the
+/// states of a non-trivial constellation becomes a constellation of its own,
+/// and only the incoming transitions of this block are inspected. The number of
+/// transitions of every state into every constellation is maintained to split
+/// the blocks with respect to the remainder of the constellation at the same
+/// time. As every state is moved to a new constellation at most log(n) times,
+/// the number of inspected transitions is O(m log n).
+///
+/// In contrast to signature refinement, the number of iterations does not
+/// depend on the depth of the LTS.
+pub fn strong_bisim_pt<L: LTS>(lts: L, timing: &mut Timing) -> (L, BlockPartition) {
+    let mut timepre = timing.start("preprocess");
+    let incoming = IncomingTransitions::new(&lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let mut partition = BlockPartition::new(lts.num_of_states());
+    let mut builder = BlockPartitionBuilder::default();
+
+    // The initial partition has all elements marked.
+    let _ = partition.partition_marked_with(BlockIndex::new(0), &mut builder, |_, _| BlockIndex::new(0));
+
+    // The number of transitions for every state and label into a constellation.
+    let mut state_out: FxHashMap<(StateIndex, LabelIndex, ConstellationIndex), usize> = FxHashMap::default();
+
+    // The sources of the transitions for every label.
+    let mut sources: Vec<Vec<StateIndex>> = vec![Vec::new(); lts.num_of_labels()];
+    for state_index in lts.iter_states() {
+        for transition in lts.outgoing_transitions(state_index) {
+            let count = state_out.entry((state_index, transition.label, 0)).or_default();
+            if *count == 0 {
+                sources[transition.label].push(state_index);
+            }
+
+            *count += 1;
+        }
+    }
+
+    let mut refinement = Refinement {
+        partition,
+        builder,
+        block_constellation: vec![0],
+        constellation_blocks: vec![vec![BlockIndex::new(0)]],
+        non_trivial: Vec::new(),
+        touched: Vec::new(),
+        kinds: Vec::new(),
+    };
+
+    // Initially, the blocks are split based on the enabled actions.
+    for states in &sources {
+        if !states.is_empty() {
+            refinement.split(states, |_| 0);
+        }
+    }
+
+    let progress = TimeProgress::new(
+        |(num_of_blocks, num_of_constellations): (usize, usize)| {
+            debug!("Found {num_of_blocks} blocks and {num_of_constellations} constellations...");
+        },
+        1,
+    );
+
+    let mut incoming_sources: FxHashMap<LabelIndex, Vec<StateIndex>> = FxHashMap::default();
+    while let Some(constellation) = refinement.non_trivial.pop() {
+        progress.print((
+            refinement.partition.num_of_blocks(),
+            refinement.constellation_blocks.len(),
+        ));
+
+        let splitter_block = refinement.split_constellation(constellation);
+        let new_constellation = refinement.block_constellation[splitter_block];
+
+        // Move the transitions into the splitter block to the new constellation.
+        incoming_sources.clear();
+        let states: Vec<StateIndex> = refinement.partition.iter_block(splitter_block).collect();
+        for state_index in states {
+            for transition in incoming.incoming_transitions(state_index) {
+                let from = transition.to;
+                let count = state_out
+                    .entry((from, transition.label, new_constellation))
+                    .or_default();
+                if *count == 0 {
+                    incoming_sources.entry(transition.label).or_default().push(from);
+                }
+                *count += 1;
+
+                let old_count = state_out
+                    .get_mut(&(from, transition.label, constellation))
+                    .expect("The counter should exist");
+                *old_count -= 1;
+                if *old_count == 0 {
+                    state_out.remove(&(from, transition.label, constellation));
+                }
+            }
+        }
+
+        // Ensure that the result does not depend on the iteration order of the hash map.
+        let mut labels: Vec<LabelIndex> = incoming_sources.keys().copied().collect();
+        labels.sort_unstable();
+
+        for label in labels {
+            // Split the blocks into the states that only have a transition into
+            // the splitter, the states that have a transition into the splitter
+            // and the remainder of the constellation, and the other states.
+            let states = &incoming_sources[&label];
+            refinement.split(states, |state_index| {
+                usize::from(state_out.contains_key(&(state_index, label, constellation)))
+            });
+        }
+    }
+
+    let partition = refinement.partition;
+    trace!("Refinement partition {partition}");
+    time.finish();
+
+    (lts, partition)
+}
+
+struct Refinement {
+    partition: BlockPartition,
+    builder: BlockPartitionBuilder,
+
+    /// The constellation of every block, and the blocks of every constellation.
+    block_constellation: Vec<ConstellationIndex>,
+    constellation_blocks: Vec<Vec<BlockIndex>>,
+
+    /// The constellations that consist of more than one block.
+    non_trivial: Vec<ConstellationIndex>,
+
+    /// The blocks that contain a marked state, and the kinds of marked states in every block.
+    touched: Vec<BlockIndex>,
+    kinds: Vec<[bool; 2]>,
+}
+
+impl Refinement {
+    /// Moves a block that contains at most half of the states of the given
+    /// constellation into a new constellation, and returns it.
+    fn split_constellation(&mut self, constellation: ConstellationIndex) -> BlockIndex {
+        let blocks = &self.constellation_blocks[constellation];
+        debug_assert!(blocks.len() > 1, "Only non-trivial constellations can be split");
+
+        // The smallest of two blocks contains at most half of the states.
+        let index = if self.partition.block(blocks[0]).len() <= self.partition.block(blocks[1]).len() {
+            0
+        } else {
+            1
+        };
+
+        let splitter_block = self.constellation_blocks[constellation].swap_remove(index);
+        self.block_constellation[splitter_block] = self.constellation_blocks.len();
+        self.constellation_blocks.push(vec![splitter_block]);
+
+        if self.constellation_blocks[constellation].len() > 1 {
+            self.non_trivial.push(constellation);
+        }
+
+        trace!("Splitting block {splitter_block} from constellation {constellation}");
+        splitter_block
+    }
+
+    /// Splits every block into its unmarked states, and the given states
+    /// grouped by their kind, which is either zero or one.
+    fn split(&mut self, states: &[StateIndex], kind: impl Fn(StateIndex) -> usize) {
+        self.kinds.resize(self.partition.num_of_blocks(), [false; 2]);
+
+        for state_index in states {
+            let block_index = self.partition.block_number(*state_index);
+            if !self.partition.block(block_index).has_marked() {
+                self.touched.push(block_index);
+            }
+
+            self.partition.mark_element(*state_index);
+            self.kinds[block_index][kind(*state_index)] = true;
+        }
+
+        for block_index in self.touched.drain(..) {
+            let [first, second] = self.kinds[block_index];
+            self.kinds[block_index] = [false; 2];
+
+            // The numbers of the resulting blocks must be dense.
+            let num_of_blocks = self.partition.num_of_blocks();
+            let new_blocks: Vec<BlockIndex> = self
+                .partition
+                .partition_marked_with(block_index, &mut self.builder, |state_index, _| {
+                    BlockIndex::new(if first && second { kind(state_index) } else { 0 })
+                })
+                .collect();
+
+            // Every new block is part of the same constellation.
+            let constellation = self.block_constellation[block_index];
+            for new_block_index in new_blocks {
+                if new_block_index.value() >= num_of_blocks {
+                    self.block_constellation.push(constellation);
+                    self.constellation_blocks[constellation].push(new_block_index);
+                    if self.constellation_blocks[constellation].len() == 2 {
+                        self.non_trivial.push(constellation);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_io::DumpFiles;
+    use merc_lts::random_lts;
+    use merc_lts::write_aut;
+    use merc_utilities::random_test;
+
+    use crate::strong_bisim_sigref;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Tests are too slow under miri.
+    fn test_random_strong_bisim_pt() {
+        random_test(100, |rng| {
+            let mut files = DumpFiles::new("test_random_strong_bisim_pt");
+
+            let lts = random_lts(rng, 10, 3, 3);
+            files.dump("input.aut", |w| write_aut(w, &lts)).unwrap();
+            let mut timing = Timing::new();
+
+            let (lts, result_partition) = strong_bisim_pt(lts, &mut timing);
+            let (_, expected_partition) = strong_bisim_sigref(lts.clone(), &mut timing);
+
+            assert_eq!(result_partition.num_of_blocks(), expected_partition.num_of_blocks());
+            for state_index in lts.iter_states() {
+                for other_state_index in lts.iter_states() {
+                    assert_eq!(
+                        result_partition.block_number(state_index) == result_partition.block_number(other_state_index),
+                        expected_partition.block_number(state_index)
+                            == expected_partition.block_number(other_state_index),
+                        "States {state_index} and {other_state_index} are related differently"
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,100 @@
+#![forbid(unsafe_code)]
+
+use merc_lts::LTS;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::LtsBuilderFast;
+use merc_lts::StateIndex;
+
+/// Removes all internal actions from the given LTS by replacing every path
+/// `s -tau*-> s' -a-> t` by a single transition `s -a-> t`, and afterwards
+/// removes the states that are no longer reachable. This preserves the weak
+/// traces of the LTS.
+pub fn tau_star_reduce<L: LTS>(lts: &L) -> LabelledTransitionSystem<L::Label> {
+    let mut builder = LtsBuilderFast::new(lts.labels().into(), Vec::new());
+
+    // Assign new indices to the states in the order that they are reached.
+    let mut indices: Vec<Option<StateIndex>> = vec![None; lts.num_of_states()];
+    indices[lts.initial_state_index()] = Some(StateIndex::new(0));
+    let mut num_of_states = 1;
+
+    let mut queue = vec![lts.initial_state_index()];
+    let mut visited = vec![false; lts.num_of_states()];
+    let mut stack = Vec::new();
+    let mut closure = Vec::new();
+
+    while let Some(state_index) = queue.pop() {
+        let from = indices[state_index].expect("Every state on the queue has an index");
+
+        // Compute the states reachable by internal steps.
+        closure.clear();
+        stack.push(state_index);
+        visited[state_index] = true;
+        while let Some(inner_state_index) = stack.pop() {
+            closure.push(inner_state_index);
+            for transition in lts.outgoing_transitions(inner_state_index) {
+                if lts.is_hidden_label(transition.label) && !visited[transition.to] {
+                    visited[transition.to] = true;
+                    stack.push(transition.to);
+                }
+            }
+        }
+
+        for inner_state_index in &closure {
+            visited[*inner_state_index] = false;
+
+            for transition in lts.outgoing_transitions(*inner_state_index) {
+                if !lts.is_hidden_label(transition.label) {
+                    let to = *indices[transition.to].get_or_insert_with(|| {
+                        queue.push(transition.to);
+                        num_of_states += 1;
+                        StateIndex::new(num_of_states - 1)
+                    });
+
+                    builder.add_transition(from, &lts.labels()[transition.label], to);
+                }
+            }
+        }
+    }
+
+    builder.require_num_of_states(num_of_states);
+    let result = builder.finish(StateIndex::new(0), true);
+
+    // The remaining actions are still declared by the specification of the original LTS.
+    match lts.specification() {
+        Some(specification) => result.with_specification(specification.clone()),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use merc_lts::read_aut;
+
+    #[test]
+    fn test_tau_star_reduce() {
+        let lts = read_aut(
+            b"des (0,4,5)
+(0,i,1)
+(1,a,2)
+(0,b,3)
+(3,i,4)
+" as &[u8],
+            Vec::new(),
+        )
+        .unwrap();
+
+        let reduced_lts = tau_star_reduce(&lts);
+        assert_eq!(reduced_lts.num_of_states(), 3);
+        assert_eq!(reduced_lts.num_of_transitions(), 2);
+        assert!(
+            reduced_lts.iter_states().all(|state_index| reduced_lts
+                .outgoing_transitions(state_index)
+                .all(|transition| !reduced_lts.is_hidden_label(transition.label))),
+            "The reduced LTS should not contain internal actions"
+        );
+    }
+}
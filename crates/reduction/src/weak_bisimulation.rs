@@ -17,6 +17,7 @@ use merc_lts::LabelledTransitionSystem;
 use merc_utilities::Timing;
 
 use crate::BlockIndex;
+use crate::IndexedPartition;
 use crate::SimpleBlockPartition;
 use crate::preprocess_branching;
 
@@ -24,12 +25,19 @@ use crate::preprocess_branching;
 type BitArray = BitVec<u64, Lsb0>;
 
 /// Apply weak bisimulation reduction
+///
+/// The returned mapping relates every state of the input LTS to its state in
+/// the preprocessed LTS, see [preprocess_branching].
 pub fn weak_bisimulation<L: LTS>(
     lts: L,
     timing: &mut Timing,
-) -> (LabelledTransitionSystem<L::Label>, SimpleBlockPartition) {
+) -> (
+    LabelledTransitionSystem<L::Label>,
+    SimpleBlockPartition,
+    IndexedPartition,
+) {
     let mut time_pre = timing.start("preprocessing");
-    let tau_loop_free_lts = preprocess_branching(lts);
+    let (tau_loop_free_lts, mapping) = preprocess_branching(lts);
     time_pre.finish();
 
     let mut time_reduction = timing.start("reduction");
@@ -92,7 +100,7 @@ pub fn weak_bisimulation<L: LTS>(
     }
 
     time_reduction.finish();
-    (tau_loop_free_lts, blocks)
+    (tau_loop_free_lts, blocks, mapping)
 }
 
 /// Sets s.act_mark to true iff exists t: S. s =\not{a}=> t
@@ -11,7 +11,9 @@ use merc_pest_consume::match_nodes;
 use crate::ActDecl;
 use crate::ActFrm;
 use crate::Action;
+use crate::ActionRHS;
 use crate::ActionRenameDecl;
+use crate::ActionRenameRule;
 use crate::Assignment;
 use crate::BagElement;
 use crate::Comm;
@@ -370,7 +372,9 @@ impl Mcrl2Parser {
                 Rule::ActSpec => {
                     action_declarations.append(&mut Mcrl2Parser::ActSpec(child)?);
                 }
-                Rule::ActionRenameRuleSpec => rename_declarations.push(Mcrl2Parser::ActionRenameRuleSpec(child)?),
+                Rule::ActionRenameRuleSpec => {
+                    rename_declarations.append(&mut Mcrl2Parser::ActionRenameRuleSpec(child)?);
+                }
                 Rule::EOI => {
                     // End of input
                     break;
@@ -874,7 +878,7 @@ impl Mcrl2Parser {
         )
     }
 
-    fn ProcExpr(input: ParseNode) -> ParseResult<ProcessExpr> {
+    pub(crate) fn ProcExpr(input: ParseNode) -> ParseResult<ProcessExpr> {
         parse_process_expr(input.children().as_pairs().clone())
     }
 
@@ -1317,8 +1321,39 @@ impl Mcrl2Parser {
         )
     }
 
-    fn ActionRenameRuleSpec(spec: ParseNode) -> ParseResult<ActionRenameDecl> {
-        unimplemented!();
+    fn ActionRenameRuleSpec(spec: ParseNode) -> ParseResult<Vec<ActionRenameDecl>> {
+        match_nodes!(spec.into_children();
+            [VarSpec(variables), ActionRenameRule(rules)..] => {
+                Ok(rules.map(|rename_rule| ActionRenameDecl { variables_specification: variables.clone(), rename_rule }).collect())
+            },
+            [ActionRenameRule(rules)..] => {
+                Ok(rules.map(|rename_rule| ActionRenameDecl { variables_specification: Vec::new(), rename_rule }).collect())
+            },
+        )
+    }
+
+    fn ActionRenameRule(rule: ParseNode) -> ParseResult<ActionRenameRule> {
+        match_nodes!(rule.into_children();
+            [DataExpr(condition), Action(action), ActionRenameRuleRHS(rhs)] => {
+                Ok(ActionRenameRule { condition: Some(condition), action, rhs })
+            },
+            [Action(action), ActionRenameRuleRHS(rhs)] => {
+                Ok(ActionRenameRule { condition: None, action, rhs })
+            },
+        )
+    }
+
+    fn ActionRenameRuleRHS(rhs: ParseNode) -> ParseResult<ActionRHS> {
+        // The tau and delta keywords are not rules, so they are distinguished by the matched text.
+        match rhs.as_str().trim() {
+            "tau" => Ok(ActionRHS::Tau),
+            "delta" => Ok(ActionRHS::Delta),
+            _ => match_nodes!(rhs.into_children();
+                [Action(action)] => {
+                    Ok(ActionRHS::Action(action))
+                },
+            ),
+        }
     }
 
     fn FormSpec(input: ParseNode) -> ParseResult<StateFrm> {
@@ -8,6 +8,7 @@ use crate::DataExpr;
 use crate::DataExprBinaryOp;
 use crate::MultiAction;
 use crate::ParseNode;
+use crate::ProcessExpr;
 use crate::StateFrmOp;
 use crate::UntypedActionRenameSpec;
 use crate::UntypedDataSpecification;
@@ -47,10 +48,29 @@ impl DataExpr {
     }
 }
 
+impl ProcessExpr {
+    pub fn parse(spec: &str) -> Result<ProcessExpr, MercError> {
+        let mut result = Mcrl2Parser::parse(Rule::ProcExpr, spec).map_err(extend_parser_error)?;
+        let root = result.next().expect("Could not parse mCRL2 process expression");
+        if root.as_span().end() < spec.trim_end().len() {
+            return Err(format!("Unexpected input after the process expression \"{}\"", root.as_str()).into());
+        }
+
+        Ok(Mcrl2Parser::ProcExpr(ParseNode::new(root))?)
+    }
+}
+
 impl MultiAction {
     pub fn parse(spec: &str) -> Result<MultiAction, MercError> {
         let mut result = Mcrl2Parser::parse(Rule::MultAct, spec).map_err(extend_parser_error)?;
         let root = result.next().expect("Could not parse mCRL2 multi-action");
+        if root.as_span().end() < spec.trim_end().len() {
+            return Err(format!(
+                "Unexpected input after the multi-action \"{}\" in \"{spec}\"",
+                root.as_str()
+            )
+            .into());
+        }
 
         Ok(Mcrl2Parser::MultAct(ParseNode::new(root))?)
     }
@@ -1,8 +1,12 @@
 use indoc::indoc;
 use pest::Parser;
 
+use merc_syntax::ActionRHS;
+use merc_syntax::DataExpr;
+use merc_syntax::DataExprBinaryOp;
 use merc_syntax::Mcrl2Parser;
 use merc_syntax::Rule;
+use merc_syntax::UntypedActionRenameSpec;
 use merc_syntax::UntypedProcessSpecification;
 use merc_syntax::UntypedStateFrmSpec;
 use merc_syntax::parse_sortexpr;
@@ -123,6 +127,56 @@ fn test_sort_precedence() {
     }
 }
 
+#[test]
+fn test_parse_action_rename_spec() {
+    let spec = indoc! {"
+        act a, b: Nat;
+            c;
+
+        var n: Nat;
+        rename
+            n > 1 -> a(n) => b(n);
+            a(0) => tau;
+            c => delta;
+    "};
+
+    let result = UntypedActionRenameSpec::parse(spec).unwrap();
+    assert_eq!(result.action_declarations.len(), 3);
+    assert_eq!(result.rename_declarations.len(), 3);
+
+    let rules: Vec<_> = result
+        .rename_declarations
+        .iter()
+        .map(|decl| &decl.rename_rule)
+        .collect();
+    assert!(rules[0].condition.is_some());
+    assert_eq!(result.rename_declarations[0].variables_specification.len(), 1);
+    assert!(matches!(rules[0].rhs, ActionRHS::Action(_)));
+    assert_eq!(rules[1].rhs, ActionRHS::Tau);
+    assert_eq!(rules[2].rhs, ActionRHS::Delta);
+}
+
+#[test]
+fn test_parse_number_without_whitespace() {
+    // The rename conditions compare numbers by their text, so the whitespace before `->` must not be part of it.
+    let spec = indoc! {"
+        act a: Nat;
+        var n: Nat;
+        rename
+            n > 1 -> a(n) => tau;
+    "};
+
+    let result = UntypedActionRenameSpec::parse(spec).unwrap();
+    let condition = result.rename_declarations[0].rename_rule.condition.as_ref().unwrap();
+    match condition {
+        DataExpr::Binary { op, rhs, .. } => {
+            assert_eq!(*op, DataExprBinaryOp::GreaterThan);
+            assert_eq!(**rhs, DataExpr::Number("1".to_string()));
+        }
+        _ => panic!("Expected a comparison, got {condition}"),
+    }
+}
+
 // #[test]
 // fn test_bool_spec() {
 //     match UntypedDataSpecification::parse(include_str!("../spec/bool.mcrl2")) {
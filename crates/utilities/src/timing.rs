@@ -1,5 +1,6 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::io;
 use std::io::Write;
 use std::rc::Rc;
@@ -13,6 +14,7 @@ use log::warn;
 #[derive(Default)]
 pub struct Timing {
     results: Rc<RefCell<Vec<(String, f32)>>>,
+    statistics: RefCell<Vec<(String, String)>>,
 }
 
 /// A timer object that measures the time between its creation and the call to
@@ -41,9 +43,17 @@ impl Timing {
     pub fn new() -> Self {
         Self {
             results: Rc::new(RefCell::new(Vec::new())),
+            statistics: RefCell::new(Vec::new()),
         }
     }
 
+    /// Records a statistic, such as the size of an intermediate result, that is
+    /// printed together with the timers in the order in which they are recorded.
+    pub fn record(&self, name: &str, value: impl Display) {
+        info!("{name}: {value}");
+        self.statistics.borrow_mut().push((name.to_string(), value.to_string()));
+    }
+
     /// Starts a new timer with the given name.
     pub fn start(&self, name: &str) -> Timer {
         Timer {
@@ -98,6 +108,10 @@ impl Timing {
                 );
             }
         }
+
+        for (name, value) in self.statistics.borrow().iter() {
+            eprintln!("Statistic {name}: {value}");
+        }
     }
 
     /// Writes a YAML report of the finished timers to the given writer.
@@ -115,6 +129,14 @@ impl Timing {
                 writeln!(writer, "      avg: {avg:.3}s", avg = ag.avg)?;
             }
         }
+
+        let statistics = self.statistics.borrow();
+        if !statistics.is_empty() {
+            writeln!(writer, "  statistics:")?;
+            for (name, value) in statistics.iter() {
+                writeln!(writer, "    {name}: {value}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -0,0 +1,68 @@
+#![forbid(unsafe_code)]
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use merc_vpg::ParityGame;
+use merc_vpg::ParityGameSolver;
+use merc_vpg::random_parity_game;
+use merc_vpg::read_pg;
+use merc_vpg::solve_parity_game;
+use merc_vpg::solve_zielonka_parallel;
+
+/// Sets the number of threads for the parallel benchmarks.
+pub const THREADS: [usize; 3] = [2, 4, 8];
+
+/// The number of vertices, priorities and the outdegree of the generated random parity games.
+pub const RANDOM_GAMES: [(usize, usize, usize); 3] = [(100, 8, 3), (1000, 8, 3), (10000, 8, 3)];
+
+pub fn criterion_benchmark_solvers(c: &mut Criterion) {
+    let mut examples: Vec<(String, ParityGame)> = vec![(
+        "example.pg".to_string(),
+        read_pg(include_bytes!("../../../../examples/vpg/example.pg") as &[u8]).unwrap(),
+    )];
+
+    // Use a fixed seed such that the generated games are the same for every run.
+    let mut rng = StdRng::seed_from_u64(42);
+    for (num_of_vertices, num_of_priorities, outdegree) in RANDOM_GAMES {
+        examples.push((
+            format!("random_{num_of_vertices}_{num_of_priorities}_{outdegree}"),
+            random_parity_game(&mut rng, true, num_of_vertices, num_of_priorities, outdegree),
+        ));
+    }
+
+    let solvers = [
+        ("zielonka", ParityGameSolver::Zielonka),
+        ("priority_promotion", ParityGameSolver::PriorityPromotion),
+        ("region_recovery", ParityGameSolver::RegionRecovery),
+        ("tangle_learning", ParityGameSolver::TangleLearning),
+        ("fixpoint_iteration", ParityGameSolver::FixpointIteration),
+        ("quasi_polynomial_zielonka", ParityGameSolver::QuasiPolynomialZielonka),
+    ];
+
+    for (name, game) in &examples {
+        for (solver_name, solver) in solvers {
+            c.bench_function(&format!("{solver_name} {name}"), |bencher| {
+                bencher.iter(|| {
+                    let _ = black_box(solve_parity_game(game, solver));
+                });
+            });
+        }
+
+        for num_of_threads in THREADS {
+            c.bench_function(&format!("zielonka_parallel_{num_of_threads} {name}"), |bencher| {
+                bencher.iter(|| {
+                    let _ = black_box(solve_zielonka_parallel(game, num_of_threads));
+                });
+            });
+        }
+    }
+}
+
+criterion_group!(benches, criterion_benchmark_solvers,);
+criterion_main!(benches);
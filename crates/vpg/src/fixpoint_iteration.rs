@@ -0,0 +1,120 @@
+//! Implements the distraction based fixpoint iteration solver for parity games, see
+//!
+//! > Tom van Dijk and Bob Rubbens. Simple fixpoint iteration to solve parity
+//! > games. GandALF 2019.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use log::debug;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Set;
+use crate::VertexIndex;
+
+/// Solves the given parity game using fixpoint iteration, and returns the winning sets.
+///
+/// # Details
+///
+/// Every vertex is initially assumed to be won by the player of its priority,
+/// and a vertex is distracted when that player can not win it in one step
+/// under the current assumptions. The priorities are considered from low to
+/// high, and whenever a vertex is distracted, the distractions of all lower
+/// priorities are reset and the iteration restarts from the lowest priority.
+/// Unlike the other solvers this does not yield a winning strategy.
+pub fn solve_fixpoint_iteration(game: &ParityGame) -> [Set; 2] {
+    debug_assert!(game.is_total(), "Fixpoint iteration requires a total parity game");
+
+    // Keep track of the vertices for each priority
+    let mut priority_vertices: Vec<Vec<VertexIndex>> = Vec::new();
+    for v in game.iter_vertices() {
+        let prio = game.priority(v);
+
+        while prio >= priority_vertices.len() {
+            priority_vertices.push(Vec::new());
+        }
+
+        priority_vertices[prio].push(v);
+    }
+
+    let mut distraction = bitvec![usize, Lsb0; 0; game.num_of_vertices()];
+    let winner = |distraction: &Set, v: VertexIndex| -> Player {
+        let player = Player::from_priority(&game.priority(v));
+        if distraction[*v] { player.opponent() } else { player }
+    };
+
+    let mut iterations = 0;
+    let mut p = 0;
+    while p < priority_vertices.len() {
+        iterations += 1;
+
+        let mut changed = false;
+        for v in &priority_vertices[p] {
+            if distraction[**v] {
+                continue;
+            }
+
+            let player = Player::from_priority(&game.priority(*v));
+            let won = if game.owner(*v) == player {
+                game.outgoing_edges(*v).any(|w| winner(&distraction, w) == player)
+            } else {
+                game.outgoing_edges(*v).all(|w| winner(&distraction, w) == player)
+            };
+
+            if !won {
+                distraction.set(**v, true);
+                changed = true;
+            }
+        }
+
+        if changed {
+            // The lower priorities were computed under assumptions that no longer hold.
+            for vertices in &priority_vertices[..p] {
+                for v in vertices {
+                    distraction.set(**v, false);
+                }
+            }
+
+            p = 0;
+        } else {
+            p += 1;
+        }
+    }
+
+    debug!("Performed {iterations} iterations");
+
+    let mut solution = [
+        bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+        bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+    ];
+    for v in game.iter_vertices() {
+        solution[winner(&distraction, v).to_index()].set(*v, true);
+    }
+
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::random_parity_game;
+    use crate::solve_zielonka;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_fixpoint_iteration() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 3);
+
+            assert_eq!(
+                solve_fixpoint_iteration(&pg),
+                solve_zielonka(&pg),
+                "The solution differs from Zielonka"
+            );
+        })
+    }
+}
@@ -2,23 +2,41 @@
 #![forbid(unsafe_code)]
 
 mod feature_transition_system;
+mod fixpoint_iteration;
 mod modal_equation_system;
+mod model_checking;
 mod parity_games;
+mod priority_promotion;
 mod project;
+mod quasi_polynomial_zielonka;
 mod reachability;
 mod repeat;
+mod scc;
+mod simplify;
+mod solver;
+mod strategy;
 mod submap;
+mod tangle_learning;
 mod translate;
 mod variability_zielonka;
 mod zielonka;
 
 pub use feature_transition_system::*;
+pub use fixpoint_iteration::*;
 pub use modal_equation_system::*;
+pub use model_checking::*;
 pub use parity_games::*;
+pub use priority_promotion::*;
 pub use project::*;
+pub use quasi_polynomial_zielonka::*;
 pub use reachability::*;
 pub use repeat::*;
+pub use scc::*;
+pub use simplify::*;
+pub use solver::*;
+pub use strategy::*;
 pub use submap::*;
+pub use tangle_learning::*;
 pub use translate::*;
 pub use variability_zielonka::*;
 pub use zielonka::*;
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use oxidd::BooleanFunction;
+use oxidd::ManagerRef;
+use oxidd::bdd::BDDFunction;
+
+use merc_collections::IndexedSet;
+use merc_lts::LTS;
+use merc_lts::LabelIndex;
+use merc_lts::LabelledTransitionSystem;
+use merc_lts::LtsBuilderFast;
+use merc_lts::StateIndex;
+use merc_lts::TransitionLabel;
+use merc_lts::parse_multi_action;
+use merc_syntax::FixedPointOperator;
+use merc_syntax::MultiAction;
+use merc_syntax::Span;
+use merc_syntax::StateFrm;
+use merc_syntax::StateVarDecl;
+use merc_utilities::MercError;
+use merc_utilities::Timing;
+
+use crate::FeatureTransitionSystem;
+use crate::PG;
+use crate::Player;
+use crate::VertexIndex;
+use crate::project_variability_parity_game;
+use crate::solve_zielonka_strategy;
+use crate::translate_labelled;
+
+/// Checks whether the given modal mu-calculus formula holds in the initial
+/// state of the LTS, and returns the evidence for the answer.
+///
+/// # Details
+///
+/// The LTS is translated into a parity game as a feature transition system
+/// with a trivial configuration, which is solved using Zielonka's algorithm.
+/// The evidence consists of the transitions of the LTS that are visited by the
+/// plays that follow the winning strategy of the initial vertex, which is a
+/// witness when the formula holds and a counterexample otherwise.
+pub fn check_formula<L: TransitionLabel>(
+    lts: LabelledTransitionSystem<L>,
+    formula: &StateFrm,
+    timing: &mut Timing,
+) -> Result<(bool, LabelledTransitionSystem<L>), MercError> {
+    let labels = lts.labels().to_vec();
+    let parsed_labels: Vec<MultiAction> = labels
+        .iter()
+        .enumerate()
+        .map(|(label_index, label)| {
+            if lts.is_hidden_label(LabelIndex::new(label_index)) {
+                MultiAction { actions: Vec::new() }
+            } else {
+                parse_multi_action(label)
+            }
+        })
+        .collect();
+    let initial_state = lts.initial_state_index();
+
+    // Without features every transition is enabled for the only configuration.
+    let manager_ref = oxidd::bdd::new_manager(1024, 1024, 1);
+    let true_bdd = manager_ref.with_manager_shared(|manager| BDDFunction::t(manager));
+    let fts = FeatureTransitionSystem::new(
+        lts.relabel(|label| label.to_string()),
+        vec![true_bdd.clone(); labels.len()],
+        HashMap::new(),
+    );
+
+    // The translation starts in the first fixpoint equation, so the formula is made a fixpoint with a fresh variable.
+    let formula = match formula {
+        StateFrm::FixedPoint { .. } => formula.clone(),
+        _ => {
+            let text = formula.to_string();
+            let mut identifier = "X".to_string();
+            while text.contains(&identifier) {
+                identifier.push('_');
+            }
+
+            StateFrm::FixedPoint {
+                operator: FixedPointOperator::Greatest,
+                variable: StateVarDecl {
+                    identifier,
+                    arguments: Vec::new(),
+                    span: Span { start: 0, end: 0 },
+                },
+                body: Box::new(formula.clone()),
+            }
+        }
+    };
+
+    let mut time_translate = timing.start("translate");
+    let (vpg, transition_edges) = translate_labelled(&manager_ref, &fts, &parsed_labels, true_bdd.clone(), &formula)?;
+    let game = project_variability_parity_game(&vpg, &true_bdd)?;
+    time_translate.finish();
+
+    let mut time_solve = timing.start("solve_zielonka");
+    let (solution, strategy) = solve_zielonka_strategy(&game);
+    time_solve.finish();
+
+    let winner = if solution[0][*game.initial_vertex()] {
+        Player::Even
+    } else {
+        Player::Odd
+    };
+
+    // Explore the plays that are consistent with the strategy of the winner, and collect the corresponding transitions.
+    let mut time_evidence = timing.start("evidence");
+    let transitions: HashMap<(VertexIndex, VertexIndex), (StateIndex, LabelIndex, StateIndex)> = transition_edges
+        .into_iter()
+        .map(|(from, to, transition)| ((from, to), transition))
+        .collect();
+
+    let mut states: IndexedSet<StateIndex> = IndexedSet::new();
+    states.insert(initial_state);
+    let mut builder = LtsBuilderFast::new(Vec::new(), Vec::new());
+
+    let mut visited = vec![false; game.num_of_vertices()];
+    let mut stack = vec![game.initial_vertex()];
+    visited[*game.initial_vertex()] = true;
+
+    while let Some(v) = stack.pop() {
+        let successors: Vec<VertexIndex> = if game.owner(v) == winner {
+            strategy[*v].into_iter().collect()
+        } else {
+            game.outgoing_edges(v).collect()
+        };
+
+        for w in successors {
+            if let Some((from, label, to)) = transitions.get(&(v, w)) {
+                let (from_index, _) = states.insert(*from);
+                let (to_index, _) = states.insert(*to);
+                builder.add_transition(
+                    StateIndex::new(*from_index),
+                    &labels[*label],
+                    StateIndex::new(*to_index),
+                );
+            }
+
+            if !visited[*w] {
+                visited[*w] = true;
+                stack.push(w);
+            }
+        }
+    }
+
+    builder.require_num_of_states(states.len());
+    let evidence = builder.finish(StateIndex::new(0), true);
+    time_evidence.finish();
+
+    Ok((winner == Player::Even, evidence))
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_lts::read_aut;
+    use merc_syntax::UntypedStateFrmSpec;
+
+    use super::*;
+
+    /// Checks the given formula on the LTS in the .aut format.
+    fn check(aut: &[u8], formula: &str) -> (bool, LabelledTransitionSystem<String>) {
+        let lts = read_aut(aut, Vec::new()).unwrap();
+        let formula = UntypedStateFrmSpec::parse(formula).unwrap();
+
+        check_formula(lts, &formula.formula, &mut Timing::new()).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_check_formula_deadlock_freedom() {
+        let aut = b"des (0,4,4)\n(0,a,1)\n(1,b,0)\n(0,c,2)\n(2,i,3)\n";
+
+        // The deadlock is reachable via c and tau, which is the counterexample.
+        let (holds, evidence) = check(aut, "nu X. ([true]X && <true>true)");
+        assert!(!holds);
+        assert_eq!(evidence.num_of_transitions(), 2);
+        assert_eq!(evidence.num_of_states(), 3);
+
+        let (holds, _) = check(aut, "<a><b><a>true");
+        assert!(holds);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_check_formula_infinite_path() {
+        let aut = b"des (0,3,3)\n(0,a,1)\n(1,a,0)\n(0,b,2)\n";
+
+        // There is an infinite a-path, and the witness is its cycle.
+        let (holds, evidence) = check(aut, "nu X. <a>X");
+        assert!(holds);
+        assert_eq!(evidence.num_of_transitions(), 2);
+        assert!(evidence.labels().iter().all(|label| label == "a" || label == "i"));
+
+        // Not every path eventually performs b.
+        let (holds, _) = check(aut, "mu X. ([!b]X && <true>true)");
+        assert!(!holds);
+    }
+}
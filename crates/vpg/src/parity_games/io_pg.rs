@@ -17,6 +17,8 @@ use crate::PG;
 use crate::ParityGame;
 use crate::Player;
 use crate::Priority;
+use crate::Set;
+use crate::Strategy;
 use crate::VertexIndex;
 
 #[derive(Error, Debug)]
@@ -144,6 +146,36 @@ pub fn write_pg(mut writer: impl Write, game: &ParityGame) -> Result<(), MercErr
     Ok(())
 }
 
+/// Writes the solution of the given parity game to the given writer in the
+/// PGSolver solution format.
+///
+/// # Details
+///
+/// The format starts with a header, followed by the winner of every vertex and
+/// the successor chosen by the strategy when the owner wins it.
+///
+/// `paritysol <num_of_vertices>;`
+/// `<index> <winner> [<successor>];`
+pub fn write_pg_solution(
+    mut writer: impl Write,
+    game: &ParityGame,
+    solution: &[Set; 2],
+    strategy: &Strategy,
+) -> Result<(), MercError> {
+    writeln!(writer, "paritysol {};", game.num_of_vertices())?;
+    for v in game.iter_vertices() {
+        let winner = if solution[0][*v] { Player::Even } else { Player::Odd };
+
+        write!(writer, "{} {}", v.value(), winner.to_index())?;
+        if let Some(w) = strategy[*v] {
+            write!(writer, " {}", w.value())?;
+        }
+        writeln!(writer, ";")?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
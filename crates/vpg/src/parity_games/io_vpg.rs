@@ -25,7 +25,9 @@ use crate::PG;
 use crate::ParityGame;
 use crate::Player;
 use crate::Priority;
+use crate::Submap;
 use crate::VariabilityParityGame;
+use crate::VariabilityStrategy;
 use crate::VertexIndex;
 
 /// Reads a variability parity game in an extended PGSolver `.vpg` format from the given reader.
@@ -250,6 +252,55 @@ pub fn write_vpg(writer: &mut impl Write, game: &VariabilityParityGame) -> Resul
     Ok(())
 }
 
+/// Writes the solution of the given variability parity game to the given
+/// writer, in a variant of the PGSolver solution format where the winners and
+/// the successors chosen by the strategy are annotated by configurations as in
+/// the .vpg format.
+///
+/// # Details
+///
+/// `confs <configurations>;`
+/// `paritysol <num_of_vertices>;`
+/// `<index> <winner>|<configurations>,... <successor>|<configurations>,...;`
+pub fn write_vpg_solution(
+    writer: &mut impl Write,
+    game: &VariabilityParityGame,
+    solution: &[Submap; 2],
+    strategy: &VariabilityStrategy,
+) -> Result<(), MercError> {
+    let mut writer = BufWriter::new(writer);
+
+    writeln!(writer, "confs {};", FormatConfigSet(game.configuration()))?;
+    writeln!(writer, "paritysol {};", game.num_of_vertices())?;
+    for v in game.iter_vertices() {
+        write!(
+            writer,
+            "{} {}",
+            v.value(),
+            solution
+                .iter()
+                .enumerate()
+                .filter(|(_, winning)| winning[v].satisfiable())
+                .format_with(",", |(winner, winning), fmt| {
+                    fmt(&format_args!("{}|{}", winner, FormatConfigSet(&winning[v])))
+                })
+        )?;
+
+        if !strategy[*v].is_empty() {
+            write!(
+                writer,
+                " {}",
+                strategy[*v].iter().format_with(",", |(w, configuration), fmt| {
+                    fmt(&format_args!("{}|{}", w, FormatConfigSet(configuration)))
+                })
+            )?;
+        }
+        writeln!(writer, ";")?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
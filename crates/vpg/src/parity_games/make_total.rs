@@ -52,12 +52,13 @@ pub fn make_vpg_total(
         // Missing configurations are those in the universe not covered by any outgoing edge.
         let missing = minus(&universe, &all_outgoing)?;
         if missing.satisfiable() {
+            // A player that cannot move loses, so the vertex moves to the node that is won by the opponent.
             if owners[*vertex] == Player::Even {
-                // Even player: add edge to true node for the remaining configurations.
-                edges.push((vertex, universe.clone(), true_node));
-            } else {
-                // Odd player: add edge to false node for the remaining configurations.
+                // Even player: add edge to false node for the remaining configurations.
                 edges.push((vertex, universe.clone(), false_node));
+            } else {
+                // Odd player: add edge to true node for the remaining configurations.
+                edges.push((vertex, universe.clone(), true_node));
             }
         }
     }
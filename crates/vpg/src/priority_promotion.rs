@@ -0,0 +1,388 @@
+//! Implements the priority promotion solver for parity games, see
+//!
+//! > Massimo Benerecetti, Daniele Dell'Erba and Fabio Mogavero. Solving parity
+//! > games via priority promotion. Formal Methods in System Design, 2018.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use log::debug;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Predecessors;
+use crate::Priority;
+use crate::Set;
+use crate::Strategy;
+use crate::VertexIndex;
+
+/// Solves the given parity game using priority promotion, and returns the
+/// winning sets together with a winning strategy for both players.
+///
+/// # Details
+///
+/// The game is decomposed top-down into regions, which are attractors to the
+/// vertices of the highest priority in the remaining subgame. A region that
+/// the opponent can only leave to higher regions is promoted to the lowest of
+/// those, and every region that the opponent can not leave at all is a
+/// dominion. After a promotion the standard algorithm resets all lower
+/// regions, whereas with `region_recovery` (the RR variant) the lower regions
+/// are kept as long as they are still valid.
+pub fn solve_priority_promotion(game: &ParityGame, region_recovery: bool) -> ([Set; 2], Strategy) {
+    debug_assert!(game.is_total(), "Priority promotion requires a total parity game");
+
+    let mut solver = PriorityPromotionSolver::new(game, region_recovery);
+    solver.solve();
+
+    debug!(
+        "Performed {} promotions and found {} dominions",
+        solver.promotions, solver.dominions
+    );
+
+    let mut strategy = solver.strategy;
+    for v in game.iter_vertices() {
+        let winner = if solver.solution[0][*v] {
+            Player::Even
+        } else {
+            Player::Odd
+        };
+        if game.owner(v) != winner {
+            strategy[*v] = None;
+        }
+    }
+
+    (solver.solution, strategy)
+}
+
+/// The status of a region after computing its attractor.
+enum RegionStatus {
+    /// The opponent can escape to a lower region, or the owner can not stay in the region.
+    Open,
+    /// The opponent can only escape to higher regions, of which the lowest is given.
+    Closed(Option<usize>),
+}
+
+struct PriorityPromotionSolver<'a> {
+    game: &'a ParityGame,
+
+    /// Stores the predecessors of the game.
+    predecessors: Predecessors,
+
+    /// Keep lower regions after a promotion when they are still valid.
+    region_recovery: bool,
+
+    /// The vertices for which the winner has been determined.
+    solved: Set,
+
+    /// The winning sets of both players.
+    solution: [Set; 2],
+
+    /// The priority of the region that every vertex belongs to.
+    region: Vec<usize>,
+
+    /// The vertices of every region, which might contain stale entries that are filtered by [Self::members].
+    members: Vec<Vec<VertexIndex>>,
+
+    /// The successor chosen by the owner of every vertex in a region of its own parity.
+    strategy: Strategy,
+
+    /// Reused temporary queue for attractor computations.
+    temp_queue: Vec<VertexIndex>,
+
+    /// Used to remove duplicates from the members of a region.
+    temp_vertices: Set,
+
+    /// The number of promotions, used for statistics.
+    promotions: usize,
+
+    /// The number of dominions, used for statistics.
+    dominions: usize,
+}
+
+impl<'a> PriorityPromotionSolver<'a> {
+    fn new(game: &'a ParityGame, region_recovery: bool) -> Self {
+        let num_of_priorities = game.iter_vertices().map(|v| *game.priority(v) + 1).max().unwrap_or(0);
+
+        Self {
+            game,
+            predecessors: Predecessors::new(game),
+            region_recovery,
+            solved: bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+            solution: [
+                bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+                bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+            ],
+            region: game.iter_vertices().map(|v| *game.priority(v)).collect(),
+            members: vec![Vec::new(); num_of_priorities],
+            strategy: vec![None; game.num_of_vertices()],
+            temp_queue: Vec::new(),
+            temp_vertices: bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+            promotions: 0,
+            dominions: 0,
+        }
+    }
+
+    /// Repeatedly searches for a dominion, until all vertices are solved.
+    fn solve(&mut self) {
+        while !self.solved.all() {
+            self.reset_below(self.members.len());
+
+            let mut p = self
+                .highest_region_below(self.members.len())
+                .expect("There is an unsolved vertex");
+
+            loop {
+                self.attract(p);
+
+                match self.region_status(p) {
+                    RegionStatus::Open => {
+                        p = self
+                            .highest_region_below(p)
+                            .expect("The lowest region is closed in its subgame");
+                    }
+                    RegionStatus::Closed(None) => {
+                        self.set_dominion(p);
+                        break;
+                    }
+                    RegionStatus::Closed(Some(q)) => {
+                        self.promote(p, q);
+                        p = q;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Extends the region `p` with the attractor to it within the subgame of the lower regions.
+    fn attract(&mut self, p: usize) {
+        let alpha = Player::from_priority(&Priority::new(p));
+
+        self.temp_queue = self.members(p);
+
+        while let Some(w) = self.temp_queue.pop() {
+            for v in self.predecessors.predecessors(w) {
+                if self.solved[*v] || self.region[*v] >= p {
+                    continue;
+                }
+
+                let attracted = self.game.owner(v) == alpha
+                    || self
+                        .game
+                        .outgoing_edges(v)
+                        .all(|u| self.solved[*u] || self.region[*u] >= p);
+
+                if attracted {
+                    if self.game.owner(v) == alpha {
+                        self.strategy[*v] = Some(w);
+                    }
+
+                    self.region[*v] = p;
+                    self.members[p].push(v);
+                    self.temp_queue.push(v);
+                }
+            }
+        }
+    }
+
+    /// Determines whether the region `p` is closed in its subgame, and chooses
+    /// a successor in the region for the vertices of the highest priority.
+    fn region_status(&mut self, p: usize) -> RegionStatus {
+        let alpha = Player::from_priority(&Priority::new(p));
+        let mut lowest_escape = None;
+
+        for v in self.members(p) {
+            if self.game.owner(v) == alpha {
+                if self.strategy[*v].is_none_or(|w| self.solved[*w] || self.region[*w] != p) {
+                    match self
+                        .game
+                        .outgoing_edges(v)
+                        .find(|w| !self.solved[**w] && self.region[**w] == p)
+                    {
+                        Some(w) => self.strategy[*v] = Some(w),
+                        None => return RegionStatus::Open,
+                    }
+                }
+            } else {
+                for w in self.game.outgoing_edges(v) {
+                    if self.solved[*w] {
+                        continue;
+                    }
+
+                    let q = self.region[*w];
+                    if q < p {
+                        return RegionStatus::Open;
+                    } else if q > p {
+                        lowest_escape = Some(lowest_escape.map_or(q, |lowest: usize| lowest.min(q)));
+                    }
+                }
+            }
+        }
+
+        RegionStatus::Closed(lowest_escape)
+    }
+
+    /// Merges the region `p` into the higher region `q`.
+    fn promote(&mut self, p: usize, q: usize) {
+        self.promotions += 1;
+
+        for v in self.members(p) {
+            self.region[*v] = q;
+            self.members[q].push(v);
+        }
+        self.members[p].clear();
+
+        if !self.region_recovery {
+            self.reset_below(q);
+        }
+    }
+
+    /// Marks the attractor of the region `p` in the remaining game as won by its player.
+    fn set_dominion(&mut self, p: usize) {
+        self.dominions += 1;
+        let alpha = Player::from_priority(&Priority::new(p));
+
+        let mut dominion = bitvec![usize, Lsb0; 0; self.game.num_of_vertices()];
+        self.temp_queue.clear();
+        for v in self.members(p) {
+            dominion.set(*v, true);
+            self.temp_queue.push(v);
+        }
+
+        while let Some(w) = self.temp_queue.pop() {
+            for v in self.predecessors.predecessors(w) {
+                if self.solved[*v] || dominion[*v] {
+                    continue;
+                }
+
+                let attracted =
+                    self.game.owner(v) == alpha || self.game.outgoing_edges(v).all(|u| self.solved[*u] || dominion[*u]);
+
+                if attracted {
+                    if self.game.owner(v) == alpha {
+                        self.strategy[*v] = Some(w);
+                    }
+
+                    dominion.set(*v, true);
+                    self.temp_queue.push(v);
+                }
+            }
+        }
+
+        self.solved |= &dominion;
+        self.solution[alpha.to_index()] |= dominion;
+    }
+
+    /// Returns the highest region below `p` that contains unsolved vertices.
+    ///
+    /// With region recovery, the regions that are no longer valid are reset on the way down.
+    fn highest_region_below(&mut self, p: usize) -> Option<usize> {
+        for q in (0..p).rev() {
+            if self.region_recovery && !self.is_valid(q) {
+                self.reset_region(q);
+            }
+
+            if !self.members(q).is_empty() {
+                return Some(q);
+            }
+        }
+
+        None
+    }
+
+    /// Returns true iff every vertex in the region `q` still has its strategy
+    /// in the region, and the opponent can only escape to higher regions.
+    fn is_valid(&mut self, q: usize) -> bool {
+        let alpha = Player::from_priority(&Priority::new(q));
+
+        self.members(q).into_iter().all(|v| {
+            let stays = |w: VertexIndex| !self.solved[*w] && self.region[*w] == q;
+
+            if *self.game.priority(v) == q {
+                // The vertices with the highest priority choose their strategy again in region_status.
+                true
+            } else if self.game.owner(v) == alpha {
+                self.strategy[*v].is_some_and(stays)
+            } else {
+                self.game
+                    .outgoing_edges(v)
+                    .all(|w| self.solved[*w] || self.region[*w] >= q)
+            }
+        })
+    }
+
+    /// Resets all the regions below `p`, such that every vertex forms a region of its own priority.
+    fn reset_below(&mut self, p: usize) {
+        for q in 0..p {
+            self.members[q].clear();
+        }
+
+        for v in self.game.iter_vertices() {
+            if !self.solved[*v] && self.region[*v] < p {
+                let priority = *self.game.priority(v);
+                self.region[*v] = priority;
+                self.strategy[*v] = None;
+                self.members[priority].push(v);
+            }
+        }
+    }
+
+    /// Resets the region `q`, such that every vertex forms a region of its own priority.
+    fn reset_region(&mut self, q: usize) {
+        for v in self.members(q) {
+            let priority = *self.game.priority(v);
+            self.region[*v] = priority;
+            self.strategy[*v] = None;
+
+            if priority != q {
+                self.members[priority].push(v);
+            }
+        }
+
+        self.members[q].retain(|v| self.region[**v] == q);
+    }
+
+    /// Returns the unsolved vertices of the region `q`, and removes the stale entries.
+    fn members(&mut self, q: usize) -> Vec<VertexIndex> {
+        let mut members = std::mem::take(&mut self.members[q]);
+        members.retain(|v| {
+            let keep = !self.solved[**v] && self.region[**v] == q && !self.temp_vertices[**v];
+            if keep {
+                self.temp_vertices.set(**v, true);
+            }
+            keep
+        });
+
+        for v in &members {
+            self.temp_vertices.set(**v, false);
+        }
+
+        self.members[q] = members.clone();
+        members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::random_parity_game;
+    use crate::solve_zielonka;
+    use crate::verify_strategy;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_priority_promotion() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 3);
+            let expected = solve_zielonka(&pg);
+
+            for region_recovery in [false, true] {
+                let (solution, strategy) = solve_priority_promotion(&pg, region_recovery);
+                assert_eq!(solution, expected, "The solution differs from Zielonka");
+                verify_strategy(&pg, &solution, &strategy).unwrap();
+            }
+        })
+    }
+}
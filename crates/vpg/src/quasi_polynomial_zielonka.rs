@@ -0,0 +1,214 @@
+#![allow(nonstandard_style)]
+//! To keep with the theory, we use capitalized variable names for sets of vertices.
+//!
+//! Implements the quasi-polynomial variant of the Zielonka algorithm, see
+//!
+//! > Karoliina Lehtinen, Sven Schewe and Dominik Wojtczak. Improving the
+//! > complexity of Parys' recursive algorithm. arXiv:1904.11810, 2019.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use log::debug;
+
+use crate::PG;
+use crate::Player;
+use crate::Predecessors;
+use crate::Priority;
+use crate::Set;
+use crate::VertexIndex;
+
+/// Solves the given parity game using the quasi-polynomial Zielonka algorithm,
+/// and returns the winning sets.
+///
+/// # Details
+///
+/// Like the Zielonka algorithm, the dominions of the opponent of the highest
+/// priority are found recursively and removed. However, every recursive call is
+/// given a precision for both players, and only dominions up to that size are
+/// guaranteed to be found. The opponent dominions are first searched for with
+/// half the precision until none is found, then once with the full precision,
+/// and then again with half the precision. This bounds the number of recursive
+/// calls quasi-polynomially in the number of vertices.
+pub fn solve_quasi_polynomial_zielonka<G: PG>(game: &G) -> [Set; 2] {
+    let V = bitvec![usize, Lsb0; 1; game.num_of_vertices()];
+    let Some(highest_prio) = game.iter_vertices().map(|v| *game.priority(v)).max() else {
+        return [V.clone(), V];
+    };
+
+    let mut solver = QuasiPolynomialZielonkaSolver::new(game);
+    let n = game.num_of_vertices();
+    let W_alpha = solver.solve_rec(V.clone(), highest_prio, n, n);
+    let W_not_alpha = V & !W_alpha.clone();
+
+    debug!("Performed {} recursive calls", solver.recursive_calls);
+
+    match Player::from_priority(&Priority::new(highest_prio)) {
+        Player::Even => [W_alpha, W_not_alpha],
+        Player::Odd => [W_not_alpha, W_alpha],
+    }
+}
+
+struct QuasiPolynomialZielonkaSolver<'a, G: PG> {
+    game: &'a G,
+
+    /// Stores the predecessors of the game.
+    predecessors: Predecessors,
+
+    /// Temporary storage for vertices per priority.
+    priority_vertices: Vec<Vec<VertexIndex>>,
+
+    /// Reused temporary queue for attractor computation.
+    temp_queue: Vec<VertexIndex>,
+
+    /// Keeps track of the total number of recursive calls.
+    recursive_calls: usize,
+}
+
+impl<'a, G: PG> QuasiPolynomialZielonkaSolver<'a, G> {
+    fn new(game: &'a G) -> Self {
+        let mut priority_vertices = Vec::new();
+        for v in game.iter_vertices() {
+            let prio = game.priority(v);
+
+            while prio >= priority_vertices.len() {
+                priority_vertices.push(Vec::new());
+            }
+
+            priority_vertices[prio].push(v);
+        }
+
+        Self {
+            game,
+            predecessors: Predecessors::new(game),
+            priority_vertices,
+            temp_queue: Vec::new(),
+            recursive_calls: 0,
+        }
+    }
+
+    /// Returns the vertices of `H` that are won by the player alpha of
+    /// priority `d`, where every priority in `H` is at most `d`.
+    ///
+    /// Every alpha dominion in `H` of at most `precision_alpha` vertices is
+    /// included in the result, and every dominion of the opponent of at most
+    /// `precision_not_alpha` vertices is excluded.
+    fn solve_rec(&mut self, mut H: Set, d: usize, precision_alpha: usize, precision_not_alpha: usize) -> Set {
+        self.recursive_calls += 1;
+
+        if !H.any() || precision_not_alpha == 0 {
+            return H;
+        }
+
+        // Skip the priorities that do not occur in H, but keep the parity of d.
+        let highest_prio = H
+            .iter_ones()
+            .map(|v| *self.game.priority(VertexIndex::new(v)))
+            .max()
+            .expect("H is not empty");
+        let d = if highest_prio % 2 == d % 2 {
+            highest_prio
+        } else {
+            highest_prio + 1
+        };
+        let alpha = Player::from_priority(&Priority::new(d));
+
+        H = self.remove_dominions(H, d, precision_alpha, precision_not_alpha / 2);
+
+        let W_not_alpha = self.solve_opponent(&H, d, precision_alpha, precision_not_alpha);
+        if W_not_alpha.any() {
+            let B = self.attractor(alpha.opponent(), &H, W_not_alpha);
+            H &= !B;
+
+            H = self.remove_dominions(H, d, precision_alpha, precision_not_alpha / 2);
+        }
+
+        H
+    }
+
+    /// Repeatedly removes the opponent dominions found with the given precision from `H`.
+    fn remove_dominions(&mut self, mut H: Set, d: usize, precision_alpha: usize, precision_not_alpha: usize) -> Set {
+        let alpha = Player::from_priority(&Priority::new(d));
+
+        loop {
+            let W_not_alpha = self.solve_opponent(&H, d, precision_alpha, precision_not_alpha);
+            if !W_not_alpha.any() {
+                return H;
+            }
+
+            let B = self.attractor(alpha.opponent(), &H, W_not_alpha);
+            H &= !B;
+        }
+    }
+
+    /// Returns the vertices won by the opponent of priority `d` in the subgame
+    /// of `H` without the attractor to the vertices of priority `d`.
+    fn solve_opponent(&mut self, H: &Set, d: usize, precision_alpha: usize, precision_not_alpha: usize) -> Set {
+        let alpha = Player::from_priority(&Priority::new(d));
+
+        let mut U = bitvec![usize, Lsb0; 0; self.game.num_of_vertices()];
+        if let Some(vertices) = self.priority_vertices.get(d) {
+            for v in vertices {
+                if H[**v] {
+                    U.set(**v, true);
+                }
+            }
+        }
+
+        let A = self.attractor(alpha, H, U);
+        let H_prime = H.clone() & !A;
+        if !H_prime.any() {
+            // This also avoids the recursion below priority zero.
+            return H_prime;
+        }
+
+        self.solve_rec(H_prime, d - 1, precision_not_alpha, precision_alpha)
+    }
+
+    /// Computes the attractor for `alpha` to the set `A` within the vertices `V`.
+    fn attractor(&mut self, alpha: Player, V: &Set, mut A: Set) -> Set {
+        self.temp_queue.clear();
+        self.temp_queue.extend(A.iter_ones().map(VertexIndex::new));
+
+        while let Some(w) = self.temp_queue.pop() {
+            for v in self.predecessors.predecessors(w) {
+                if !V[*v] || A[*v] {
+                    continue;
+                }
+
+                let attracted = self.game.owner(v) == alpha
+                    || self.game.outgoing_edges(v).all(|w_prime| !V[*w_prime] || A[*w_prime]);
+
+                if attracted {
+                    A.set(*v, true);
+                    self.temp_queue.push(v);
+                }
+            }
+        }
+
+        A
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::random_parity_game;
+    use crate::solve_zielonka;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_quasi_polynomial_zielonka() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 3);
+
+            assert_eq!(
+                solve_quasi_polynomial_zielonka(&pg),
+                solve_zielonka(&pg),
+                "The solution differs from Zielonka"
+            );
+        })
+    }
+}
@@ -0,0 +1,143 @@
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+
+use crate::PG;
+use crate::VertexIndex;
+
+/// Computes the strongly connected components of the given parity game, where
+/// only the edges (from, to) for which `filter` holds are considered.
+///
+/// # Details
+///
+/// Returns the component number of every vertex, where the components are
+/// numbered in reverse topological order, i.e., an edge between different
+/// components always goes to a component with a lower number. This is an
+/// iterative variant of Tarjan's algorithm, to avoid exhausting the stack on
+/// large games.
+pub fn scc_decomposition<G: PG>(game: &G, filter: impl Fn(VertexIndex, VertexIndex) -> bool) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; game.num_of_vertices()];
+    let mut lowlink = vec![0; game.num_of_vertices()];
+    let mut on_stack = bitvec![usize, Lsb0; 0; game.num_of_vertices()];
+    let mut component = vec![0; game.num_of_vertices()];
+
+    let mut next_index = 0;
+    let mut num_of_components = 0;
+
+    // The vertices of the components that have not been completed yet.
+    let mut stack = Vec::new();
+
+    // The depth first search stack, with the successors that must still be visited.
+    let mut dfs: Vec<(VertexIndex, Vec<VertexIndex>)> = Vec::new();
+
+    for root in game.iter_vertices() {
+        if index[*root] != UNVISITED {
+            continue;
+        }
+
+        let mut discovered = Some(root);
+        loop {
+            if let Some(v) = discovered.take() {
+                index[*v] = next_index;
+                lowlink[*v] = next_index;
+                next_index += 1;
+
+                stack.push(v);
+                on_stack.set(*v, true);
+                dfs.push((v, game.outgoing_edges(v).filter(|w| filter(v, *w)).collect()));
+            }
+
+            let Some((v, successors)) = dfs.last_mut() else {
+                break;
+            };
+            let v = *v;
+
+            if let Some(w) = successors.pop() {
+                if index[*w] == UNVISITED {
+                    discovered = Some(w);
+                } else if on_stack[*w] {
+                    lowlink[*v] = lowlink[*v].min(index[*w]);
+                }
+            } else {
+                dfs.pop();
+                if let Some((u, _)) = dfs.last() {
+                    lowlink[**u] = lowlink[**u].min(lowlink[*v]);
+                }
+
+                if lowlink[*v] == index[*v] {
+                    // The vertex is the root of a component, which consists of the vertices above it on the stack.
+                    while let Some(w) = stack.pop() {
+                        on_stack.set(*w, false);
+                        component[*w] = num_of_components;
+
+                        if w == v {
+                            break;
+                        }
+                    }
+
+                    num_of_components += 1;
+                }
+            }
+        }
+    }
+
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::PG;
+    use crate::random_parity_game;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_scc_decomposition() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 50, 5, 2);
+            let component = scc_decomposition(&pg, |_, _| true);
+
+            // Compute the reachable vertices to check that two vertices share a component iff they reach each other.
+            let reachable: Vec<Vec<bool>> = pg
+                .iter_vertices()
+                .map(|v| {
+                    let mut visited = vec![false; pg.num_of_vertices()];
+                    let mut stack = vec![v];
+                    visited[*v] = true;
+
+                    while let Some(u) = stack.pop() {
+                        for w in pg.outgoing_edges(u) {
+                            if !visited[*w] {
+                                visited[*w] = true;
+                                stack.push(w);
+                            }
+                        }
+                    }
+
+                    visited
+                })
+                .collect();
+
+            for v in pg.iter_vertices() {
+                for w in pg.iter_vertices() {
+                    assert_eq!(
+                        component[*v] == component[*w],
+                        reachable[*v][*w] && reachable[*w][*v],
+                        "Vertices {v} and {w} are not correctly decomposed"
+                    );
+                }
+
+                for w in pg.outgoing_edges(v) {
+                    assert!(
+                        component[*w] <= component[*v],
+                        "The components are not in reverse topological order"
+                    );
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,662 @@
+//! Simplifications of (variability) parity games that preserve the winners of all vertices.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use oxidd::BooleanFunction;
+use oxidd::ManagerRef;
+use oxidd::bdd::BDDFunction;
+use oxidd::bdd::BDDManagerRef;
+
+use merc_symbolic::minus;
+use merc_utilities::MercError;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Priority;
+use crate::Set;
+use crate::Strategy;
+use crate::Submap;
+use crate::VariabilityParityGame;
+use crate::VariabilityStrategy;
+use crate::VertexIndex;
+
+/// Relates a simplified game to the original game, see [simplify_parity_game]
+/// and [simplify_variability_parity_game].
+pub struct Simplification<E> {
+    /// The vertex of the simplified game that every vertex is mapped to.
+    mapping: Vec<VertexIndex>,
+
+    /// For every vertex that is kept, the edges that survived the
+    /// simplification as the target in the simplified game, the original
+    /// successor and the label of the edge. This is empty for the vertices
+    /// that are removed, of which the single edge is forced, and for dead ends.
+    edges: Vec<Vec<(VertexIndex, VertexIndex, E)>>,
+}
+
+impl<E> Simplification<E> {
+    /// Returns the vertex of the simplified game that every vertex is mapped to.
+    pub fn mapping(&self) -> &[VertexIndex] {
+        &self.mapping
+    }
+}
+
+/// Simplifies the given parity game, and returns the simplified game together
+/// with its relation to the original game.
+///
+/// # Details
+///
+/// Every vertex is won by the same player as the vertex that it is mapped to,
+/// see [lift_solution] and [lift_strategy] to obtain the solution of the
+/// original game. The following simplifications are applied:
+///
+///  - A self-loop that is winning for the owner of a vertex replaces all its
+///    other edges, and a self-loop that is losing for its owner is removed when
+///    the vertex has other edges.
+///  - A dead end is lost by its owner, so it becomes a self-loop with a
+///    priority of the opponent.
+///  - The priority of a vertex is raised to the lowest priority of its
+///    successors, because only the successors matter when it is visited
+///    infinitely often.
+///  - A vertex with a single successor of at least its priority is removed, and
+///    its predecessors move to the successor directly.
+///  - The priorities are compressed, such that there are no gaps and the
+///    consecutive priorities have a different parity.
+///
+/// The resulting game is total.
+pub fn simplify_parity_game(game: &ParityGame) -> (ParityGame, Simplification<()>) {
+    let mut graph = Graph {
+        owners: game.iter_vertices().map(|v| game.owner(v)).collect(),
+        priorities: game.iter_vertices().map(|v| game.priority(v)).collect(),
+        edges: game
+            .iter_vertices()
+            .map(|v| game.outgoing_edges(v).map(|w| (w, ())).collect())
+            .collect(),
+    };
+
+    // Only the vertices with a single original successor are removed, such that their strategy is forced.
+    let single: Set = graph.edges.iter().map(|edges| edges.len() == 1).collect();
+
+    for v in game.iter_vertices() {
+        let owner = graph.owners[*v];
+        let edges = &mut graph.edges[*v];
+
+        if edges.is_empty() {
+            graph.priorities[*v] = dead_end_priority(owner);
+            edges.push((v, ()));
+        } else if edges.iter().any(|(w, _)| *w == v) {
+            if Player::from_priority(&graph.priorities[*v]) == owner {
+                edges.clear();
+                edges.push((v, ()));
+            } else if edges.iter().any(|(w, _)| *w != v) {
+                edges.retain(|(w, _)| *w != v);
+            }
+        }
+    }
+
+    propagate_priorities(&mut graph);
+    let representative = single_successor_representatives(&graph, |v| single[*v]);
+    let (mut simplified, mapping) = rebuild(&graph, game.initial_vertex(), &representative, |_, _| Ok(()))
+        .expect("Merging edges of a parity game can not fail");
+    compress_priorities(&mut simplified.priorities);
+
+    let edges = surviving_edges(&graph, &representative, &mapping, |v| {
+        game.outgoing_edges(v).next().is_none()
+    });
+
+    let simplified_edges = simplified.edges;
+    let simplified = ParityGame::from_edges(
+        VertexIndex::new(0),
+        simplified.owners,
+        simplified.priorities,
+        false,
+        || {
+            simplified_edges
+                .iter()
+                .enumerate()
+                .flat_map(|(v, edges)| edges.iter().map(move |(w, _)| (VertexIndex::new(v), *w)))
+        },
+    );
+
+    (simplified, Simplification { mapping, edges })
+}
+
+/// Simplifies the given variability parity game, and returns the simplified
+/// game together with its relation to the original game.
+///
+/// # Details
+///
+/// Applies the same simplifications as [simplify_parity_game], where the
+/// self-loops are only taken for the configurations of the self-loop, dead ends
+/// are the vertices without any outgoing edge, and a single successor must be
+/// enabled for all configurations of the game.
+pub fn simplify_variability_parity_game(
+    manager_ref: &BDDManagerRef,
+    vpg: &VariabilityParityGame,
+) -> Result<(VariabilityParityGame, Simplification<BDDFunction>), MercError> {
+    let mut graph = Graph {
+        owners: vpg.iter_vertices().map(|v| vpg.owner(v)).collect(),
+        priorities: vpg.iter_vertices().map(|v| vpg.priority(v)).collect(),
+        edges: vpg
+            .iter_vertices()
+            .map(|v| {
+                vpg.outgoing_conf_edges(v)
+                    .map(|edge| (edge.to(), edge.configuration().clone()))
+                    .collect()
+            })
+            .collect(),
+    };
+
+    // Only the vertices with a single original edge are removed, such that their strategy is forced.
+    let single: Set = graph.edges.iter().map(|edges| edges.len() == 1).collect();
+
+    let false_bdd = manager_ref.with_manager_shared(|manager| BDDFunction::f(manager));
+    for v in vpg.iter_vertices() {
+        let owner = graph.owners[*v];
+        let edges = &mut graph.edges[*v];
+
+        if edges.is_empty() {
+            graph.priorities[*v] = dead_end_priority(owner);
+            edges.push((v, vpg.configuration().clone()));
+            continue;
+        }
+
+        let mut self_loop = false_bdd.clone();
+        let mut others = false_bdd.clone();
+        for (w, configuration) in edges.iter() {
+            if *w == v {
+                self_loop = self_loop.or(configuration)?;
+            } else {
+                others = others.or(configuration)?;
+            }
+        }
+
+        if !self_loop.satisfiable() {
+            continue;
+        }
+
+        let mut result = Vec::new();
+        if Player::from_priority(&graph.priorities[*v]) == owner {
+            // The owner takes the self-loop whenever it is enabled.
+            result.push((v, self_loop.clone()));
+            for (w, configuration) in edges.drain(..) {
+                if w != v {
+                    let configuration = minus(&configuration, &self_loop)?;
+                    if configuration.satisfiable() {
+                        result.push((w, configuration));
+                    }
+                }
+            }
+        } else {
+            // The owner only takes the self-loop when there is no other edge.
+            let self_loop = minus(&self_loop, &others)?;
+            if self_loop.satisfiable() {
+                result.push((v, self_loop));
+            }
+            result.extend(edges.drain(..).filter(|(w, _)| *w != v));
+        }
+
+        *edges = result;
+    }
+
+    propagate_priorities(&mut graph);
+
+    // A single successor can only be taken directly when it is enabled in all configurations.
+    let mut forced = bitvec![usize, Lsb0; 0; vpg.num_of_vertices()];
+    for v in vpg.iter_vertices() {
+        if let [(_, configuration)] = graph.edges[*v].as_slice() {
+            forced.set(
+                *v,
+                single[*v] && !minus(vpg.configuration(), configuration)?.satisfiable(),
+            );
+        }
+    }
+
+    let representative = single_successor_representatives(&graph, |v| forced[*v]);
+    let (mut simplified, mapping) = rebuild(&graph, vpg.initial_vertex(), &representative, |lhs, rhs| {
+        *lhs = lhs.or(rhs)?;
+        Ok(())
+    })?;
+    compress_priorities(&mut simplified.priorities);
+
+    let edges = surviving_edges(&graph, &representative, &mapping, |v| {
+        vpg.outgoing_conf_edges(v).next().is_none()
+    });
+
+    let simplified_edges = simplified.edges;
+    let simplified = VariabilityParityGame::from_edges(
+        manager_ref,
+        VertexIndex::new(0),
+        simplified.owners,
+        simplified.priorities,
+        vpg.configuration().clone(),
+        vpg.variables().clone(),
+        || {
+            simplified_edges.iter().enumerate().flat_map(|(v, edges)| {
+                edges
+                    .iter()
+                    .map(move |(w, configuration)| (VertexIndex::new(v), configuration.clone(), *w))
+            })
+        },
+    );
+
+    Ok((simplified, Simplification { mapping, edges }))
+}
+
+/// Returns the solution of the original game, given the simplification and the solution of the simplified game.
+pub fn lift_solution<E>(simplification: &Simplification<E>, solution: &[Set; 2]) -> [Set; 2] {
+    let mapping = simplification.mapping();
+    let mut result = [
+        bitvec![usize, Lsb0; 0; mapping.len()],
+        bitvec![usize, Lsb0; 0; mapping.len()],
+    ];
+
+    for (v, w) in mapping.iter().enumerate() {
+        for (index, winning) in solution.iter().enumerate() {
+            result[index].set(v, winning[**w]);
+        }
+    }
+
+    result
+}
+
+/// Returns a winning strategy for the original game, given its (lifted)
+/// solution, the simplification, and the strategy of the simplified game.
+///
+/// # Details
+///
+/// The strategy only takes the edges that survived the simplification, since
+/// for example a removed self-loop can be mapped to the same vertex.
+pub fn lift_strategy(
+    game: &ParityGame,
+    simplification: &Simplification<()>,
+    solution: &[Set; 2],
+    strategy: &Strategy,
+) -> Strategy {
+    let mut result = vec![None; game.num_of_vertices()];
+
+    for v in game.iter_vertices() {
+        if !solution[game.owner(v).to_index()][*v] {
+            continue;
+        }
+
+        let edges = &simplification.edges[*v];
+        result[*v] = if edges.is_empty() {
+            // The successor is forced, which covers the vertices that have been removed.
+            game.outgoing_edges(v).next()
+        } else {
+            let target = strategy[*simplification.mapping[*v]].expect("Every vertex won by its owner has a strategy");
+            edges
+                .iter()
+                .find(|(simplified, _, _)| *simplified == target)
+                .map(|(_, w, _)| *w)
+        };
+    }
+
+    result
+}
+
+/// Returns the solution of the original variability parity game, given the
+/// simplification and the solution of the simplified game.
+pub fn lift_variability_solution(
+    manager_ref: &BDDManagerRef,
+    simplification: &Simplification<BDDFunction>,
+    solution: &[Submap; 2],
+) -> [Submap; 2] {
+    let mapping = simplification.mapping();
+    let false_bdd = manager_ref.with_manager_shared(|manager| BDDFunction::f(manager));
+    let mut result = [
+        Submap::new(manager_ref, false_bdd.clone(), mapping.len()),
+        Submap::new(manager_ref, false_bdd, mapping.len()),
+    ];
+
+    manager_ref.with_manager_shared(|manager| {
+        for (v, w) in mapping.iter().enumerate() {
+            for (index, winning) in solution.iter().enumerate() {
+                result[index].set(manager, VertexIndex::new(v), winning[*w].clone());
+            }
+        }
+    });
+
+    result
+}
+
+/// Returns a winning strategy for the original variability parity game, given
+/// the simplification and the strategy of the simplified game.
+///
+/// # Details
+///
+/// The strategy only takes the edges, and configurations, that survived the
+/// simplification, and chooses exactly one successor for every configuration.
+pub fn lift_variability_strategy(
+    manager_ref: &BDDManagerRef,
+    vpg: &VariabilityParityGame,
+    simplification: &Simplification<BDDFunction>,
+    strategy: &VariabilityStrategy,
+) -> Result<VariabilityStrategy, MercError> {
+    let false_bdd = manager_ref.with_manager_shared(|manager| BDDFunction::f(manager));
+    let mut result = vec![Vec::new(); vpg.num_of_vertices()];
+
+    for v in vpg.iter_vertices() {
+        let edges = &simplification.edges[*v];
+
+        if edges.is_empty() {
+            // The successor is forced, which covers the vertices that have been removed.
+            if let Some(edge) = vpg.outgoing_conf_edges(v).next() {
+                result[*v].push((edge.to(), edge.configuration().clone()));
+            }
+            continue;
+        }
+
+        // The configurations for which a successor has already been chosen.
+        let mut covered = false_bdd.clone();
+        for (target, configuration) in &strategy[*simplification.mapping[*v]] {
+            for (_, w, surviving) in edges.iter().filter(|(simplified, _, _)| simplified == target) {
+                let chosen = minus(&configuration.and(surviving)?, &covered)?;
+                if chosen.satisfiable() {
+                    covered = covered.or(&chosen)?;
+                    result[*v].push((*w, chosen));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// The intermediate representation of a game during simplification, where every edge is labelled by an `E`.
+struct Graph<E> {
+    owners: Vec<Player>,
+    priorities: Vec<Priority>,
+    edges: Vec<Vec<(VertexIndex, E)>>,
+}
+
+/// Returns the priority of a dead end, which is lost by the given owner.
+fn dead_end_priority(owner: Player) -> Priority {
+    match owner {
+        Player::Even => Priority::new(1),
+        Player::Odd => Priority::new(0),
+    }
+}
+
+/// Raises the priority of every vertex to the lowest priority of its successors, until nothing changes.
+fn propagate_priorities<E>(graph: &mut Graph<E>) {
+    let num_of_vertices = graph.owners.len();
+
+    let mut predecessors = vec![Vec::new(); num_of_vertices];
+    for (v, edges) in graph.edges.iter().enumerate() {
+        for (w, _) in edges {
+            predecessors[**w].push(VertexIndex::new(v));
+        }
+    }
+
+    let mut queue: Vec<VertexIndex> = (0..num_of_vertices).map(VertexIndex::new).collect();
+    let mut queued = bitvec![usize, Lsb0; 1; num_of_vertices];
+    while let Some(v) = queue.pop() {
+        queued.set(*v, false);
+
+        let lowest = graph.edges[*v].iter().map(|(w, _)| graph.priorities[**w]).min();
+        if let Some(lowest) = lowest {
+            if lowest > graph.priorities[*v] {
+                graph.priorities[*v] = lowest;
+
+                for u in &predecessors[*v] {
+                    if !queued[**u] {
+                        queued.set(**u, true);
+                        queue.push(*u);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns for every vertex the vertex that it is merged into, where a vertex
+/// with a single successor of at least its priority, for which `forced` holds,
+/// is merged into its successor.
+fn single_successor_representatives<E>(graph: &Graph<E>, forced: impl Fn(VertexIndex) -> bool) -> Vec<VertexIndex> {
+    let num_of_vertices = graph.owners.len();
+
+    let next: Vec<Option<VertexIndex>> = (0..num_of_vertices)
+        .map(VertexIndex::new)
+        .map(|v| match graph.edges[*v].as_slice() {
+            [(w, _)] if *w != v && graph.priorities[*v] <= graph.priorities[**w] && forced(v) => Some(*w),
+            _ => None,
+        })
+        .collect();
+
+    const UNVISITED: usize = 0;
+    const ON_PATH: usize = 1;
+    const DONE: usize = 2;
+
+    let mut state = vec![UNVISITED; num_of_vertices];
+    let mut representative: Vec<VertexIndex> = (0..num_of_vertices).map(VertexIndex::new).collect();
+    let mut path = Vec::new();
+
+    for v in (0..num_of_vertices).map(VertexIndex::new) {
+        let mut current = v;
+        let result = loop {
+            match state[*current] {
+                DONE => break representative[*current],
+                // A cycle of single successors, of which the current vertex is kept.
+                ON_PATH => break current,
+                _ => {}
+            }
+
+            state[*current] = ON_PATH;
+            path.push(current);
+
+            match next[*current] {
+                Some(w) => current = w,
+                None => break current,
+            }
+        };
+
+        for u in path.drain(..) {
+            representative[*u] = result;
+            state[*u] = DONE;
+        }
+    }
+
+    representative
+}
+
+/// Removes the vertices that are merged into another vertex, and renumbers the
+/// remaining vertices such that the initial vertex becomes vertex 0. The edges
+/// to the same vertex are combined by `merge`.
+///
+/// Returns the new graph and the new vertex of every original vertex.
+fn rebuild<E: Clone>(
+    graph: &Graph<E>,
+    initial_vertex: VertexIndex,
+    representative: &[VertexIndex],
+    mut merge: impl FnMut(&mut E, &E) -> Result<(), MercError>,
+) -> Result<(Graph<E>, Vec<VertexIndex>), MercError> {
+    let num_of_vertices = graph.owners.len();
+
+    let mut kept = vec![representative[*initial_vertex]];
+    kept.extend(
+        (0..num_of_vertices)
+            .map(VertexIndex::new)
+            .filter(|v| representative[**v] == *v && *v != representative[*initial_vertex]),
+    );
+
+    let mut new_index = vec![VertexIndex::new(0); num_of_vertices];
+    for (index, v) in kept.iter().enumerate() {
+        new_index[**v] = VertexIndex::new(index);
+    }
+
+    // The position of the edge to every vertex for the current vertex, to merge duplicates.
+    let mut position: Vec<Option<usize>> = vec![None; kept.len()];
+
+    let mut result = Graph {
+        owners: Vec::with_capacity(kept.len()),
+        priorities: Vec::with_capacity(kept.len()),
+        edges: Vec::with_capacity(kept.len()),
+    };
+    for v in &kept {
+        let mut edges: Vec<(VertexIndex, E)> = Vec::new();
+        for (w, label) in &graph.edges[**v] {
+            let w = new_index[*representative[**w]];
+            match position[*w] {
+                Some(index) => merge(&mut edges[index].1, label)?,
+                None => {
+                    position[*w] = Some(edges.len());
+                    edges.push((w, label.clone()));
+                }
+            }
+        }
+
+        for (w, _) in &edges {
+            position[**w] = None;
+        }
+
+        result.owners.push(graph.owners[**v]);
+        result.priorities.push(graph.priorities[**v]);
+        result.edges.push(edges);
+    }
+
+    let mapping = representative.iter().map(|v| new_index[**v]).collect();
+    Ok((result, mapping))
+}
+
+/// Returns for every vertex that is kept the edges of the given graph, as the
+/// target in the simplified game, the original successor and the label. The
+/// self-loop that is added to a dead end is not an original edge.
+fn surviving_edges<E: Clone>(
+    graph: &Graph<E>,
+    representative: &[VertexIndex],
+    mapping: &[VertexIndex],
+    dead_end: impl Fn(VertexIndex) -> bool,
+) -> Vec<Vec<(VertexIndex, VertexIndex, E)>> {
+    (0..graph.owners.len())
+        .map(VertexIndex::new)
+        .map(|v| {
+            if representative[*v] != v || dead_end(v) {
+                return Vec::new();
+            }
+
+            graph.edges[*v]
+                .iter()
+                .map(|(w, label)| (mapping[**w], *w, label.clone()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Renumbers the priorities such that there are no gaps, and consecutive priorities have a different parity.
+fn compress_priorities(priorities: &mut [Priority]) {
+    let mut occurring: Vec<Priority> = priorities.to_vec();
+    occurring.sort_unstable();
+    occurring.dedup();
+
+    let Some(lowest) = occurring.first() else {
+        return;
+    };
+
+    // Maps every occurring priority to its compressed priority.
+    let mut compressed = vec![Priority::new(0); *occurring[occurring.len() - 1] + 1];
+    let mut current = **lowest % 2;
+    for priority in &occurring {
+        if **priority % 2 != current % 2 {
+            current += 1;
+        }
+
+        compressed[**priority] = Priority::new(current);
+    }
+
+    for priority in priorities.iter_mut() {
+        *priority = compressed[**priority];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::Timing;
+    use merc_utilities::random_test;
+    use merc_utilities::random_test_seeded;
+    use rand::rngs::StdRng;
+
+    use crate::ZielonkaVariant;
+    use crate::random_parity_game;
+    use crate::random_variability_parity_game;
+    use crate::solve_variability_zielonka_strategy;
+    use crate::solve_zielonka;
+    use crate::solve_zielonka_strategy;
+    use crate::verify_strategy;
+    use crate::verify_variability_strategy;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_simplify_parity_game() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 3);
+            let (simplified, simplification) = simplify_parity_game(&pg);
+            assert!(simplified.is_total(), "The simplified game should be total");
+
+            let (solution, strategy) = solve_zielonka_strategy(&simplified);
+            let solution = lift_solution(&simplification, &solution);
+            let strategy = lift_strategy(&pg, &simplification, &solution, &strategy);
+
+            assert_eq!(
+                solution,
+                solve_zielonka(&pg),
+                "The lifted solution differs from Zielonka"
+            );
+            verify_strategy(&pg, &solution, &strategy).unwrap();
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_simplify_parity_game_dead_ends() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, false, 100, 8, 3);
+            let (simplified, simplification) = simplify_parity_game(&pg);
+
+            let (solution, strategy) = solve_zielonka_strategy(&simplified);
+            let solution = lift_solution(&simplification, &solution);
+            let strategy = lift_strategy(&pg, &simplification, &solution, &strategy);
+
+            verify_strategy(&pg, &solution, &strategy).unwrap();
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_random_simplify_variability_parity_game() {
+        random_test(100, check_simplify_variability_parity_game)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_simplify_variability_parity_game_removed_self_loop() {
+        // A losing self-loop is removed, and the vertex with a single successor merged into it is mapped to the same vertex.
+        random_test_seeded(10506078825188591365, 100, check_simplify_variability_parity_game)
+    }
+
+    /// Checks that the lifted strategy of a simplified random variability parity game is winning.
+    fn check_simplify_variability_parity_game(rng: &mut StdRng) {
+        let manager_ref = oxidd::bdd::new_manager(2048, 1024, 1);
+        let vpg = random_variability_parity_game(&manager_ref, rng, true, 20, 3, 3, 3).unwrap();
+        let (simplified, simplification) = simplify_variability_parity_game(&manager_ref, &vpg).unwrap();
+
+        let (solution, strategy) =
+            solve_variability_zielonka_strategy(&manager_ref, &simplified, ZielonkaVariant::Family, false).unwrap();
+        let solution = lift_variability_solution(&manager_ref, &simplification, &solution);
+        let strategy = lift_variability_strategy(&manager_ref, &vpg, &simplification, &strategy).unwrap();
+
+        verify_variability_strategy(&vpg, &solution, &strategy, &Timing::new()).unwrap();
+    }
+
+    #[test]
+    fn test_compress_priorities() {
+        let mut priorities: Vec<Priority> = [3, 5, 8, 10, 3, 11].into_iter().map(Priority::new).collect();
+        compress_priorities(&mut priorities);
+
+        let expected: Vec<Priority> = [1, 1, 2, 2, 1, 3].into_iter().map(Priority::new).collect();
+        assert_eq!(priorities, expected);
+    }
+}
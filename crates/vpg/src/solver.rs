@@ -0,0 +1,403 @@
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use oxidd::BooleanFunction;
+use oxidd::ManagerRef;
+use oxidd::bdd::BDDFunction;
+use oxidd::bdd::BDDManagerRef;
+
+use merc_symbolic::minus;
+use merc_utilities::MercError;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Predecessors;
+use crate::Set;
+use crate::Strategy;
+use crate::Submap;
+use crate::VariabilityParityGame;
+use crate::VariabilityPredecessors;
+use crate::VariabilityStrategy;
+use crate::VertexIndex;
+use crate::scc_decomposition;
+use crate::solve_fixpoint_iteration;
+use crate::solve_priority_promotion;
+use crate::solve_quasi_polynomial_zielonka;
+use crate::solve_tangle_learning;
+use crate::solve_zielonka_strategy;
+
+/// The algorithm used to solve a parity game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ParityGameSolver {
+    /// The recursive Zielonka algorithm.
+    Zielonka,
+    /// Priority promotion (PP).
+    PriorityPromotion,
+    /// Priority promotion with region recovery (RR).
+    RegionRecovery,
+    /// Tangle learning.
+    TangleLearning,
+    /// Distraction based fixpoint iteration, which does not compute a strategy.
+    FixpointIteration,
+    /// The quasi-polynomial variant of Zielonka, which does not compute a strategy.
+    QuasiPolynomialZielonka,
+}
+
+impl ParityGameSolver {
+    /// Returns true iff the solver also computes a winning strategy.
+    pub fn computes_strategy(&self) -> bool {
+        !matches!(
+            self,
+            ParityGameSolver::FixpointIteration | ParityGameSolver::QuasiPolynomialZielonka
+        )
+    }
+}
+
+/// Solves the given parity game with the given solver, and returns the winning
+/// sets together with a winning strategy when the solver computes one.
+pub fn solve_parity_game(game: &ParityGame, solver: ParityGameSolver) -> ([Set; 2], Option<Strategy>) {
+    match solver {
+        ParityGameSolver::Zielonka => {
+            let (solution, strategy) = solve_zielonka_strategy(game);
+            (solution, Some(strategy))
+        }
+        ParityGameSolver::PriorityPromotion => {
+            let (solution, strategy) = solve_priority_promotion(game, false);
+            (solution, Some(strategy))
+        }
+        ParityGameSolver::RegionRecovery => {
+            let (solution, strategy) = solve_priority_promotion(game, true);
+            (solution, Some(strategy))
+        }
+        ParityGameSolver::TangleLearning => {
+            let (solution, strategy) = solve_tangle_learning(game);
+            (solution, Some(strategy))
+        }
+        ParityGameSolver::FixpointIteration => (solve_fixpoint_iteration(game), None),
+        ParityGameSolver::QuasiPolynomialZielonka => (solve_quasi_polynomial_zielonka(game), None),
+    }
+}
+
+/// Solves the given parity game by solving its strongly connected components
+/// bottom-up, where every component is solved by the given `solve` function.
+///
+/// # Details
+///
+/// The vertices of a component that can be attracted to the winning sets of
+/// the components below it are solved directly. The remaining vertices form a
+/// total subgame, because every edge to a lower component leads to the winning
+/// set of the opponent of its owner, which is then solved by `solve`. The
+/// strategy is only returned when `solve` returns a strategy for every
+/// component.
+pub fn solve_parity_game_scc(
+    game: &ParityGame,
+    mut solve: impl FnMut(&ParityGame) -> ([Set; 2], Option<Strategy>),
+) -> ([Set; 2], Option<Strategy>) {
+    let component = scc_decomposition(game, |_, _| true);
+    let num_of_components = component.iter().max().map_or(0, |c| c + 1);
+
+    let mut members = vec![Vec::new(); num_of_components];
+    for v in game.iter_vertices() {
+        members[component[*v]].push(v);
+    }
+
+    let predecessors = Predecessors::new(game);
+    let mut solution = [
+        bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+        bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+    ];
+    let mut strategy = Some(vec![None; game.num_of_vertices()]);
+
+    // The index of every vertex in the subgame of its component.
+    let mut local_index = vec![0; game.num_of_vertices()];
+
+    // The components are numbered in reverse topological order, so the lower components are solved first.
+    for (c, vertices) in members.iter().enumerate() {
+        for player in [Player::Even, Player::Odd] {
+            let mut queue = vertices.clone();
+            while let Some(v) = queue.pop() {
+                if solution[0][*v] || solution[1][*v] {
+                    continue;
+                }
+
+                let winning = &solution[player.to_index()];
+                let choice = if game.owner(v) == player {
+                    game.outgoing_edges(v).find(|w| winning[**w])
+                } else {
+                    None
+                };
+
+                if choice.is_some() || (game.owner(v) != player && game.outgoing_edges(v).all(|w| winning[*w])) {
+                    solution[player.to_index()].set(*v, true);
+                    if let Some(strategy) = &mut strategy {
+                        strategy[*v] = choice;
+                    }
+
+                    queue.extend(predecessors.predecessors(v).filter(|u| component[**u] == c));
+                }
+            }
+        }
+
+        let remaining: Vec<VertexIndex> = vertices
+            .iter()
+            .filter(|v| !solution[0][***v] && !solution[1][***v])
+            .cloned()
+            .collect();
+        if remaining.is_empty() {
+            continue;
+        }
+
+        for (index, v) in remaining.iter().enumerate() {
+            local_index[**v] = index;
+        }
+
+        let is_remaining = |w: VertexIndex| component[*w] == c && !solution[0][*w] && !solution[1][*w];
+        let local_index = &local_index;
+        let subgame = ParityGame::from_edges(
+            VertexIndex::new(0),
+            remaining.iter().map(|v| game.owner(*v)).collect(),
+            remaining.iter().map(|v| game.priority(*v)).collect(),
+            false,
+            || {
+                remaining.iter().enumerate().flat_map(|(index, v)| {
+                    game.outgoing_edges(*v)
+                        .filter(|w| is_remaining(*w))
+                        .map(move |w| (VertexIndex::new(index), VertexIndex::new(local_index[*w])))
+                })
+            },
+        );
+
+        let (subgame_solution, subgame_strategy) = solve(&subgame);
+        for (index, v) in remaining.iter().enumerate() {
+            for (player, winning) in subgame_solution.iter().enumerate() {
+                if winning[index] {
+                    solution[player].set(**v, true);
+                }
+            }
+        }
+
+        match (&mut strategy, subgame_strategy) {
+            (Some(strategy), Some(subgame_strategy)) => {
+                for (index, v) in remaining.iter().enumerate() {
+                    strategy[**v] = subgame_strategy[index].map(|w| remaining[*w]);
+                }
+            }
+            _ => strategy = None,
+        }
+    }
+
+    (solution, strategy)
+}
+
+/// Solves the given variability parity game by solving its strongly connected
+/// components bottom-up, where every component is solved by the given `solve`
+/// function.
+///
+/// # Details
+///
+/// The same as [solve_parity_game_scc], but the vertices are attracted to the
+/// winning sets of the lower components for each configuration separately. The
+/// subgame of a component contains the remaining configurations of every
+/// vertex, and a self-loop for the configurations in which it has already been
+/// solved to keep it total.
+pub fn solve_variability_parity_game_scc(
+    manager_ref: &BDDManagerRef,
+    vpg: &VariabilityParityGame,
+    mut solve: impl FnMut(&VariabilityParityGame) -> Result<([Submap; 2], VariabilityStrategy), MercError>,
+) -> Result<([Submap; 2], VariabilityStrategy), MercError> {
+    let component = scc_decomposition(vpg, |_, _| true);
+    let num_of_components = component.iter().max().map_or(0, |c| c + 1);
+
+    let mut members = vec![Vec::new(); num_of_components];
+    for v in vpg.iter_vertices() {
+        members[component[*v]].push(v);
+    }
+
+    let predecessors = VariabilityPredecessors::new(manager_ref, vpg);
+    let false_bdd = manager_ref.with_manager_shared(|manager| BDDFunction::f(manager));
+    let mut solution = [
+        Submap::new(manager_ref, false_bdd.clone(), vpg.num_of_vertices()),
+        Submap::new(manager_ref, false_bdd.clone(), vpg.num_of_vertices()),
+    ];
+    let mut strategy: VariabilityStrategy = vec![Vec::new(); vpg.num_of_vertices()];
+
+    // The configurations in which the given vertex has not been solved yet.
+    let unsolved = |solution: &[Submap; 2], v: VertexIndex| -> Result<BDDFunction, MercError> {
+        Ok(minus(vpg.configuration(), &solution[0][v].or(&solution[1][v])?)?)
+    };
+
+    // The index of every vertex in the subgame of its component.
+    let mut local_index = vec![0; vpg.num_of_vertices()];
+
+    // The components are numbered in reverse topological order, so the lower components are solved first.
+    for (c, vertices) in members.iter().enumerate() {
+        for player in [Player::Even, Player::Odd] {
+            let mut queue = vertices.clone();
+            while let Some(v) = queue.pop() {
+                let remaining = unsolved(&solution, v)?;
+                if !remaining.satisfiable() {
+                    continue;
+                }
+
+                let winning = &solution[player.to_index()];
+                let mut attracted = false_bdd.clone();
+                if vpg.owner(v) == player {
+                    for edge in vpg.outgoing_conf_edges(v) {
+                        let chosen = minus(
+                            &edge.configuration().and(&winning[edge.to()])?.and(&remaining)?,
+                            &attracted,
+                        )?;
+                        if chosen.satisfiable() {
+                            attracted = attracted.or(&chosen)?;
+                            strategy[*v].push((edge.to(), chosen));
+                        }
+                    }
+                } else {
+                    attracted = remaining;
+                    for edge in vpg.outgoing_conf_edges(v) {
+                        attracted = minus(&attracted, &minus(edge.configuration(), &winning[edge.to()])?)?;
+                    }
+                }
+
+                if attracted.satisfiable() {
+                    let winning = winning[v].or(&attracted)?;
+                    manager_ref.with_manager_shared(|manager| solution[player.to_index()].set(manager, v, winning));
+
+                    queue.extend(
+                        predecessors
+                            .predecessors(v)
+                            .map(|(u, _)| u)
+                            .filter(|u| component[**u] == c),
+                    );
+                }
+            }
+        }
+
+        let mut remaining = Vec::new();
+        for v in vertices {
+            let configuration = unsolved(&solution, *v)?;
+            if configuration.satisfiable() {
+                remaining.push((*v, configuration));
+            }
+        }
+
+        if remaining.is_empty() {
+            continue;
+        }
+
+        for (index, (v, _)) in remaining.iter().enumerate() {
+            local_index[**v] = index;
+        }
+
+        let mut edges = Vec::new();
+        for (index, (v, configuration)) in remaining.iter().enumerate() {
+            for edge in vpg.outgoing_conf_edges(*v) {
+                if component[*edge.to()] != c {
+                    continue;
+                }
+
+                let target = unsolved(&solution, edge.to())?;
+                let enabled = edge.configuration().and(configuration)?.and(&target)?;
+                if enabled.satisfiable() {
+                    edges.push((
+                        VertexIndex::new(index),
+                        enabled,
+                        VertexIndex::new(local_index[*edge.to()]),
+                    ));
+                }
+            }
+
+            let solved = minus(vpg.configuration(), configuration)?;
+            if solved.satisfiable() {
+                edges.push((VertexIndex::new(index), solved, VertexIndex::new(index)));
+            }
+        }
+
+        let subgame = VariabilityParityGame::from_edges(
+            manager_ref,
+            VertexIndex::new(0),
+            remaining.iter().map(|(v, _)| vpg.owner(*v)).collect(),
+            remaining.iter().map(|(v, _)| vpg.priority(*v)).collect(),
+            vpg.configuration().clone(),
+            vpg.variables().clone(),
+            || edges.iter().cloned(),
+        );
+
+        let (subgame_solution, subgame_strategy) = solve(&subgame)?;
+        for (index, (v, configuration)) in remaining.iter().enumerate() {
+            let index = VertexIndex::new(index);
+            for (player, winning) in subgame_solution.iter().enumerate() {
+                let winning = solution[player][*v].or(&winning[index].and(configuration)?)?;
+                manager_ref.with_manager_shared(|manager| solution[player].set(manager, *v, winning));
+            }
+
+            // Only the configurations that are won by the owner in this component are relevant.
+            let owned = subgame_solution[vpg.owner(*v).to_index()][index].and(configuration)?;
+            for (w, chosen) in &subgame_strategy[*index] {
+                let chosen = chosen.and(&owned)?;
+                if chosen.satisfiable() {
+                    strategy[**v].push((remaining[**w].0, chosen));
+                }
+            }
+        }
+    }
+
+    Ok((solution, strategy))
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::Timing;
+    use merc_utilities::random_test;
+
+    use crate::ZielonkaVariant;
+    use crate::random_parity_game;
+    use crate::random_variability_parity_game;
+    use crate::solve_variability_zielonka;
+    use crate::solve_variability_zielonka_strategy;
+    use crate::solve_zielonka;
+    use crate::verify_strategy;
+    use crate::verify_variability_strategy;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_solve_parity_game_scc() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 2);
+            let expected = solve_zielonka(&pg);
+
+            for solver in [ParityGameSolver::Zielonka, ParityGameSolver::FixpointIteration] {
+                let (solution, strategy) = solve_parity_game_scc(&pg, |game| solve_parity_game(game, solver));
+                assert_eq!(solution, expected, "The solution differs from Zielonka");
+
+                if let Some(strategy) = strategy {
+                    verify_strategy(&pg, &solution, &strategy).unwrap();
+                }
+            }
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_random_solve_variability_parity_game_scc() {
+        random_test(100, |rng| {
+            let manager_ref = oxidd::bdd::new_manager(2048, 1024, 1);
+            let vpg = random_variability_parity_game(&manager_ref, rng, true, 20, 3, 3, 3).unwrap();
+            let expected = solve_variability_zielonka(&manager_ref, &vpg, ZielonkaVariant::Family, false).unwrap();
+
+            let (solution, strategy) = solve_variability_parity_game_scc(&manager_ref, &vpg, |game| {
+                solve_variability_zielonka_strategy(&manager_ref, game, ZielonkaVariant::Family, false)
+            })
+            .unwrap();
+            assert!(
+                solution[0] == expected[0] && solution[1] == expected[1],
+                "The solution differs from Zielonka"
+            );
+
+            verify_variability_strategy(&vpg, &solution, &strategy, &Timing::new()).unwrap();
+        })
+    }
+}
@@ -0,0 +1,205 @@
+//! Verification of winning strategies, which certifies the solution of a parity game.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use oxidd::BooleanFunction;
+
+use merc_symbolic::FormatConfig;
+use merc_utilities::MercError;
+use merc_utilities::Timing;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Priority;
+use crate::Set;
+use crate::Strategy;
+use crate::Submap;
+use crate::VariabilityParityGame;
+use crate::VariabilityStrategy;
+use crate::VertexIndex;
+use crate::project_variability_parity_games_iter;
+use crate::scc_decomposition;
+
+/// Verifies that the given strategy is winning for both players on their
+/// winning sets, which shows that the solution is correct.
+///
+/// # Details
+///
+/// For every player the game is restricted to its winning set, in which the
+/// vertices owned by that player only keep the edge chosen by the strategy.
+/// This is a one-player game for the opponent, and the strategy is winning
+/// when the winning set is closed under the remaining edges and every cycle
+/// has a highest priority that is good for the player.
+pub fn verify_strategy(game: &ParityGame, solution: &[Set; 2], strategy: &Strategy) -> Result<(), MercError> {
+    for v in game.iter_vertices() {
+        if solution[0][*v] == solution[1][*v] {
+            return Err(format!("Vertex {v} should be won by exactly one player").into());
+        }
+    }
+
+    for player in [Player::Even, Player::Odd] {
+        let winning = &solution[player.to_index()];
+
+        // The edges of the restricted one-player game.
+        let edge = |v: VertexIndex, w: VertexIndex| -> bool {
+            winning[*v] && winning[*w] && (game.owner(v) != player || strategy[*v] == Some(w))
+        };
+
+        for v in winning.iter_ones().map(VertexIndex::new) {
+            if game.owner(v) == player {
+                let w = strategy[*v].ok_or(format!("Vertex {v} is won by {player}, but has no strategy"))?;
+                if !game.outgoing_edges(v).any(|u| u == w) {
+                    return Err(format!("The strategy of vertex {v} to {w} is not an edge").into());
+                }
+
+                if !winning[*w] {
+                    return Err(format!("The strategy of vertex {v} leaves the winning set of {player}").into());
+                }
+            } else if let Some(w) = game.outgoing_edges(v).find(|w| !winning[**w]) {
+                return Err(format!("Vertex {v} is won by {player}, but the opponent can move to {w}").into());
+            }
+        }
+
+        // For every priority of the opponent, check whether there is a cycle on
+        // which it is the highest priority, using the vertices of at most that
+        // priority.
+        let mut priorities: Vec<Priority> = winning
+            .iter_ones()
+            .map(|v| game.priority(VertexIndex::new(v)))
+            .filter(|priority| Player::from_priority(priority) != player)
+            .collect();
+        priorities.sort_unstable();
+        priorities.dedup();
+
+        for priority in priorities {
+            let component = scc_decomposition(game, |v, w| {
+                edge(v, w) && game.priority(v) <= priority && game.priority(w) <= priority
+            });
+
+            // Count the number of vertices in every component to find the non-trivial ones.
+            let mut size = vec![0usize; game.num_of_vertices()];
+            for v in winning.iter_ones().map(VertexIndex::new) {
+                if game.priority(v) <= priority {
+                    size[component[*v]] += 1;
+                }
+            }
+
+            for v in winning.iter_ones().map(VertexIndex::new) {
+                if game.priority(v) == priority
+                    && (size[component[*v]] > 1 || game.outgoing_edges(v).any(|w| w == v && edge(v, w)))
+                {
+                    return Err(format!(
+                        "Vertex {v} is won by {player}, but the opponent can visit its priority {priority} infinitely often"
+                    )
+                    .into());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies that the given strategy is winning for both players on their
+/// winning sets for every configuration of the variability parity game.
+///
+/// # Details
+///
+/// The game, its solution and the strategy are projected for every
+/// configuration, which are then checked by [verify_strategy].
+pub fn verify_variability_strategy(
+    vpg: &VariabilityParityGame,
+    solution: &[Submap; 2],
+    strategy: &VariabilityStrategy,
+    timing: &Timing,
+) -> Result<(), MercError> {
+    for result in project_variability_parity_games_iter(vpg, timing) {
+        let ((cube, bdd, pg), _) = result?;
+
+        let mut projected_solution = [
+            bitvec![usize, Lsb0; 0; vpg.num_of_vertices()],
+            bitvec![usize, Lsb0; 0; vpg.num_of_vertices()],
+        ];
+        let mut projected_strategy = vec![None; vpg.num_of_vertices()];
+
+        for v in vpg.iter_vertices() {
+            for (index, winning) in solution.iter().enumerate() {
+                if winning[v].and(&bdd)?.satisfiable() {
+                    projected_solution[index].set(*v, true);
+                }
+            }
+
+            for (w, configuration) in &strategy[*v] {
+                if configuration.and(&bdd)?.satisfiable() {
+                    projected_strategy[*v] = Some(*w);
+                }
+            }
+        }
+
+        verify_strategy(&pg, &projected_solution, &projected_strategy)
+            .map_err(|e| format!("For product {}: {e}", FormatConfig(&cube)))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::ZielonkaVariant;
+    use crate::random_parity_game;
+    use crate::random_variability_parity_game;
+    use crate::solve_variability_zielonka_strategy;
+    use crate::solve_zielonka_strategy;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_verify_strategy() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 5, 3);
+            let (solution, strategy) = solve_zielonka_strategy(&pg);
+
+            verify_strategy(&pg, &solution, &strategy).unwrap();
+        })
+    }
+
+    #[test]
+    fn test_verify_strategy_cycle() {
+        // Even wins both vertices by moving from 0 to 1, since the self loop on 0 has odd priority.
+        let pg = ParityGame::from_edges(
+            VertexIndex::new(0),
+            vec![Player::Even, Player::Odd],
+            vec![Priority::new(1), Priority::new(2)],
+            false,
+            || {
+                [(0, 0), (0, 1), (1, 0), (1, 1)]
+                    .into_iter()
+                    .map(|(from, to)| (VertexIndex::new(from), VertexIndex::new(to)))
+            },
+        );
+        let solution = [bitvec![usize, Lsb0; 1; 2], bitvec![usize, Lsb0; 0; 2]];
+
+        verify_strategy(&pg, &solution, &vec![Some(VertexIndex::new(1)), None]).unwrap();
+        assert!(verify_strategy(&pg, &solution, &vec![Some(VertexIndex::new(0)), None]).is_err());
+        assert!(verify_strategy(&pg, &solution, &vec![None, None]).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_random_verify_variability_strategy() {
+        random_test(100, |rng| {
+            let manager_ref = oxidd::bdd::new_manager(2048, 1024, 1);
+            let vpg = random_variability_parity_game(&manager_ref, rng, true, 20, 3, 3, 3).unwrap();
+
+            for variant in [ZielonkaVariant::Family, ZielonkaVariant::FamilyOptimisedLeft] {
+                let (solution, strategy) =
+                    solve_variability_zielonka_strategy(&manager_ref, &vpg, variant, false).unwrap();
+                verify_variability_strategy(&vpg, &solution, &strategy, &Timing::new()).unwrap();
+            }
+        })
+    }
+}
@@ -0,0 +1,358 @@
+#![allow(nonstandard_style)]
+//! To keep with the theory, we use capitalized variable names for sets of vertices.
+//!
+//! Implements the tangle learning solver for parity games, see
+//!
+//! > Tom van Dijk. Attracting tangles to solve parity games. CAV 2018.
+
+use bitvec::bitvec;
+use bitvec::order::Lsb0;
+use log::debug;
+
+use crate::PG;
+use crate::ParityGame;
+use crate::Player;
+use crate::Predecessors;
+use crate::Set;
+use crate::Strategy;
+use crate::VertexIndex;
+use crate::scc_decomposition;
+
+/// Solves the given parity game using tangle learning, and returns the
+/// winning sets together with a winning strategy for both players.
+///
+/// # Details
+///
+/// A tangle is a strongly connected set of vertices together with a strategy
+/// for one of the players, such that every play that stays in the tangle is won
+/// by that player. Every iteration computes an attractor decomposition of the
+/// unsolved vertices, where the tangles are attracted as a whole when the
+/// opponent can only escape to the attractor. The bottom strongly connected
+/// components of the closed regions are either dominions, which are solved, or
+/// new tangles that are learned for the next iteration.
+pub fn solve_tangle_learning(game: &ParityGame) -> ([Set; 2], Strategy) {
+    debug_assert!(game.is_total(), "Tangle learning requires a total parity game");
+
+    let mut solver = TangleLearningSolver::new(game);
+    solver.solve();
+
+    debug!(
+        "Performed {} iterations and learned {} tangles",
+        solver.iterations, solver.learned
+    );
+
+    let mut strategy = solver.strategy;
+    for v in game.iter_vertices() {
+        let winner = if solver.solution[0][*v] {
+            Player::Even
+        } else {
+            Player::Odd
+        };
+        if game.owner(v) != winner {
+            strategy[*v] = None;
+        }
+    }
+
+    (solver.solution, strategy)
+}
+
+/// A tangle of the player, with the strategy of the player and the vertices to which the opponent can escape.
+struct Tangle {
+    player: Player,
+    vertices: Vec<VertexIndex>,
+    strategy: Vec<(VertexIndex, VertexIndex)>,
+    escapes: Vec<VertexIndex>,
+}
+
+struct TangleLearningSolver<'a> {
+    game: &'a ParityGame,
+
+    /// Stores the predecessors of the game.
+    predecessors: Predecessors,
+
+    /// The vertices for which the winner has not yet been determined.
+    unsolved: Set,
+
+    /// The winning sets of both players.
+    solution: [Set; 2],
+
+    /// The tangles that have been learned so far.
+    tangles: Vec<Tangle>,
+
+    /// The successor chosen by the owner of every attracted vertex.
+    strategy: Strategy,
+
+    /// Temporary storage for vertices per priority.
+    priority_vertices: Vec<Vec<VertexIndex>>,
+
+    /// Reused temporary queue for attractor computation.
+    temp_queue: Vec<VertexIndex>,
+
+    /// The number of iterations, used for statistics.
+    iterations: usize,
+
+    /// The number of learned tangles, used for statistics.
+    learned: usize,
+}
+
+impl<'a> TangleLearningSolver<'a> {
+    fn new(game: &'a ParityGame) -> Self {
+        let mut priority_vertices = Vec::new();
+        for v in game.iter_vertices() {
+            let prio = game.priority(v);
+
+            while prio >= priority_vertices.len() {
+                priority_vertices.push(Vec::new());
+            }
+
+            priority_vertices[prio].push(v);
+        }
+
+        Self {
+            game,
+            predecessors: Predecessors::new(game),
+            unsolved: bitvec![usize, Lsb0; 1; game.num_of_vertices()],
+            solution: [
+                bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+                bitvec![usize, Lsb0; 0; game.num_of_vertices()],
+            ],
+            tangles: Vec::new(),
+            strategy: vec![None; game.num_of_vertices()],
+            priority_vertices,
+            temp_queue: Vec::new(),
+            iterations: 0,
+            learned: 0,
+        }
+    }
+
+    /// Searches for dominions and learns tangles until all vertices are solved.
+    fn solve(&mut self) {
+        while self.unsolved.any() {
+            self.iterations += 1;
+
+            let (dominions, tangles) = self.search();
+            if dominions.is_empty() {
+                assert!(
+                    !tangles.is_empty(),
+                    "Every iteration should find a dominion or learn a tangle"
+                );
+
+                self.learned += tangles.len();
+                self.tangles.extend(tangles);
+            } else {
+                for (player, dominion) in dominions {
+                    let mut Z = bitvec![usize, Lsb0; 0; self.game.num_of_vertices()];
+                    for v in dominion {
+                        Z.set(*v, true);
+                    }
+
+                    // The dominions are disjoint, but an earlier one can attract vertices of a later one.
+                    Z &= &self.unsolved;
+                    let unsolved = self.unsolved.clone();
+                    let Z = self.attractor(player, &unsolved, Z);
+
+                    self.unsolved &= !Z.clone();
+                    self.solution[player.to_index()] |= Z;
+                }
+
+                // Only keep the tangles that are still part of the unsolved game.
+                let unsolved = &self.unsolved;
+                self.tangles
+                    .retain(|tangle| tangle.vertices.iter().all(|v| unsolved[**v]));
+            }
+        }
+    }
+
+    /// Computes an attractor decomposition of the unsolved vertices, and
+    /// returns the dominions and the new tangles that are found.
+    #[allow(clippy::type_complexity)]
+    fn search(&mut self) -> (Vec<(Player, Vec<VertexIndex>)>, Vec<Tangle>) {
+        let mut dominions = Vec::new();
+        let mut tangles = Vec::new();
+
+        let mut G = self.unsolved.clone();
+        while G.any() {
+            let highest_prio = G
+                .iter_ones()
+                .map(|v| self.game.priority(VertexIndex::new(v)))
+                .max()
+                .expect("G is not empty");
+            let alpha = Player::from_priority(&highest_prio);
+
+            let mut U = bitvec![usize, Lsb0; 0; self.game.num_of_vertices()];
+            for v in &self.priority_vertices[*highest_prio] {
+                if G[**v] {
+                    U.set(**v, true);
+                    self.strategy[**v] = None;
+                }
+            }
+
+            let Z = self.attractor(alpha, &G, U.clone());
+
+            // The vertices with the highest priority can choose any successor in the region.
+            let mut closed = true;
+            for v in U.iter_ones().map(VertexIndex::new) {
+                if self.game.owner(v) == alpha {
+                    self.strategy[*v] = self.game.outgoing_edges(v).find(|w| Z[**w]);
+                    closed &= self.strategy[*v].is_some();
+                } else {
+                    closed &= self.game.outgoing_edges(v).all(|w| !G[*w] || Z[*w]);
+                }
+            }
+
+            if closed {
+                self.extract_tangles(alpha, &Z, &mut dominions, &mut tangles);
+            }
+
+            G &= !Z;
+        }
+
+        (dominions, tangles)
+    }
+
+    /// Adds the bottom strongly connected components of the region `Z`,
+    /// restricted to the strategy of `alpha`, to the dominions when the
+    /// opponent can not escape, and to the tangles otherwise.
+    #[allow(clippy::type_complexity)]
+    fn extract_tangles(
+        &self,
+        alpha: Player,
+        Z: &Set,
+        dominions: &mut Vec<(Player, Vec<VertexIndex>)>,
+        tangles: &mut Vec<Tangle>,
+    ) {
+        let edge = |v: VertexIndex, w: VertexIndex| -> bool {
+            Z[*v] && Z[*w] && (self.game.owner(v) != alpha || self.strategy[*v] == Some(w))
+        };
+        let component = scc_decomposition(self.game, edge);
+
+        // A component is a bottom component when it has no edges to other components.
+        let mut is_bottom = vec![true; self.game.num_of_vertices()];
+        let mut has_cycle = vec![false; self.game.num_of_vertices()];
+        let mut vertices = vec![Vec::new(); self.game.num_of_vertices()];
+        for v in Z.iter_ones().map(VertexIndex::new) {
+            vertices[component[*v]].push(v);
+
+            for w in self.game.outgoing_edges(v).filter(|w| edge(v, *w)) {
+                if component[*w] != component[*v] {
+                    is_bottom[component[*v]] = false;
+                } else {
+                    has_cycle[component[*v]] = true;
+                }
+            }
+        }
+
+        for (index, vertices) in vertices.into_iter().enumerate() {
+            if vertices.is_empty() || !is_bottom[index] || !has_cycle[index] {
+                continue;
+            }
+
+            let mut escapes: Vec<VertexIndex> = vertices
+                .iter()
+                .filter(|v| self.game.owner(**v) != alpha)
+                .flat_map(|v| self.game.outgoing_edges(*v))
+                .filter(|w| self.unsolved[**w] && component[**w] != index)
+                .collect();
+            escapes.sort_unstable();
+            escapes.dedup();
+
+            if escapes.is_empty() {
+                dominions.push((alpha, vertices));
+            } else {
+                let strategy = vertices
+                    .iter()
+                    .filter(|v| self.game.owner(**v) == alpha)
+                    .map(|v| (*v, self.strategy[**v].expect("The strategy stays in the component")))
+                    .collect();
+
+                tangles.push(Tangle {
+                    player: alpha,
+                    vertices,
+                    strategy,
+                    escapes,
+                });
+            }
+        }
+    }
+
+    /// Computes the attractor for `alpha` to the set `A` within the vertices
+    /// `V`, where a tangle of `alpha` in `V` is attracted when all its escapes
+    /// in `V` are in the attractor.
+    fn attractor(&mut self, alpha: Player, V: &Set, mut A: Set) -> Set {
+        self.temp_queue.clear();
+        self.temp_queue.extend(A.iter_ones().map(VertexIndex::new));
+
+        loop {
+            while let Some(w) = self.temp_queue.pop() {
+                for v in self.predecessors.predecessors(w) {
+                    if !V[*v] || A[*v] {
+                        continue;
+                    }
+
+                    let attracted = self.game.owner(v) == alpha
+                        || self.game.outgoing_edges(v).all(|w_prime| !V[*w_prime] || A[*w_prime]);
+
+                    if attracted {
+                        if self.game.owner(v) == alpha {
+                            self.strategy[*v] = Some(w);
+                        }
+
+                        A.set(*v, true);
+                        self.temp_queue.push(v);
+                    }
+                }
+            }
+
+            for tangle in &self.tangles {
+                if tangle.player == alpha
+                    && tangle.vertices.iter().all(|v| V[**v])
+                    && tangle.vertices.iter().any(|v| !A[**v])
+                    && tangle.escapes.iter().all(|w| !V[**w] || A[**w])
+                {
+                    for (v, w) in &tangle.strategy {
+                        if !A[**v] {
+                            self.strategy[**v] = Some(*w);
+                        }
+                    }
+
+                    for v in &tangle.vertices {
+                        if !A[**v] {
+                            A.set(**v, true);
+                            self.temp_queue.push(*v);
+                        }
+                    }
+                }
+            }
+
+            if self.temp_queue.is_empty() {
+                break;
+            }
+        }
+
+        A
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use merc_utilities::random_test;
+
+    use crate::random_parity_game;
+    use crate::solve_zielonka;
+    use crate::verify_strategy;
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_tangle_learning() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 8, 3);
+            let expected = solve_zielonka(&pg);
+
+            let (solution, strategy) = solve_tangle_learning(&pg);
+            assert_eq!(solution, expected, "The solution differs from Zielonka");
+            verify_strategy(&pg, &solution, &strategy).unwrap();
+        })
+    }
+}
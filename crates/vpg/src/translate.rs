@@ -9,6 +9,7 @@ use oxidd::bdd::BDDManagerRef;
 use merc_collections::IndexedSet;
 use merc_io::TimeProgress;
 use merc_lts::LTS;
+use merc_lts::LabelIndex;
 use merc_lts::StateIndex;
 use merc_syntax::ActFrm;
 use merc_syntax::ActFrmBinaryOp;
@@ -47,11 +48,28 @@ pub fn translate(
         .map(strip_feature_configuration_from_multi_action)
         .collect();
 
+    let (result, _) = translate_labelled(manager_ref, fts, &simplified_labels, configuration, formula)?;
+    Ok(result)
+}
+
+/// An edge of the game that corresponds to the transition `(from, label, to)` of the transition system.
+pub(crate) type TransitionEdge = (VertexIndex, VertexIndex, (StateIndex, LabelIndex, StateIndex));
+
+/// Translates a feature transition system into a variability parity game,
+/// where the transitions are labelled by the given multi-actions. Also
+/// returns the edges of the game that correspond to transitions.
+pub(crate) fn translate_labelled(
+    manager_ref: &BDDManagerRef,
+    fts: &FeatureTransitionSystem,
+    labels: &Vec<MultiAction>,
+    configuration: BDDFunction,
+    formula: &StateFrm,
+) -> Result<(VariabilityParityGame, Vec<TransitionEdge>), MercError> {
     let equation_system = ModalEquationSystem::new(formula);
     debug!("{}", equation_system);
     let mut algorithm = Translation::new(
         fts,
-        &simplified_labels,
+        labels,
         &equation_system,
         manager_ref.with_manager_shared(|manager| BDDFunction::t(manager)),
     );
@@ -88,7 +106,7 @@ pub fn translate(
         result
     };
 
-    Ok(total_result)
+    Ok((total_result, algorithm.transition_edges))
 }
 
 /// Is used to distinguish between StateFrm and Equation vertices in the vertex map.
@@ -112,6 +130,9 @@ struct Translation<'a> {
     vertices: Vec<(Player, Priority)>,
     edges: Vec<(VertexIndex, BDDFunction, VertexIndex)>,
 
+    /// The edges that correspond to a transition of the feature transition system.
+    transition_edges: Vec<TransitionEdge>,
+
     // Used for the breadth first search.
     queue: Vec<(StateIndex, Formula<'a>, VertexIndex)>,
 
@@ -150,6 +171,7 @@ impl<'a> Translation<'a> {
             vertex_map: IndexedSet::new(),
             vertices: Vec::new(),
             edges: Vec::new(),
+            transition_edges: Vec::new(),
             queue: Vec::new(),
             fts,
             parsed_labels,
@@ -163,12 +185,13 @@ impl<'a> Translation<'a> {
     fn translate(&mut self, initial_state: StateIndex, initial_equation_index: usize) -> Result<(), MercError> {
         // We store (state, formula, N) into the queue, where N is the vertex number assigned to this pair. This means
         // that during the traversal we can assume this N to exist.
-        self.queue = vec![(
-            initial_state,
-            Formula::Equation(initial_equation_index),
+        // The initial vertex must be registered, such that no other pair is assigned index 0.
+        let initial_vertex = self.queue_vertex(initial_state, Formula::Equation(initial_equation_index));
+        debug_assert_eq!(
+            initial_vertex,
             VertexIndex::new(0),
-        )];
-        self.vertices.push((Player::Odd, Priority::new(0))); // Placeholder for the initial vertex
+            "The initial vertex should be vertex 0"
+        );
 
         while let Some((s, formula, vertex_index)) = self.queue.pop() {
             debug!("Translating vertex {}: (s={}, formula={:?})", vertex_index, s, formula);
@@ -256,6 +279,11 @@ impl<'a> Translation<'a> {
 
                             if match_regular_formula(formula, action) {
                                 let s_prime_psi = self.queue_vertex(transition.to, Formula::StateFrm(expr));
+                                self.transition_edges.push((
+                                    vertex_index,
+                                    s_prime_psi,
+                                    (s, transition.label, transition.to),
+                                ));
 
                                 self.edges.push((
                                     vertex_index,
@@ -274,6 +302,11 @@ impl<'a> Translation<'a> {
 
                             if match_regular_formula(formula, action) {
                                 let s_prime_psi = self.queue_vertex(transition.to, Formula::StateFrm(expr));
+                                self.transition_edges.push((
+                                    vertex_index,
+                                    s_prime_psi,
+                                    (s, transition.label, transition.to),
+                                ));
 
                                 self.edges.push((
                                     vertex_index,
@@ -382,10 +415,37 @@ mod tests {
     use merc_syntax::UntypedStateFrmSpec;
 
     use crate::FeatureDiagram;
+    use crate::PG;
+    use crate::ZielonkaVariant;
     use crate::read_fts;
+    use crate::solve_variability_zielonka;
 
     use super::*;
 
+    /// Translates the given formula for the feature transition system in the
+    /// .aut format, with a single feature, and returns true iff the initial
+    /// vertex is won by even for all configurations.
+    fn translate_and_solve(aut: &[u8], formula: &str) -> bool {
+        let manager_ref = oxidd::bdd::new_manager(2048, 1024, 1);
+
+        let fd = FeatureDiagram::from_reader(&manager_ref, b"A\ntt\n" as &[u8]).unwrap();
+        let fts = read_fts(&manager_ref, aut, fd.features().clone()).unwrap();
+        let formula = UntypedStateFrmSpec::parse(formula).unwrap();
+
+        let vpg = translate(&manager_ref, &fts, fd.configuration().clone(), &formula.formula).unwrap();
+        let solution = solve_variability_zielonka(&manager_ref, &vpg, ZielonkaVariant::Family, false).unwrap();
+
+        let initial_vertex = vpg.initial_vertex();
+        assert!(
+            !solution[0][initial_vertex]
+                .and(&solution[1][initial_vertex])
+                .unwrap()
+                .satisfiable(),
+            "The initial vertex cannot be won by both players"
+        );
+        !solution[1][initial_vertex].satisfiable()
+    }
+
     #[merc_test]
     #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
     fn test_running_example() {
@@ -407,4 +467,28 @@ mod tests {
 
         let _vpg = translate(&manager_ref, &fts, fd.configuration().clone(), &formula.formula).unwrap();
     }
+
+    #[merc_test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_translate_deadlock() {
+        let aut = b"des (0,1,2)\n(0,\"a(tt)\",1)\n";
+
+        // A player that cannot move loses, so the diamond fails and the box holds in the deadlock.
+        assert!(translate_and_solve(aut, "nu X. <a>true"));
+        assert!(!translate_and_solve(aut, "nu X. <a><a>true"));
+        assert!(translate_and_solve(aut, "nu X. [a][a]false"));
+        assert!(!translate_and_solve(aut, "nu X. [a]<true>true"));
+    }
+
+    #[merc_test]
+    #[cfg_attr(miri, ignore)] // Oxidd does not work with miri
+    fn test_translate_initial_vertex() {
+        let aut = b"des (0,2,2)\n(0,\"a(tt)\",1)\n(1,\"a(tt)\",0)\n";
+
+        // The initial equation is revisited, which should yield the initial vertex itself.
+        assert!(translate_and_solve(aut, "nu X. <a>X"));
+        assert!(!translate_and_solve(aut, "mu X. <a>X"));
+        assert!(translate_and_solve(aut, "nu X. mu Y. (<a>X || <a>Y)"));
+        assert!(!translate_and_solve(aut, "mu X. nu Y. ([a]X && [a]Y)"));
+    }
 }
@@ -9,7 +9,6 @@ use std::ops::Index;
 use bitvec::bitvec;
 use bitvec::order::Lsb0;
 use bitvec::vec::BitVec;
-use clap::ValueEnum;
 use log::debug;
 use log::info;
 use log::trace;
@@ -58,6 +57,11 @@ pub enum ZielonkaVariant {
     FamilyOptimisedLeft,
 }
 
+/// A positional strategy for a variability parity game, which assigns to every
+/// vertex the chosen successors together with the configurations in which
+/// they are chosen by the owner.
+pub type VariabilityStrategy = Vec<Vec<(VertexIndex, BDDFunction)>>;
+
 /// Solves the given variability parity game using the specified Zielonka algorithm variant.
 pub fn solve_variability_zielonka(
     manager_ref: &BDDManagerRef,
@@ -65,6 +69,24 @@ pub fn solve_variability_zielonka(
     variant: ZielonkaVariant,
     alternative_solving: bool,
 ) -> Result<[Submap; 2], MercError> {
+    let (solution, _) = solve_variability_zielonka_strategy(manager_ref, game, variant, alternative_solving)?;
+    Ok(solution)
+}
+
+/// Solves the given variability parity game using the specified Zielonka
+/// algorithm variant, and returns the winning submaps together with a winning
+/// strategy for both players.
+///
+/// # Details
+///
+/// For every configuration the strategy chooses a successor for the vertices
+/// that are won by their owner in that configuration.
+pub fn solve_variability_zielonka_strategy(
+    manager_ref: &BDDManagerRef,
+    game: &VariabilityParityGame,
+    variant: ZielonkaVariant,
+    alternative_solving: bool,
+) -> Result<([Submap; 2], VariabilityStrategy), MercError> {
     debug_assert!(
         game.is_total(manager_ref)?,
         "Zielonka solver requires a total parity game"
@@ -108,7 +130,18 @@ pub fn solve_variability_zielonka(
         (W0, W1)
     };
 
-    Ok([W0, W1])
+    // Only keep the choices for the configurations in which the owner wins the vertex.
+    let mut strategy = zielonka.strategy;
+    for v in game.iter_vertices() {
+        let W = if game.owner(v) == Player::Even { &W0 } else { &W1 };
+        for (_, configuration) in strategy[*v].iter_mut() {
+            *configuration = configuration.and(&W[v])?;
+        }
+
+        strategy[*v].retain(|(_, configuration)| configuration.satisfiable());
+    }
+
+    Ok(([W0, W1], strategy))
 }
 
 /// Solves the given variability parity game using the product-based Zielonka algorithm.
@@ -210,6 +243,9 @@ struct VariabilityZielonkaSolver<'a> {
     /// Temporary storage for vertices per priority.
     priority_vertices: Vec<Vec<VertexIndex>>,
 
+    /// The successors chosen by the owner of every vertex, which are winning for the configurations in its winning submap.
+    strategy: VariabilityStrategy,
+
     /// The BDD function representing the universe configuration.
     true_bdd: BDDFunction,
 
@@ -246,6 +282,7 @@ impl<'a> VariabilityZielonkaSolver<'a> {
             temp_vertices: BitVec::repeat(false, game.num_of_vertices()),
             predecessors: VariabilityPredecessors::new(manager_ref, game),
             priority_vertices,
+            strategy: vec![Vec::new(); game.num_of_vertices()],
             recursive_calls: 0,
             alternative_solving,
             true_bdd,
@@ -296,7 +333,7 @@ impl<'a> VariabilityZielonkaSolver<'a> {
 
         trace!("{indent}Vertices in gamma: {:?}", gamma);
         trace!("{indent}Vertices in mu: {:?}", mu);
-        let alpha = self.attractor(x, &gamma, mu)?;
+        let alpha = self.attractor(x, &gamma, mu.clone())?;
         trace!("{indent}Vertices in alpha: {:?}", alpha);
 
         // 9. (omega'_0, omega'_1) := solve(\gamma \ \alpha)
@@ -313,6 +350,9 @@ impl<'a> VariabilityZielonkaSolver<'a> {
 
         let (mut omega1_x, mut omega1_not_x) = x_and_not_x(omega1_0, omega1_1, x);
         if omega1_not_x.is_empty() {
+            // x wins every play that visits mu infinitely often, so the vertices in mu can move anywhere in gamma.
+            self.choose_successors(x, &gamma, &mu)?;
+
             // 11. omega_x := omega'_x \cup alpha
             omega1_x = omega1_x.or(self.manager_ref, &alpha)?;
             // 20. return (omega_0, omega_1)
@@ -389,7 +429,7 @@ impl<'a> VariabilityZielonkaSolver<'a> {
         // 9. alpha := attr_x(\mu).
         trace!("{indent}gamma: {:?}", gamma);
         trace!("{indent}C: {}", FormatConfigSet(&C));
-        let alpha = self.attractor(x, &gamma, mu)?;
+        let alpha = self.attractor(x, &gamma, mu.clone())?;
         trace!("{indent}alpha: {:?}", alpha);
 
         // 10. (omega'_0, omega'_1) := solve(gamma \ alpha)
@@ -421,6 +461,8 @@ impl<'a> VariabilityZielonkaSolver<'a> {
 
         // 10.
         if omega1_not_x_restricted.is_empty() {
+            self.choose_successors(x, &gamma, &mu)?;
+
             // 11. omega'_x := omega'_x \cup A
             omega1_x = omega1_x.or(self.manager_ref, &alpha)?;
             if cfg!(debug_assertions) {
@@ -454,6 +496,9 @@ impl<'a> VariabilityZielonkaSolver<'a> {
             let alpha1 = self.attractor(not_x, &gamma, omega1_not_x_restricted1)?;
             trace!("{indent}alpha': {:?}", alpha1);
 
+            // For the configurations outside of C' the vertices in mu are won by x as before.
+            self.choose_successors(x, &gamma, &mu.minus_function(self.manager_ref, &C1)?)?;
+
             // Solve on (gamma | C') \ alpha'
             let gamma_restricted = gamma.minus_function(self.manager_ref, &C1_restricted)?;
 
@@ -599,8 +644,18 @@ impl<'a> VariabilityZielonkaSolver<'a> {
                             }
 
                             // 15. a \ A(v) != \emptyset
-                            if *EdgeDropGuard::new(manager, minus_edge(manager, &a, A[v].as_edge(manager))?) != *f_edge
-                            {
+                            let new = EdgeDropGuard::new(manager, minus_edge(manager, &a, A[v].as_edge(manager))?);
+                            if *new != *f_edge {
+                                if self.game.owner(v) == alpha {
+                                    // Alpha moves to w in the configurations for which v is attracted.
+                                    set_strategy(
+                                        &mut self.strategy,
+                                        v,
+                                        w,
+                                        BDDFunction::from_edge(manager, new.into_edge()),
+                                    )?;
+                                }
+
                                 // 16. A(v) := A(v) \cup a
                                 let was_empty = *A[v].as_edge(manager) == *f_edge;
                                 let update = BDDFunction::or_edge(manager, A[v].as_edge(manager), &a)?;
@@ -629,6 +684,26 @@ impl<'a> VariabilityZielonkaSolver<'a> {
         Ok(A)
     }
 
+    /// Chooses a successor in `gamma` for the vertices owned by `x`, for the configurations in `mu`.
+    fn choose_successors(&mut self, x: Player, gamma: &Submap, mu: &Submap) -> Result<(), MercError> {
+        for (v, configuration) in mu.iter() {
+            if self.game.owner(v) != x || !configuration.satisfiable() {
+                continue;
+            }
+
+            let mut remaining = configuration.clone();
+            for edge in self.game.outgoing_conf_edges(v) {
+                let chosen = remaining.and(edge.configuration())?.and(&gamma[edge.to()])?;
+                if chosen.satisfiable() {
+                    remaining = minus(&remaining, &chosen)?;
+                    set_strategy(&mut self.strategy, v, edge.to(), chosen)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns the highest and lowest priority in the given set of vertices V.
     fn get_highest_lowest_prio(&self, V: &Submap) -> (Priority, Priority) {
         let mut highest = usize::MIN;
@@ -670,6 +745,26 @@ impl<'a> VariabilityZielonkaSolver<'a> {
     }
 }
 
+/// Changes the strategy of vertex `v` to move to `w` in the given configurations.
+fn set_strategy(
+    strategy: &mut VariabilityStrategy,
+    v: VertexIndex,
+    w: VertexIndex,
+    configuration: BDDFunction,
+) -> Result<(), MercError> {
+    for (_, existing) in strategy[*v].iter_mut() {
+        *existing = minus(existing, &configuration)?;
+    }
+
+    strategy[*v].retain(|(_, existing)| existing.satisfiable());
+    match strategy[*v].iter_mut().find(|(u, _)| *u == w) {
+        Some((_, existing)) => *existing = existing.or(&configuration)?,
+        None => strategy[*v].push((w, configuration)),
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use merc_io::DumpFiles;
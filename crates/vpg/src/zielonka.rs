@@ -14,6 +14,8 @@ use bitvec::vec::BitVec;
 use itertools::Itertools;
 use log::debug;
 use log::trace;
+use rayon::ThreadPool;
+use rayon::prelude::*;
 
 use crate::PG;
 use crate::ParityGame;
@@ -26,16 +28,53 @@ use crate::VertexIndex;
 /// The type for a set of vertices.
 pub type Set = BitVec<usize, Lsb0>;
 
+/// A positional strategy, which assigns the chosen successor to every vertex
+/// that is owned by the player that wins it.
+pub type Strategy = Vec<Option<VertexIndex>>;
+
+/// The minimum number of vertices in the frontier that is processed by a single thread.
+const MIN_FRONTIER_CHUNK: usize = 1024;
+
 /// Solves the given parity game using the Zielonka algorithm.
 pub fn solve_zielonka(game: &ParityGame) -> [Set; 2] {
+    let (solution, _) = solve_zielonka_strategy(game);
+    solution
+}
+
+/// Solves the given parity game using the Zielonka algorithm, and returns the
+/// winning sets together with a winning strategy for both players.
+pub fn solve_zielonka_strategy(game: &ParityGame) -> ([Set; 2], Strategy) {
+    solve_zielonka_impl(ZielonkaSolver::new(game, None))
+}
+
+/// Solves the given parity game using the Zielonka algorithm, where the
+/// attractors are computed using the given number of threads.
+///
+/// # Details
+///
+/// The attractors are computed level by level, where the predecessors of all
+/// vertices added in the previous level are considered in parallel. The
+/// vertices are added in the order of the frontier, such that the solution and
+/// the strategy are the same as computed by [solve_zielonka_strategy] for any
+/// number of threads.
+pub fn solve_zielonka_parallel(game: &ParityGame, num_of_threads: usize) -> ([Set; 2], Strategy) {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_of_threads)
+        .build()
+        .expect("Failed to create the thread pool");
+
+    solve_zielonka_impl(ZielonkaSolver::new(game, Some(pool)))
+}
+
+/// Solves the parity game of the given solver, and returns the winning sets together with the strategy.
+fn solve_zielonka_impl(mut zielonka: ZielonkaSolver) -> ([Set; 2], Strategy) {
+    let game = zielonka.game;
     debug_assert!(game.is_total(), "Zielonka solver requires a total parity game");
 
     let mut V = bitvec![usize, Lsb0; 0; game.num_of_vertices()];
     V.set_elements(usize::MAX);
     let full_V = V.clone(); // Used for debugging.
 
-    let mut zielonka = ZielonkaSolver::new(game);
-
     let (W0, W1) = zielonka.zielonka_rec(V, 0);
 
     // Check that the result is a valid partition
@@ -43,7 +82,17 @@ pub fn solve_zielonka(game: &ParityGame) -> [Set; 2] {
     if cfg!(debug_assertions) {
         zielonka.check_partition(&W0, &W1, &full_V);
     }
-    [W0, W1]
+
+    // Only keep the choices of the vertices that are won by their owner.
+    let mut strategy = zielonka.strategy;
+    for v in game.iter_vertices() {
+        let winner = if W0[*v] { Player::Even } else { Player::Odd };
+        if game.owner(v) != winner {
+            strategy[*v] = None;
+        }
+    }
+
+    ([W0, W1], strategy)
 }
 
 struct ZielonkaSolver<'a> {
@@ -58,13 +107,19 @@ struct ZielonkaSolver<'a> {
     /// Temporary storage for vertices per priority.
     priority_vertices: Vec<Vec<VertexIndex>>,
 
+    /// The successor chosen by the owner of every vertex, which is winning for the vertices in its winning set.
+    strategy: Strategy,
+
+    /// When set, the attractors are computed in parallel using this thread pool.
+    pool: Option<ThreadPool>,
+
     /// Keeps track of the total number of recursive calls.
     recursive_calls: usize,
 }
 
 impl ZielonkaSolver<'_> {
     /// Creates a new Zielonka solver for the given parity game.
-    fn new<'a>(game: &'a ParityGame) -> ZielonkaSolver<'a> {
+    fn new<'a>(game: &'a ParityGame, pool: Option<ThreadPool>) -> ZielonkaSolver<'a> {
         // Keep track of the vertices for each priority
         let mut priority_vertices = Vec::new();
 
@@ -83,6 +138,8 @@ impl ZielonkaSolver<'_> {
             predecessors: Predecessors::new(game),
             priority_vertices,
             temp_queue: Vec::new(),
+            strategy: vec![None; game.num_of_vertices()],
+            pool,
             recursive_calls: 0,
         }
     }
@@ -120,7 +177,7 @@ impl ZielonkaSolver<'_> {
         );
         trace!("{}Vertices in U: {}", indent, DisplaySet(&U));
 
-        let A = self.attractor(alpha, &V, U);
+        let A = self.attractor(alpha, &V, U.clone());
 
         trace!("{}Vertices in A: {}", indent, DisplaySet(&A));
         debug!("{}zielonka(V \\ A) |A| = {}", indent, A.count_ones());
@@ -129,6 +186,14 @@ impl ZielonkaSolver<'_> {
         let (mut W1_alpha, W1_not_alpha) = x_and_not_x(W1_0, W1_1, alpha);
 
         if !W1_not_alpha.any() {
+            // Alpha wins every play that visits U infinitely often, so the vertices in U can move anywhere in V.
+            for u in U.iter_ones() {
+                let u = VertexIndex::new(u);
+                if self.game.owner(u) == alpha {
+                    self.strategy[*u] = self.game.outgoing_edges(u).find(|w| V[**w]);
+                }
+            }
+
             W1_alpha |= A;
             combine(W1_alpha, W1_not_alpha, alpha)
         } else {
@@ -147,35 +212,62 @@ impl ZielonkaSolver<'_> {
     }
 
     /// Computes the attractor for `alpha` to the set `U` within the vertices `V`.
+    ///
+    /// The attractor is computed level by level, where the attracted
+    /// predecessors of every level are collected in the order of the frontier.
+    /// When a thread pool is given the predecessors of the frontier are
+    /// considered in parallel, which results in the same attractor and strategy.
     fn attractor(&mut self, alpha: Player, V: &Set, mut A: Set) -> Set {
+        let game = self.game;
+        let predecessors = &self.predecessors;
+
         // 2. Q = {v \in A}
-        self.temp_queue.clear();
-        for v in A.iter_ones() {
-            self.temp_queue.push(VertexIndex::new(v));
-        }
+        let mut frontier = std::mem::take(&mut self.temp_queue);
+        frontier.clear();
+        frontier.extend(A.iter_ones().map(VertexIndex::new));
 
         // 4. While Q is not empty do
-        // 5. w := Q.pop()
-        while let Some(w) = self.temp_queue.pop() {
-            // For every u \in Ew do
-            for v in self.predecessors.predecessors(w) {
-                if V[*v] {
-                    let attracted = if self.game.owner(v) == alpha {
-                        // v \in V and v in V_\alpha
-                        true
-                    } else {
-                        // Check if all successors of v are in the attractor
-                        self.game.outgoing_edges(v).all(|w_prime| !V[*w_prime] || A[*w_prime])
-                    };
-
-                    if attracted && !A[*v] {
-                        A.set(*v, true);
-                        self.temp_queue.push(v);
+        while !frontier.is_empty() {
+            // The predecessors of w that are attracted, together with w as the chosen successor.
+            let attracted_predecessors = |w: VertexIndex| {
+                let A = &A;
+                predecessors
+                    .predecessors(w)
+                    .filter(move |&v| {
+                        // v \in V and v in V_\alpha, or all successors of v are in the attractor
+                        V[*v]
+                            && !A[*v]
+                            && (game.owner(v) == alpha
+                                || game.outgoing_edges(v).all(|w_prime| !V[*w_prime] || A[*w_prime]))
+                    })
+                    .map(move |v| (v, w))
+            };
+
+            let attracted: Vec<(VertexIndex, VertexIndex)> = match &self.pool {
+                Some(pool) => pool.install(|| {
+                    frontier
+                        .par_iter()
+                        .with_min_len(MIN_FRONTIER_CHUNK)
+                        .flat_map_iter(|&w| attracted_predecessors(w))
+                        .collect()
+                }),
+                None => frontier.iter().flat_map(|&w| attracted_predecessors(w)).collect(),
+            };
+
+            frontier.clear();
+            for (v, w) in attracted {
+                if !A[*v] {
+                    if game.owner(v) == alpha {
+                        self.strategy[*v] = Some(w);
                     }
+
+                    A.set(*v, true);
+                    frontier.push(v);
                 }
             }
         }
 
+        self.temp_queue = frontier;
         A
     }
 
@@ -241,10 +333,16 @@ impl fmt::Display for DisplaySet<'_> {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
     use merc_utilities::random_test;
 
+    use crate::PG;
+    use crate::Player;
     use crate::random_parity_game;
     use crate::solve_zielonka;
+    use crate::solve_zielonka_parallel;
+    use crate::solve_zielonka_strategy;
+    use crate::verify_strategy;
 
     #[test]
     #[cfg_attr(miri, ignore)] // Very slow under Miri
@@ -256,4 +354,47 @@ mod tests {
             solve_zielonka(&pg);
         })
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_parity_game_strategy() {
+        random_test(100, |rng| {
+            let pg = random_parity_game(rng, true, 100, 5, 3);
+            let (solution, strategy) = solve_zielonka_strategy(&pg);
+
+            // Every vertex owned by the winner moves to a vertex that is won by the same player.
+            for v in pg.iter_vertices() {
+                let winner = if solution[0][*v] { Player::Even } else { Player::Odd };
+                if pg.owner(v) == winner {
+                    let w = strategy[*v].expect("A winning vertex should have a strategy");
+                    assert!(pg.outgoing_edges(v).contains(&w), "The strategy of {v} is not an edge");
+                    assert!(
+                        solution[winner.to_index()][*w],
+                        "The strategy of {v} leaves the winning set"
+                    );
+                }
+            }
+        })
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Very slow under Miri
+    fn test_random_parity_game_parallel() {
+        // The games are large enough for the frontiers to be split over the threads.
+        random_test(20, |rng| {
+            let pg = random_parity_game(rng, true, 5000, 3, 3);
+            let expected = solve_zielonka_strategy(&pg);
+
+            let (solution, strategy) = solve_zielonka_parallel(&pg, 4);
+            assert_eq!(
+                (&solution, &strategy),
+                (&expected.0, &expected.1),
+                "The parallel result differs from the sequential result"
+            );
+            verify_strategy(&pg, &solution, &strategy).unwrap();
+
+            // The result should not depend on the number of threads.
+            assert_eq!(solve_zielonka_parallel(&pg, 1), (solution, strategy));
+        })
+    }
 }
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::stdout;
+use std::fs::read_to_string;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::ExitCode;
@@ -9,25 +9,47 @@ use clap::Subcommand;
 use log::info;
 
 use merc_io::LargeFormatter;
+use merc_lts::ActionFilter;
+use merc_lts::CompositionSpecification;
 use merc_lts::GenericLts;
 use merc_lts::LTS;
+use merc_lts::LabelledTransitionSystem;
 use merc_lts::LtsFormat;
+use merc_lts::StateLabelFormatter;
+use merc_lts::TransitionLabel;
+use merc_lts::allow_actions;
 use merc_lts::apply_lts;
 use merc_lts::apply_lts_pair;
+use merc_lts::block_actions;
 use merc_lts::guess_lts_format_from_extension;
+use merc_lts::hide_actions;
 use merc_lts::read_explicit_lts;
+use merc_lts::remove_unreachable_states;
+use merc_lts::rename_actions;
 use merc_lts::write_aut;
-use merc_lts::write_bcg;
+use merc_lts::write_explicit_lts;
 use merc_preorder::RefinementType;
 use merc_preorder::refines;
+use merc_preorder::refines_counter_example;
 use merc_reduction::Equivalence;
+use merc_reduction::Witness;
+use merc_reduction::compare_lts;
+use merc_reduction::distinguishing_formula;
+use merc_reduction::find_action;
+use merc_reduction::find_deadlock;
+use merc_reduction::find_livelock;
 use merc_reduction::reduce_lts;
+use merc_reduction::reduce_lts_parallel;
+use merc_reduction::smart_reduction;
+use merc_syntax::UntypedActionRenameSpec;
+use merc_syntax::UntypedStateFrmSpec;
 use merc_tools::Version;
 use merc_tools::VersionFlag;
 use merc_tools::verbosity::VerbosityFlag;
 use merc_unsafety::print_allocator_metrics;
 use merc_utilities::MercError;
 use merc_utilities::Timing;
+use merc_vpg::check_formula;
 
 #[derive(clap::Parser, Debug)]
 #[command(
@@ -46,6 +68,14 @@ struct Cli {
 
     #[arg(long, global = true)]
     timings: bool,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 1,
+        help = "The number of threads used to read .aut files and to compute the signatures of strong and branching bisimulation"
+    )]
+    threads: usize,
 }
 
 /// Defines the subcommands for this tool.
@@ -56,6 +86,10 @@ enum Commands {
     Compare(CompareArgs),
     Refines(RefinesArgs),
     Convert(ConvertArgs),
+    Transform(TransformArgs),
+    Compose(ComposeArgs),
+    Check(CheckArgs),
+    CheckFormula(CheckFormulaArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -63,6 +97,9 @@ enum Commands {
 struct InfoArgs {
     filename: String,
     filetype: Option<LtsFormat>,
+
+    #[arg(long, help = "Print the values of the process parameters for every state")]
+    state_labels: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -76,8 +113,15 @@ struct ReduceArgs {
     #[arg(long, help = "Explicitly specify the LTS file format")]
     filetype: Option<LtsFormat>,
 
+    /// Specify the output LTS, the format is derived from the extension.
     output: Option<PathBuf>,
 
+    #[arg(
+        long,
+        help = "Explicitly specify the LTS output file format, defaults to the .aut format when writing to stdout"
+    )]
+    output_filetype: Option<LtsFormat>,
+
     #[arg(
         short,
         long,
@@ -101,6 +145,12 @@ struct CompareArgs {
     #[arg(long, help = "Explicitly specify the LTS file format")]
     filetype: Option<LtsFormat>,
 
+    #[arg(
+        long,
+        help = "Print a formula that distinguishes the LTSs when they are not equivalent, only for strong and branching bisimulation"
+    )]
+    counterexample: bool,
+
     #[arg(
         short,
         long,
@@ -134,6 +184,152 @@ struct ConvertArgs {
     tau: Option<Vec<String>>,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(about = "Renames, hides, blocks and allows the actions of the given LTS, in that order")]
+struct TransformArgs {
+    #[arg(long, help = "Explicitly specify the LTS input file format")]
+    input_filetype: Option<LtsFormat>,
+
+    /// Specify the input LTS.
+    filename: PathBuf,
+
+    #[arg(
+        long,
+        help = "Explicitly specify the LTS output file format, defaults to the .aut format when writing to stdout"
+    )]
+    output_filetype: Option<LtsFormat>,
+
+    /// Specify the output LTS, the format is derived from the extension.
+    output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Rename the actions according to the rules of the given mCRL2 action rename (.arn) file"
+    )]
+    rename: Option<PathBuf>,
+
+    #[arg(long, help = "List of action names that should be hidden", value_delimiter = ',')]
+    hide: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Hide the actions of which the name matches the given regular expression"
+    )]
+    hide_regex: Option<String>,
+
+    #[arg(long, help = "List of action names that should be blocked", value_delimiter = ',')]
+    block: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "List of multi-action names, such as a|b, that are allowed, all other visible multi-actions are blocked",
+        value_delimiter = ','
+    )]
+    allow: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Remove the states that are not reachable from the initial state afterwards"
+    )]
+    remove_unreachable: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(
+    about = "Composes the given component LTSs in parallel using compositional minimisation, where the intermediate products are reduced"
+)]
+struct ComposeArgs {
+    /// A file containing the composition, such as `hide({c}, allow({c}, comm({a|b -> c}, P || Q)))`.
+    specification: PathBuf,
+
+    /// The component LTSs, in the order of the parallel composition.
+    #[arg(required = true)]
+    components: Vec<PathBuf>,
+
+    #[arg(long, help = "Explicitly specify the LTS input file format of all components")]
+    input_filetype: Option<LtsFormat>,
+
+    #[arg(
+        long,
+        short,
+        help = "Specify the output LTS, the format is derived from the extension"
+    )]
+    output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Explicitly specify the LTS output file format, defaults to the .aut format when writing to stdout"
+    )]
+    output_filetype: Option<LtsFormat>,
+
+    #[arg(
+        long,
+        default_value = "branching-bisim",
+        help = "The equivalence modulo which the intermediate products are reduced, which must be a congruence"
+    )]
+    equivalence: Equivalence,
+}
+
+/// The queries that can be checked by the check subcommand.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum Query {
+    /// A reachable state without outgoing transitions.
+    Deadlock,
+    /// A reachable state on a cycle of internal transitions.
+    Livelock,
+    /// A reachable transition labelled by one of the given actions.
+    Action,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(
+    about = "Checks whether the given LTS has a deadlock, a tau-livelock or a reachable action, and prints a shortest trace"
+)]
+struct CheckArgs {
+    query: Query,
+
+    /// Specify the input LTS.
+    filename: PathBuf,
+
+    #[arg(long, help = "Explicitly specify the LTS file format")]
+    filetype: Option<LtsFormat>,
+
+    #[arg(
+        long,
+        required_if_eq("query", "action"),
+        value_delimiter = ',',
+        help = "List of action names that should be reached by the action query"
+    )]
+    action: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Write the trace to the witness as an LTS in the .aut format to the given file"
+    )]
+    trace_output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(
+    about = "Checks whether the given modal mu-calculus formula holds in the initial state of the given LTS, and prints true or false"
+)]
+struct CheckFormulaArgs {
+    /// Specify the input LTS.
+    filename: PathBuf,
+
+    /// A file containing the modal formula, such as `nu X. ([true]X && <true>true)`.
+    formula_filename: PathBuf,
+
+    #[arg(long, help = "Explicitly specify the LTS file format")]
+    filetype: Option<LtsFormat>,
+
+    #[arg(
+        long,
+        help = "Write the witness or counterexample as an LTS in the .aut format to the given file"
+    )]
+    evidence_output: Option<PathBuf>,
+}
+
 #[derive(clap::Args, Debug)]
 #[command(
     about = "Checks whether the given implementation LTS refines the given specification LTS modulo various preorders."
@@ -150,6 +346,16 @@ struct RefinesArgs {
 
     #[arg(long, help = "Explicitly specify the LTS file format")]
     filetype: Option<LtsFormat>,
+
+    #[arg(long, help = "Print a counter example when the refinement does not hold")]
+    counterexample: bool,
+
+    #[arg(
+        long,
+        requires = "counterexample",
+        help = "Write the trace of the counter example as an LTS in the .aut format to the given file"
+    )]
+    counterexample_output: Option<PathBuf>,
 }
 
 fn main() -> Result<ExitCode, MercError> {
@@ -170,19 +376,31 @@ fn main() -> Result<ExitCode, MercError> {
     if let Some(command) = &cli.commands {
         match command {
             Commands::Info(args) => {
-                handle_info(args, &mut timing)?;
+                handle_info(&cli, args, &mut timing)?;
             }
             Commands::Reduce(args) => {
-                handle_reduce(args, &mut timing)?;
+                handle_reduce(&cli, args, &mut timing)?;
             }
             Commands::Compare(args) => {
-                handle_compare(args, &mut timing)?;
+                handle_compare(&cli, args, &mut timing)?;
             }
             Commands::Refines(args) => {
-                handle_refinement(args, &mut timing)?;
+                handle_refinement(&cli, args, &mut timing)?;
             }
             Commands::Convert(args) => {
-                handle_convert(args, &mut timing)?;
+                handle_convert(&cli, args, &mut timing)?;
+            }
+            Commands::Transform(args) => {
+                handle_transform(&cli, args, &mut timing)?;
+            }
+            Commands::Compose(args) => {
+                handle_compose(&cli, args, &mut timing)?;
+            }
+            Commands::Check(args) => {
+                handle_check(&cli, args, &mut timing)?;
+            }
+            Commands::CheckFormula(args) => {
+                handle_check_formula(&cli, args, &mut timing)?;
             }
         }
     }
@@ -196,11 +414,11 @@ fn main() -> Result<ExitCode, MercError> {
 }
 
 /// Display information about the given LTS.
-fn handle_info(args: &InfoArgs, timing: &mut Timing) -> Result<(), MercError> {
+fn handle_info(cli: &Cli, args: &InfoArgs, timing: &mut Timing) -> Result<(), MercError> {
     let path = Path::new(&args.filename);
 
     let format = guess_lts_format_from_extension(path, args.filetype).ok_or("Unknown LTS file format.")?;
-    let lts = read_explicit_lts(path, format, Vec::new(), timing)?;
+    let lts = read_explicit_lts(path, format, Vec::new(), cli.threads, timing)?;
     println!(
         "LTS has {} states and {} transitions.",
         LargeFormatter(lts.num_of_states()),
@@ -212,53 +430,92 @@ fn handle_info(args: &InfoArgs, timing: &mut Timing) -> Result<(), MercError> {
         for label in lts.labels() {
             println!("  {}", label);
         }
+
+        if let Some(state_labels) = lts.state_labels() {
+            println!("Parameters:");
+            for (name, sort) in state_labels.parameters() {
+                println!("  {name}: {sort}");
+            }
+
+            if args.state_labels {
+                println!("States:");
+                for state_index in lts.iter_states() {
+                    println!(
+                        "  {state_index}: {}",
+                        StateLabelFormatter(state_labels.values(state_index))
+                    );
+                }
+            } else {
+                println!(
+                    "Initial state: {}",
+                    StateLabelFormatter(state_labels.values(lts.initial_state_index()))
+                );
+            }
+        }
     });
 
     Ok(())
 }
 
 /// Reduce the given LTS into another LTS modulo any of the supported equivalences.
-fn handle_reduce(args: &ReduceArgs, timing: &mut Timing) -> Result<(), MercError> {
+fn handle_reduce(cli: &Cli, args: &ReduceArgs, timing: &mut Timing) -> Result<(), MercError> {
     let path = Path::new(&args.filename);
     let format = guess_lts_format_from_extension(path, args.filetype).ok_or("Unknown LTS file format.")?;
 
-    let lts = read_explicit_lts(path, format, args.tau.clone().unwrap_or_default(), timing)?;
+    let lts = read_explicit_lts(path, format, args.tau.clone().unwrap_or_default(), cli.threads, timing)?;
     info!(
         "LTS has {} states and {} transitions.",
         LargeFormatter(lts.num_of_states()),
         LargeFormatter(lts.num_of_transitions())
     );
 
-    apply_lts!(lts, timing, |lts, timing| -> Result<(), MercError> {
-        let reduced_lts = reduce_lts(lts, args.equivalence, timing);
+    let output_format = if let Some(output) = &args.output {
+        guess_lts_format_from_extension(output, args.output_filetype).ok_or("Unknown LTS file format.")?
+    } else {
+        args.output_filetype.unwrap_or(LtsFormat::Aut)
+    };
 
-        info!(
-            "Reduced LTS has {} states and {} transitions.",
-            LargeFormatter(reduced_lts.num_of_states()),
-            LargeFormatter(reduced_lts.num_of_transitions())
-        );
+    // The reduced LTS keeps the labels of the input, such that multi-actions are preserved.
+    let reduced_lts = match lts {
+        GenericLts::Aut(lts) => GenericLts::Aut(reduce(lts, cli, args, timing)),
+        GenericLts::Lts(lts) => GenericLts::Lts(reduce(lts, cli, args, timing)),
+        GenericLts::Bcg(lts) => GenericLts::Bcg(reduce(lts, cli, args, timing)),
+    };
 
-        if let Some(file) = &args.output {
-            let mut writer = File::create(file)?;
-            write_aut(&mut writer, &reduced_lts)?;
-        } else {
-            write_aut(&mut stdout(), &reduced_lts)?;
-        }
+    info!(
+        "Reduced LTS has {} states and {} transitions.",
+        LargeFormatter(reduced_lts.num_of_states()),
+        LargeFormatter(reduced_lts.num_of_transitions())
+    );
 
-        Ok(())
-    })?;
+    write_explicit_lts(reduced_lts, args.output.as_deref(), output_format, timing)
+}
 
-    Ok(())
+/// Reduces the given LTS modulo the equivalence given in the arguments.
+fn reduce<L: LTS + Sync>(
+    lts: L,
+    cli: &Cli,
+    args: &ReduceArgs,
+    timing: &mut Timing,
+) -> LabelledTransitionSystem<L::Label>
+where
+    L::Label: Send + Sync,
+{
+    if cli.threads > 1 {
+        reduce_lts_parallel(lts, args.equivalence, cli.threads, timing)
+    } else {
+        reduce_lts(lts, args.equivalence, timing)
+    }
 }
 
 /// Handles the refinement checking between two LTSs.
-fn handle_refinement(args: &RefinesArgs, timing: &mut Timing) -> Result<(), MercError> {
+fn handle_refinement(cli: &Cli, args: &RefinesArgs, timing: &mut Timing) -> Result<(), MercError> {
     let impl_path = Path::new(&args.implementation_filename);
     let spec_path = Path::new(&args.specification_filename);
     let format = guess_lts_format_from_extension(impl_path, args.filetype).ok_or("Unknown LTS file format.")?;
 
-    let impl_lts = read_explicit_lts(impl_path, format, Vec::new(), timing)?;
-    let spec_lts = read_explicit_lts(spec_path, format, Vec::new(), timing)?;
+    let impl_lts = read_explicit_lts(impl_path, format, Vec::new(), cli.threads, timing)?;
+    let spec_lts = read_explicit_lts(spec_path, format, Vec::new(), cli.threads, timing)?;
 
     info!(
         "Implementation LTS has {} states and {} transitions.",
@@ -272,8 +529,8 @@ fn handle_refinement(args: &RefinesArgs, timing: &mut Timing) -> Result<(), Merc
     );
 
     let refines = apply_lts_pair!(impl_lts, spec_lts, timing, |left, right, timing| {
-        refines(left, right, args.refinement, timing)
-    });
+        check_refinement(left, right, args, timing)
+    })?;
 
     if refines {
         println!("true");
@@ -284,8 +541,37 @@ fn handle_refinement(args: &RefinesArgs, timing: &mut Timing) -> Result<(), Merc
     Ok(())
 }
 
+/// Checks the refinement, and prints the counter example when requested.
+fn check_refinement<L: LTS>(
+    impl_lts: L,
+    spec_lts: L,
+    args: &RefinesArgs,
+    timing: &mut Timing,
+) -> Result<bool, MercError> {
+    if !args.counterexample {
+        return Ok(refines(impl_lts, spec_lts, args.refinement, timing));
+    }
+
+    if args.refinement.is_simulation() {
+        return Err(format!("Counter examples are not supported for {:?}", args.refinement).into());
+    }
+
+    match refines_counter_example(impl_lts, spec_lts, args.refinement, timing) {
+        Ok(()) => Ok(true),
+        Err(counter_example) => {
+            println!("Counter example: {counter_example}");
+
+            if let Some(path) = &args.counterexample_output {
+                write_aut(&mut File::create(path)?, &counter_example.to_lts())?;
+            }
+
+            Ok(false)
+        }
+    }
+}
+
 /// Compares two LTSs for equivalence modulo any of the available equivalences.
-fn handle_compare(args: &CompareArgs, timing: &mut Timing) -> Result<(), MercError> {
+fn handle_compare(cli: &Cli, args: &CompareArgs, timing: &mut Timing) -> Result<(), MercError> {
     let format =
         guess_lts_format_from_extension(&args.left_filename, args.filetype).ok_or("Unknown LTS file format.")?;
 
@@ -294,12 +580,14 @@ fn handle_compare(args: &CompareArgs, timing: &mut Timing) -> Result<(), MercErr
         &args.left_filename,
         format,
         args.tau.clone().unwrap_or_default(),
+        cli.threads,
         timing,
     )?;
     let right_lts = read_explicit_lts(
         &args.right_filename,
         format,
         args.tau.clone().unwrap_or_default(),
+        cli.threads,
         timing,
     )?;
 
@@ -315,8 +603,8 @@ fn handle_compare(args: &CompareArgs, timing: &mut Timing) -> Result<(), MercErr
     );
 
     let equivalent = apply_lts_pair!(left_lts, right_lts, timing, |left, right, timing| {
-        merc_reduction::compare_lts(args.equivalence, left, right, timing)
-    });
+        check_equivalence(left, right, args, timing)
+    })?;
 
     if equivalent {
         println!("true");
@@ -327,11 +615,32 @@ fn handle_compare(args: &CompareArgs, timing: &mut Timing) -> Result<(), MercErr
     Ok(())
 }
 
+/// Checks the equivalence, and prints a distinguishing formula when requested.
+fn check_equivalence<L: LTS>(left: L, right: L, args: &CompareArgs, timing: &mut Timing) -> Result<bool, MercError> {
+    if !args.counterexample {
+        return Ok(compare_lts(args.equivalence, left, right, timing));
+    }
+
+    match distinguishing_formula(args.equivalence, left, right, timing)? {
+        None => Ok(true),
+        Some(formula) => {
+            println!("Distinguishing formula: {formula}");
+            Ok(false)
+        }
+    }
+}
+
 /// Converts an LTS from one format to another, does not do any reduction, see [handle_reduce] for that.
-fn handle_convert(args: &ConvertArgs, timing: &mut Timing) -> Result<(), MercError> {
+fn handle_convert(cli: &Cli, args: &ConvertArgs, timing: &mut Timing) -> Result<(), MercError> {
     let format =
         guess_lts_format_from_extension(&args.filename, args.input_filetype).ok_or("Unknown LTS file format.")?;
-    let input_lts = read_explicit_lts(&args.filename, format, args.tau.clone().unwrap_or_default(), timing)?;
+    let input_lts = read_explicit_lts(
+        &args.filename,
+        format,
+        args.tau.clone().unwrap_or_default(),
+        cli.threads,
+        timing,
+    )?;
 
     let output_format = if let Some(output) = &args.output {
         guess_lts_format_from_extension(output, args.output_filetype).ok_or("Unknown LTS file format.")?
@@ -341,54 +650,234 @@ fn handle_convert(args: &ConvertArgs, timing: &mut Timing) -> Result<(), MercErr
         return Err("Either output path or output file format must be specified.".into());
     };
 
-    match input_lts {
-        GenericLts::Aut(lts) => match output_format {
-            LtsFormat::Bcg => {
-                if let Some(path) = &args.output {
-                    write_bcg(&lts, path)?;
-                } else {
-                    return Err("Output path must be specified when writing BCG files.".into());
-                }
-            }
-            LtsFormat::Aut => {
-                return Err("Conversion from AUT to AUT is not useful.".into());
-            }
-            _ => {
-                return Err(format!("Conversion to {output_format:?}LTS format is not yet implemented.").into());
-            }
-        },
-        GenericLts::Lts(lts) => match output_format {
-            LtsFormat::Aut => {
-                if let Some(path) = &args.output {
-                    write_aut(&mut File::create(path)?, &lts.relabel(|label| label.to_string()))?;
-                } else {
-                    write_aut(&mut stdout(), &lts.relabel(|label| label.to_string()))?;
-                }
-            }
-            LtsFormat::Bcg => {
-                if let Some(path) = &args.output {
-                    write_bcg(&lts.relabel(|label| label.to_string()), path)?;
-                } else {
-                    return Err("Output path must be specified when writing BCG files.".into());
-                }
-            }
-            LtsFormat::Lts => {
-                return Err("Conversion from LTS to LTS is not useful.".into());
-            }
-        },
-        GenericLts::Bcg(lts) => match output_format {
-            LtsFormat::Aut => {
-                if let Some(path) = &args.output {
-                    write_aut(&mut File::create(path)?, &lts)?;
-                } else {
-                    write_aut(&mut stdout(), &lts)?;
-                }
-            }
-            _ => {
-                return Err(format!("Conversion to {output_format:?}LTS format is not yet implemented.").into());
+    write_explicit_lts(input_lts, args.output.as_deref(), output_format, timing)
+}
+
+/// Applies the action transformations given in the arguments to an LTS.
+fn handle_transform(cli: &Cli, args: &TransformArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let format =
+        guess_lts_format_from_extension(&args.filename, args.input_filetype).ok_or("Unknown LTS file format.")?;
+    let lts = read_explicit_lts(&args.filename, format, Vec::new(), cli.threads, timing)?;
+    info!(
+        "LTS has {} states and {} transitions.",
+        LargeFormatter(lts.num_of_states()),
+        LargeFormatter(lts.num_of_transitions())
+    );
+
+    let output_format = if let Some(output) = &args.output {
+        guess_lts_format_from_extension(output, args.output_filetype).ok_or("Unknown LTS file format.")?
+    } else {
+        args.output_filetype.unwrap_or(LtsFormat::Aut)
+    };
+
+    let rename_specification = match &args.rename {
+        Some(path) => Some(UntypedActionRenameSpec::parse(&read_to_string(path)?)?),
+        None => None,
+    };
+
+    let transformed_lts = match lts {
+        GenericLts::Aut(lts) => GenericLts::Aut(transform(lts, args, rename_specification.as_ref(), timing)?),
+        GenericLts::Lts(lts) => GenericLts::Lts(transform(lts, args, rename_specification.as_ref(), timing)?),
+        GenericLts::Bcg(lts) => GenericLts::Bcg(transform(lts, args, rename_specification.as_ref(), timing)?),
+    };
+
+    info!(
+        "Transformed LTS has {} states and {} transitions.",
+        LargeFormatter(transformed_lts.num_of_states()),
+        LargeFormatter(transformed_lts.num_of_transitions())
+    );
+
+    write_explicit_lts(transformed_lts, args.output.as_deref(), output_format, timing)
+}
+
+/// Renames, hides, blocks and allows the actions of the given LTS, and optionally removes the unreachable states.
+fn transform<L: TransitionLabel>(
+    mut lts: LabelledTransitionSystem<L>,
+    args: &TransformArgs,
+    rename_specification: Option<&UntypedActionRenameSpec>,
+    timing: &mut Timing,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let mut time = timing.start("transform");
+
+    if let Some(specification) = rename_specification {
+        lts = rename_actions(lts, specification)?;
+    }
+
+    if let Some(names) = &args.hide {
+        lts = hide_actions(lts, &ActionFilter::Names(names.clone()))?;
+    }
+
+    if let Some(pattern) = &args.hide_regex {
+        lts = hide_actions(lts, &ActionFilter::regex(pattern)?)?;
+    }
+
+    if let Some(names) = &args.block {
+        lts = block_actions(lts, &ActionFilter::Names(names.clone()))?;
+    }
+
+    if let Some(allowed) = &args.allow {
+        lts = allow_actions(lts, allowed)?;
+    }
+
+    if args.remove_unreachable {
+        lts = remove_unreachable_states(lts);
+    }
+
+    time.finish();
+    Ok(lts)
+}
+
+/// Composes the component LTSs according to the specification, reducing the intermediate products.
+fn handle_compose(cli: &Cli, args: &ComposeArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let specification = CompositionSpecification::parse(&read_to_string(&args.specification)?)?;
+
+    let mut components = Vec::with_capacity(args.components.len());
+    for path in &args.components {
+        let format = guess_lts_format_from_extension(path, args.input_filetype).ok_or("Unknown LTS file format.")?;
+        let lts = read_explicit_lts(path, format, Vec::new(), cli.threads, timing)?;
+        info!(
+            "Component {} has {} states and {} transitions.",
+            path.display(),
+            LargeFormatter(lts.num_of_states()),
+            LargeFormatter(lts.num_of_transitions())
+        );
+        components.push(lts);
+    }
+
+    let output_format = if let Some(output) = &args.output {
+        guess_lts_format_from_extension(output, args.output_filetype).ok_or("Unknown LTS file format.")?
+    } else {
+        args.output_filetype.unwrap_or(LtsFormat::Aut)
+    };
+
+    // The labels of all components must be of the same type, which is determined by the first component.
+    let result = match components.first() {
+        Some(GenericLts::Lts(_)) => GenericLts::Lts(compose(
+            components,
+            |lts| match lts {
+                GenericLts::Lts(lts) => Some(lts),
+                _ => None,
+            },
+            &specification,
+            args.equivalence,
+            timing,
+        )?),
+        _ => GenericLts::Aut(compose(
+            components,
+            |lts| match lts {
+                GenericLts::Aut(lts) | GenericLts::Bcg(lts) => Some(lts),
+                GenericLts::Lts(_) => None,
+            },
+            &specification,
+            args.equivalence,
+            timing,
+        )?),
+    };
+
+    info!(
+        "Composed LTS has {} states and {} transitions.",
+        LargeFormatter(result.num_of_states()),
+        LargeFormatter(result.num_of_transitions())
+    );
+
+    write_explicit_lts(result, args.output.as_deref(), output_format, timing)
+}
+
+/// Applies smart reduction to the components, which must all be converted by the given function.
+fn compose<L: TransitionLabel>(
+    components: Vec<GenericLts>,
+    convert: impl Fn(GenericLts) -> Option<LabelledTransitionSystem<L>>,
+    specification: &CompositionSpecification,
+    equivalence: Equivalence,
+    timing: &mut Timing,
+) -> Result<LabelledTransitionSystem<L>, MercError> {
+    let components = components
+        .into_iter()
+        .map(|lts| convert(lts).ok_or("All components must be given in formats with the same kind of labels."))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    smart_reduction(components, specification, equivalence, timing)
+}
+
+/// Checks the query on the given LTS, and prints the witness when it is found.
+fn handle_check(cli: &Cli, args: &CheckArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let format = guess_lts_format_from_extension(&args.filename, args.filetype).ok_or("Unknown LTS file format.")?;
+    let lts = read_explicit_lts(&args.filename, format, Vec::new(), cli.threads, timing)?;
+    info!(
+        "LTS has {} states and {} transitions.",
+        LargeFormatter(lts.num_of_states()),
+        LargeFormatter(lts.num_of_transitions())
+    );
+
+    apply_lts!(lts, timing, |lts, timing| check(lts, args, timing))
+}
+
+/// Searches for a witness of the query, and prints its trace.
+fn check<L: LTS>(lts: L, args: &CheckArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let mut time = timing.start("check");
+    let witness: Option<Witness<L::Label>> = match args.query {
+        Query::Deadlock => find_deadlock(&lts),
+        Query::Livelock => find_livelock(&lts),
+        Query::Action => find_action(
+            &lts,
+            &ActionFilter::Names(args.action.clone().ok_or("The action query requires --action.")?),
+        ),
+    };
+    time.finish();
+
+    let description = match args.query {
+        Query::Deadlock => "deadlock",
+        Query::Livelock => "livelock",
+        Query::Action => "reachable action",
+    };
+
+    match witness {
+        Some(witness) => {
+            println!("Found a {description} at {witness}");
+
+            if let Some(path) = &args.trace_output {
+                write_aut(&mut File::create(path)?, &witness.to_lts())?;
             }
-        },
+        }
+        None => println!("No {description} found"),
     }
 
     Ok(())
 }
+
+/// Checks the modal formula on the given LTS, and writes the evidence when requested.
+fn handle_check_formula(cli: &Cli, args: &CheckFormulaArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let formula_spec = UntypedStateFrmSpec::parse(&read_to_string(&args.formula_filename).map_err(|e| {
+        MercError::from(format!(
+            "Could not open formula file '{}': {}",
+            args.formula_filename.display(),
+            e
+        ))
+    })?)?;
+    if !formula_spec.action_declarations.is_empty() {
+        return Err(MercError::from("We do not support formulas with action declarations."));
+    }
+
+    if !formula_spec.data_specification.is_empty() {
+        return Err(MercError::from("The formula must not contain a data specification."));
+    }
+
+    let format = guess_lts_format_from_extension(&args.filename, args.filetype).ok_or("Unknown LTS file format.")?;
+    let lts = read_explicit_lts(&args.filename, format, Vec::new(), cli.threads, timing)?;
+    info!(
+        "LTS has {} states and {} transitions.",
+        LargeFormatter(lts.num_of_states()),
+        LargeFormatter(lts.num_of_transitions())
+    );
+
+    apply_lts!(lts, timing, |lts, timing| {
+        let (holds, evidence) = check_formula(lts, &formula_spec.formula, timing)?;
+        println!("{holds}");
+
+        if let Some(path) = &args.evidence_output {
+            write_aut(&mut File::create(path)?, &evidence)?;
+        }
+
+        Ok(())
+    })
+}
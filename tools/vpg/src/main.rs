@@ -24,23 +24,41 @@ use merc_unsafety::print_allocator_metrics;
 use merc_utilities::MercError;
 use merc_utilities::Timing;
 use merc_vpg::FeatureDiagram;
+use merc_vpg::PG;
+use merc_vpg::ParityGame;
 use merc_vpg::ParityGameFormat;
+use merc_vpg::ParityGameSolver;
 use merc_vpg::PgDot;
 use merc_vpg::Player;
+use merc_vpg::VariabilityParityGame;
 use merc_vpg::VpgDot;
 use merc_vpg::ZielonkaVariant;
 use merc_vpg::compute_reachable;
 use merc_vpg::guess_format_from_extension;
+use merc_vpg::lift_solution;
+use merc_vpg::lift_strategy;
+use merc_vpg::lift_variability_solution;
+use merc_vpg::lift_variability_strategy;
 use merc_vpg::project_variability_parity_games_iter;
 use merc_vpg::read_fts;
 use merc_vpg::read_pg;
 use merc_vpg::read_vpg;
+use merc_vpg::simplify_parity_game;
+use merc_vpg::simplify_variability_parity_game;
+use merc_vpg::solve_parity_game;
+use merc_vpg::solve_parity_game_scc;
+use merc_vpg::solve_variability_parity_game_scc;
 use merc_vpg::solve_variability_product_zielonka;
-use merc_vpg::solve_variability_zielonka;
+use merc_vpg::solve_variability_zielonka_strategy;
 use merc_vpg::solve_zielonka;
+use merc_vpg::solve_zielonka_parallel;
 use merc_vpg::translate;
+use merc_vpg::verify_strategy;
+use merc_vpg::verify_variability_strategy;
 use merc_vpg::write_pg;
+use merc_vpg::write_pg_solution;
 use merc_vpg::write_vpg;
+use merc_vpg::write_vpg_solution;
 
 /// Default node capacity for the Oxidd decision diagram manager.
 const DEFAULT_OXIDD_NODE_CAPACITY: usize = 2024;
@@ -77,6 +95,7 @@ struct Cli {
 enum Commands {
     Solve(SolveArgs),
     Reachable(ReachableArgs),
+    Simplify(SimplifyArgs),
     Project(ProjectArgs),
     Translate(TranslateArgs),
     Display(DisplayArgs),
@@ -91,6 +110,10 @@ struct SolveArgs {
     #[arg(long)]
     format: Option<ParityGameFormat>,
 
+    /// The algorithm used to solve standard parity games.
+    #[arg(long, value_enum, default_value_t = ParityGameSolver::Zielonka)]
+    solver: ParityGameSolver,
+
     /// For variability parity games there are several ways for solving.
     #[arg(long)]
     solve_variant: Option<ZielonkaVariant>,
@@ -102,6 +125,18 @@ struct SolveArgs {
     /// Whether to verify the solution after computing it
     #[arg(long, default_value_t = false)]
     verify_solution: bool,
+
+    /// Write the winners and the winning strategies of all vertices to the given file
+    #[arg(long)]
+    strategy: Option<String>,
+
+    /// The number of threads used to compute the attractors of the Zielonka solver for standard parity games
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
+    /// Simplify the game before solving it, and solve its strongly connected components bottom-up, except for the product-based variant
+    #[arg(long, default_value_t = false)]
+    simplify: bool,
 }
 
 /// Arguments for computing the reachable part of a parity game
@@ -115,6 +150,17 @@ struct ReachableArgs {
     format: Option<ParityGameFormat>,
 }
 
+/// Arguments for simplifying a parity game
+#[derive(clap::Args, Debug)]
+struct SimplifyArgs {
+    filename: String,
+
+    output: String,
+
+    #[arg(long, short)]
+    format: Option<ParityGameFormat>,
+}
+
 /// Arguments for projecting a variability parity game
 #[derive(clap::Args, Debug)]
 struct ProjectArgs {
@@ -178,6 +224,7 @@ fn main() -> Result<ExitCode, MercError> {
         match command {
             Commands::Solve(args) => handle_solve(&cli, args, &mut timing)?,
             Commands::Reachable(args) => handle_reachable(&cli, args, &mut timing)?,
+            Commands::Simplify(args) => handle_simplify(&cli, args, &mut timing)?,
             Commands::Project(args) => handle_project(&cli, args, &mut timing)?,
             Commands::Translate(args) => handle_translate(&cli, args)?,
             Commands::Display(args) => handle_display(&cli, args, &mut timing)?,
@@ -199,7 +246,7 @@ fn main() -> Result<ExitCode, MercError> {
 ///
 /// Reads either a standard parity game (PG) or a variability parity game (VPG)
 /// based on the provided format or filename extension, then solves it using
-/// Zielonka's algorithm.
+/// the selected solver, or Zielonka's algorithm for variability parity games.
 fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(), MercError> {
     let path = Path::new(&args.filename);
     let mut file = File::open(path)?;
@@ -211,8 +258,47 @@ fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(),
         let game = read_pg(&mut file)?;
         time_read.finish();
 
-        let mut time_solve = timing.start("solve_zielonka");
-        let solution = solve_zielonka(&game);
+        if !args.solver.computes_strategy() && args.strategy.is_some() {
+            return Err(format!("The {:?} solver does not compute winning strategies.", args.solver).into());
+        }
+
+        if args.threads > 1 && args.solver != ParityGameSolver::Zielonka {
+            return Err("Only the Zielonka solver can use multiple threads.".into());
+        }
+
+        let solve = |game: &ParityGame| {
+            if args.threads > 1 {
+                let (solution, strategy) = solve_zielonka_parallel(game, args.threads);
+                (solution, Some(strategy))
+            } else {
+                solve_parity_game(game, args.solver)
+            }
+        };
+
+        let simplified = if args.simplify {
+            let mut time_simplify = timing.start("simplify");
+            let (simplified, simplification) = simplify_parity_game(&game);
+            time_simplify.finish();
+
+            info!(
+                "Simplified the parity game from {} to {} vertices",
+                game.num_of_vertices(),
+                simplified.num_of_vertices()
+            );
+            Some((simplified, simplification))
+        } else {
+            None
+        };
+
+        let mut time_solve = timing.start("solve_parity_game");
+        let (solution, strategy) = if let Some((simplified, simplification)) = &simplified {
+            let (solution, strategy) = solve_parity_game_scc(simplified, solve);
+            let solution = lift_solution(simplification, &solution);
+            let strategy = strategy.map(|strategy| lift_strategy(&game, simplification, &solution, &strategy));
+            (solution, strategy)
+        } else {
+            solve(&game)
+        };
         if args.full_solution {
             for (index, player_set) in solution.iter().enumerate() {
                 println!("W{index}: {}", player_set.iter_ones().format(", "));
@@ -223,10 +309,35 @@ fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(),
             println!("{}", Player::Odd.solution())
         }
         time_solve.finish();
+
+        if let (Some(path), Some(strategy)) = (&args.strategy, &strategy) {
+            write_pg_solution(File::create(path)?, &game, &solution, strategy)?;
+        }
+
+        if args.verify_solution {
+            if let Some(strategy) = &strategy {
+                verify_strategy(&game, &solution, strategy)?;
+            } else if solution != solve_zielonka(&game) {
+                return Err("The solution differs from the solution computed by Zielonka".into());
+            }
+        }
     } else {
+        if args.solver != ParityGameSolver::Zielonka {
+            return Err("Variability parity games can only be solved using the Zielonka algorithm.".into());
+        }
+
+        if args.threads > 1 {
+            return Err(
+                "Variability parity games are solved using a single thread, see --oxidd-workers instead.".into(),
+            );
+        }
+
         let solve_variant = args
             .solve_variant
             .ok_or("For variability parity game solving a solving strategy should be selected")?;
+        if solve_variant == ZielonkaVariant::Product && args.strategy.is_some() {
+            return Err("The product-based variant does not compute winning strategies.".into());
+        }
 
         // Read and solve a variability parity game.
         let manager_ref = oxidd::bdd::new_manager(
@@ -246,12 +357,32 @@ fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(),
             game
         };
 
+        let simplified = if args.simplify {
+            let mut time_simplify = timing.start("simplify");
+            let (simplified, simplification) = simplify_variability_parity_game(&manager_ref, &game)?;
+            time_simplify.finish();
+
+            info!(
+                "Simplified the variability parity game from {} to {} vertices",
+                game.num_of_vertices(),
+                simplified.num_of_vertices()
+            );
+            Some((simplified, simplification))
+        } else {
+            None
+        };
+        let solving_game = simplified.as_ref().map_or(&game, |(simplified, _)| simplified);
+
         let mut time_solve = timing.start("solve_variability_zielonka");
         if solve_variant == ZielonkaVariant::Product {
             // Since we want to print W0, W1 separately, we need to store the results temporarily.
             let mut results = [Vec::new(), Vec::new()];
-            for result in solve_variability_product_zielonka(&game, timing) {
+            for result in solve_variability_product_zielonka(solving_game, timing) {
                 let (cube, _bdd, solution) = result?;
+                let solution = match &simplified {
+                    Some((_, simplification)) => lift_solution(simplification, &solution),
+                    None => solution,
+                };
 
                 for (index, w) in solution.iter().enumerate() {
                     results[index].push((cube.clone(), w.clone()));
@@ -273,7 +404,20 @@ fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(),
                 }
             }
         } else {
-            let solutions = solve_variability_zielonka(&manager_ref, &game, solve_variant, false)?;
+            let solve = |game: &VariabilityParityGame| {
+                solve_variability_zielonka_strategy(&manager_ref, game, solve_variant, false)
+            };
+
+            let (solutions, strategy) = match &simplified {
+                Some((simplified, simplification)) => {
+                    let (solutions, strategy) = solve_variability_parity_game_scc(&manager_ref, simplified, solve)?;
+                    (
+                        lift_variability_solution(&manager_ref, simplification, &solutions),
+                        lift_variability_strategy(&manager_ref, &game, simplification, &strategy)?,
+                    )
+                }
+                None => solve(&game)?,
+            };
             for (index, w) in solutions.iter().enumerate() {
                 println!("W{index}: ");
 
@@ -292,8 +436,13 @@ fn handle_solve(cli: &Cli, args: &SolveArgs, timing: &mut Timing) -> Result<(),
                 }
             }
 
+            if let Some(path) = &args.strategy {
+                write_vpg_solution(&mut File::create(path)?, &game, &solutions, &strategy)?;
+            }
+
             if args.verify_solution {
                 verify_variability_product_zielonka_solution(&game, &solutions, timing)?;
+                verify_variability_strategy(&game, &solutions, &strategy, timing)?;
             }
         }
         time_solve.finish();
@@ -357,6 +506,73 @@ fn handle_reachable(cli: &Cli, args: &ReachableArgs, timing: &mut Timing) -> Res
     Ok(())
 }
 
+/// Handle the `simplify` subcommand.
+///
+/// Reads a PG or VPG, simplifies it while preserving the winners of all
+/// vertices, and writes the simplified game to `output`. Also logs the vertex
+/// index mapping to aid inspection.
+fn handle_simplify(cli: &Cli, args: &SimplifyArgs, timing: &mut Timing) -> Result<(), MercError> {
+    let path = Path::new(&args.filename);
+    let mut file = File::open(path)?;
+
+    let format = guess_format_from_extension(path, args.format).ok_or("Unknown parity game file format.")?;
+
+    match format {
+        ParityGameFormat::PG => {
+            let mut time_read = timing.start("read_pg");
+            let game = read_pg(&mut file)?;
+            time_read.finish();
+
+            let mut time_simplify = timing.start("simplify");
+            let (simplified, simplification) = simplify_parity_game(&game);
+            time_simplify.finish();
+
+            for (old_index, new_index) in simplification.mapping().iter().enumerate() {
+                debug!("{} -> {:?}", old_index, new_index);
+            }
+
+            info!(
+                "Simplified the parity game from {} to {} vertices",
+                game.num_of_vertices(),
+                simplified.num_of_vertices()
+            );
+
+            let mut output_file = File::create(&args.output)?;
+            write_pg(&mut output_file, &simplified)?;
+        }
+        ParityGameFormat::VPG => {
+            let manager_ref = oxidd::bdd::new_manager(
+                cli.oxidd_node_capacity,
+                cli.oxidd_cache_capacity.unwrap_or(cli.oxidd_node_capacity),
+                cli.oxidd_workers,
+            );
+
+            let mut time_read = timing.start("read_vpg");
+            let game = read_vpg(&manager_ref, &mut file)?;
+            time_read.finish();
+
+            let mut time_simplify = timing.start("simplify_vpg");
+            let (simplified, simplification) = simplify_variability_parity_game(&manager_ref, &game)?;
+            time_simplify.finish();
+
+            for (old_index, new_index) in simplification.mapping().iter().enumerate() {
+                debug!("{} -> {:?}", old_index, new_index);
+            }
+
+            info!(
+                "Simplified the variability parity game from {} to {} vertices",
+                game.num_of_vertices(),
+                simplified.num_of_vertices()
+            );
+
+            let mut output_file = File::create(&args.output)?;
+            write_vpg(&mut output_file, &simplified)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Compute all the projects of a variability parity game and write them to output.
 fn handle_project(cli: &Cli, args: &ProjectArgs, timing: &mut Timing) -> Result<(), MercError> {
     let path = Path::new(&args.filename);